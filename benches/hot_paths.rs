@@ -0,0 +1,79 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use gomoku::goban::{Move, Player, Position, Stone};
+use gomoku::gomoku::{CacheRetention, Gomoku, PositionSetup, SearchLimits};
+use gomoku::threat_evaluator::ThreatEvaluator;
+
+// A middlegame with threats of both sides, black to move
+const MIDDLEGAME: &str = "J10 K11 K10 J11 L11 M12 L9 L10 K8 M10";
+
+fn middlegame() -> Gomoku {
+    let mut gomoku = Gomoku::default();
+    let setup: PositionSetup = MIDDLEGAME.parse().unwrap();
+
+    gomoku.set_position(setup, Stone::Black).unwrap();
+    gomoku
+}
+
+fn evaluate(c: &mut Criterion) {
+    let goban = middlegame().board().clone();
+    let mut evaluator = ThreatEvaluator::new();
+
+    c.bench_function("evaluate", |bencher| {
+        bencher.iter(|| black_box(&goban).evaluate(&mut evaluator, Player::Opponent))
+    });
+
+    c.bench_function("evaluate_lines", |bencher| {
+        bencher.iter(|| black_box(&goban).evaluate_lines(Player::Opponent))
+    });
+}
+
+fn extract_patterns(c: &mut Criterion) {
+    let goban = middlegame().board().clone();
+    let evaluator = ThreatEvaluator::new();
+
+    c.bench_function("extract_patterns", |bencher| {
+        bencher.iter(|| evaluator.extract_patterns(black_box(goban.stones(Stone::Black)), goban.stones(Stone::White)))
+    });
+}
+
+fn get_limited_moves(c: &mut Criterion) {
+    let goban = middlegame().board().clone();
+
+    c.bench_function("get_limited_moves", |bencher| {
+        bencher.iter(|| black_box(&goban).get_limited_moves(2))
+    });
+}
+
+fn apply_move(c: &mut Criterion) {
+    let goban = middlegame().board().clone();
+    let played = Move::new(Stone::Black, Position::new(10, 12));
+
+    c.bench_function("apply_move", |bencher| {
+        bencher.iter(|| {
+            let mut child = goban.clone();
+
+            child.apply_move(black_box(played));
+            child.get_hash()
+        })
+    });
+
+    c.bench_function("get_canonical_hash", |bencher| {
+        bencher.iter(|| black_box(&goban).get_canonical_hash())
+    });
+}
+
+fn shallow_search(c: &mut Criterion) {
+    let mut gomoku = middlegame();
+
+    c.bench_function("search_depth_2", |bencher| {
+        bencher.iter(|| {
+            gomoku.new_game(CacheRetention::Clear);
+            gomoku.set_position(MIDDLEGAME.parse().unwrap(), Stone::Black).unwrap();
+            gomoku.suggest_move(SearchLimits::new(2))
+        })
+    });
+}
+
+criterion_group!(hot_paths, evaluate, extract_patterns, get_limited_moves, apply_move, shallow_search);
+criterion_main!(hot_paths);
@@ -1,23 +1,31 @@
-#![feature(test)]
-extern crate test;
+use criterion::{criterion_group, criterion_main, Criterion};
 
 use gomoku::goban::{Player, Position};
-use gomoku::gomoku::Gomoku;
+use gomoku::gomoku::{CacheRetention, Gomoku, SearchLimits};
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use test::Bencher;
+fn minmax(c: &mut Criterion) {
+    let mut gomoku: Gomoku = Gomoku::default();
 
-    #[bench]
-    fn minmax(bencher: &mut Bencher) {
-        let mut gomoku: Gomoku = Gomoku::default();
+    c.bench_function("minmax", |bencher| {
+        bencher.iter(|| {
+            gomoku.new_game(CacheRetention::Clear);
 
-        gomoku.play(Position { row: 3, col: 3 }, Player::Opponent);
-        gomoku.play(Position { row: 4, col: 4 }, Player::Opponent);
-        gomoku.play(Position { row: 5, col: 5 }, Player::Opponent);
-        gomoku.play(Position { row: 7, col: 7 }, Player::Opponent);
+            gomoku.play(Position { row: 3, col: 3 }, Player::Opponent).ok();
+            gomoku.play(Position { row: 3, col: 4 }, Player::Computer).ok();
+            gomoku.play(Position { row: 4, col: 4 }, Player::Opponent).ok();
+            gomoku.play(Position { row: 4, col: 5 }, Player::Computer).ok();
+            gomoku.play(Position { row: 5, col: 5 }, Player::Opponent).ok();
+            gomoku.play(Position { row: 5, col: 6 }, Player::Computer).ok();
+            gomoku.play(Position { row: 7, col: 7 }, Player::Opponent).ok();
 
-        bencher.iter(|| gomoku.play_computer_move(4));
-    }
+            gomoku.play_computer_move(SearchLimits::new(4))
+        })
+    });
 }
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = minmax
+}
+criterion_main!(benches);
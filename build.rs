@@ -0,0 +1,16 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    // protox compiles the service definition, so building it does not need protoc
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=proto/gomoku.proto");
+
+        let descriptors = protox::compile(["proto/gomoku.proto"], ["proto"]).expect("proto/gomoku.proto compiles");
+
+        tonic_build::configure()
+            .build_client(false)
+            .compile_fds(descriptors)
+            .expect("gRPC code generates");
+    }
+}
@@ -0,0 +1,23 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use gomoku::goban::{CoordinateStyle, Position, GOBAN_SIZE};
+
+// Whatever the input, parsing returns a position on the board or an error, and the
+// notation of a parsed position reads back as the same position in every style
+fuzz_target!(|data: &[u8]| {
+    let text = String::from_utf8_lossy(data);
+
+    for style in [CoordinateStyle::Letters, CoordinateStyle::LettersSkipI, CoordinateStyle::Numeric] {
+        if let Ok(position) = Position::from_coordinates_with(&text, style) {
+            assert!(position.row < GOBAN_SIZE && position.col < GOBAN_SIZE);
+
+            for notation_style in [CoordinateStyle::Letters, CoordinateStyle::LettersSkipI, CoordinateStyle::Numeric] {
+                let notation = position.to_notation(notation_style);
+
+                assert_eq!(Position::from_coordinates_with(&notation, notation_style), Ok(position));
+            }
+        }
+    }
+});
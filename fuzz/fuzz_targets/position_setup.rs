@@ -0,0 +1,27 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use gomoku::goban::Stone;
+use gomoku::gomoku::{GameState, Gomoku, PositionSetup};
+
+// A move list either fails to parse, is rejected by `set_position`, or sets up a position
+// holding exactly its stones
+fuzz_target!(|data: &[u8]| {
+    let text = String::from_utf8_lossy(data);
+    let moves = match text.parse::<PositionSetup>() {
+        Ok(PositionSetup::Moves(moves)) => moves,
+        _ => return,
+    };
+    let mut gomoku = Gomoku::with_hash_size(1);
+
+    for side in [Stone::Black, Stone::White] {
+        if let Ok(state) = gomoku.set_position(PositionSetup::Moves(moves.clone()), side) {
+            let board = gomoku.board();
+
+            assert_eq!(board.count(Stone::Black) + board.count(Stone::White), moves.len());
+            assert_eq!(board.side_to_move(), side);
+            assert_eq!(state == GameState::InProgress, !gomoku.legal_moves().is_empty());
+        }
+    }
+});
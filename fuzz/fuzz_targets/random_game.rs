@@ -0,0 +1,66 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use gomoku::error::GomokuError;
+use gomoku::goban::{Goban, Move, Player, Position, GOBAN_SIZE};
+use gomoku::gomoku::{GameState, Gomoku, SearchLimits};
+
+// Every two bytes pick an intersection for the side to move. Playing never panics, illegal
+// moves leave the game untouched, the hash matches the board rebuilt from the history and
+// taking a move back restores the hash and the line scores it had
+fuzz_target!(|data: &[u8]| {
+    let mut gomoku = Gomoku::with_hash_size(1);
+
+    for pair in data.chunks_exact(2) {
+        let index = u16::from_le_bytes([pair[0], pair[1]]) as usize % (GOBAN_SIZE * GOBAN_SIZE);
+        let position = Position::new(index / GOBAN_SIZE, index % GOBAN_SIZE);
+        let stone = gomoku.board().side_to_move();
+        let before = gomoku.board().clone();
+        let legal = gomoku.is_legal(position);
+
+        match gomoku.play(position, Player::from(stone)) {
+            Ok(state) => {
+                assert!(legal);
+
+                let mut undone = gomoku.board().clone();
+
+                undone.undo_move(Move::new(stone, position));
+                assert_eq!(undone.get_hash(), before.get_hash());
+                assert_eq!(undone.side_to_move(), stone);
+                assert_eq!(undone.evaluate_lines(Player::Opponent), before.evaluate_lines(Player::Opponent));
+
+                undone.apply_move(Move::new(stone, position));
+                assert_eq!(undone.get_hash(), gomoku.board().get_hash());
+
+                if state != GameState::InProgress {
+                    assert!(gomoku.legal_moves().is_empty());
+                    assert_eq!(gomoku.play(position, Player::from(stone)), Err(GomokuError::GameOver));
+                    break;
+                }
+            }
+            Err(_) => {
+                assert!(!legal);
+                assert_eq!(gomoku.board().get_hash(), before.get_hash());
+            }
+        }
+    }
+
+    let mut rebuilt = Goban::new();
+
+    for played in gomoku.history() {
+        rebuilt.apply_move(Move::new(played.stone, played.position));
+    }
+
+    rebuilt.set_side_to_move(gomoku.board().side_to_move());
+    assert_eq!(rebuilt.get_hash(), gomoku.board().get_hash());
+
+    // A shallow search of the final position answers with a legal move
+    if gomoku.game_state() == GameState::InProgress {
+        let report = gomoku
+            .suggest_move(SearchLimits::new(2).with_nodes(64))
+            .expect("an unfinished game has a move");
+
+        assert!(gomoku.is_legal(report.best_move));
+    }
+});
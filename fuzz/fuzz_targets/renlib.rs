@@ -0,0 +1,20 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use gomoku::goban::GOBAN_SIZE;
+use gomoku::renju::{read_renlib, RENJU_SIZE};
+
+// Any bytes either fail to parse or give lines of stones within the 15x15 board centred on the goban
+fuzz_target!(|data: &[u8]| {
+    let offset = (GOBAN_SIZE - RENJU_SIZE) / 2;
+
+    if let Ok(lines) = read_renlib(data) {
+        for line in lines {
+            assert!(!line.is_empty());
+            assert!(line
+                .iter()
+                .all(|position| (offset..offset + RENJU_SIZE).contains(&position.row) && (offset..offset + RENJU_SIZE).contains(&position.col)));
+        }
+    }
+});
@@ -0,0 +1,26 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use gomoku::goban::GOBAN_SIZE;
+use gomoku::sgf::read_sgf;
+
+// Any text either fails to parse or gives moves on the board, one set of properties each,
+// and a game on the full board reads back the same once written
+fuzz_target!(|data: &[u8]| {
+    let text = String::from_utf8_lossy(data);
+    let game = match read_sgf(&text) {
+        Ok(game) => game,
+        Err(_) => return,
+    };
+
+    assert_eq!(game.move_properties.len(), game.moves.len());
+    assert!(game.moves.iter().all(|played| played.position.row < GOBAN_SIZE && played.position.col < GOBAN_SIZE));
+
+    if game.property("SZ").map_or(true, |size| size.trim() == GOBAN_SIZE.to_string()) {
+        let written = read_sgf(&game.to_string()).expect("a written game reads back");
+
+        assert_eq!(written.moves, game.moves);
+        assert_eq!(written.result, game.result);
+    }
+});
@@ -0,0 +1,55 @@
+/* C interface of the gomoku engine, built with `cargo rustc --release --lib --no-default-features --features ffi --crate-type cdylib` */
+
+#ifndef GOMOKU_H
+#define GOMOKU_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Status codes returned by every function taking an engine */
+#define GOMOKU_OK 0
+#define GOMOKU_NULL_POINTER 1
+#define GOMOKU_OUT_OF_BOUNDS 2
+#define GOMOKU_OCCUPIED 3
+#define GOMOKU_GAME_OVER 4
+#define GOMOKU_NO_MOVE 5
+#define GOMOKU_INVALID_ARGUMENT 6
+#define GOMOKU_PANIC 7
+
+/* Intersections, see gomoku_stone_at */
+#define GOMOKU_EMPTY 0
+#define GOMOKU_BLACK 1
+#define GOMOKU_WHITE 2
+
+/* Game states, see gomoku_game_state */
+#define GOMOKU_IN_PROGRESS 0
+#define GOMOKU_BLACK_WON 1
+#define GOMOKU_WHITE_WON 2
+#define GOMOKU_DRAW 3
+
+/* The board is 19x19, rows are counted from the top and columns from the left, from 0 */
+typedef struct GomokuEngine GomokuEngine;
+
+GomokuEngine *gomoku_new(size_t hash_size_mb);
+void gomoku_free(GomokuEngine *engine);
+
+int gomoku_reset(GomokuEngine *engine);
+/* Plays a stone of the side to move, Black moving first */
+int gomoku_play(GomokuEngine *engine, uint32_t row, uint32_t col);
+/* Searches without playing, a zero timeout searches to the default depth */
+int gomoku_best_move(GomokuEngine *engine, uint32_t timeout_ms, uint32_t *row, uint32_t *col);
+int gomoku_stone_at(GomokuEngine *engine, uint32_t row, uint32_t col, int *stone);
+int gomoku_game_state(GomokuEngine *engine, int *state);
+
+/* Static string describing a status code */
+const char *gomoku_status_message(int status);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,139 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+use crate::error::GomokuError;
+use crate::gomoku::{Gomoku, SearchLimits, SearchReport};
+
+#[derive(Default)]
+struct Shared {
+    result: Option<Result<SearchReport, GomokuError>>,
+    waker: Option<Waker>,
+}
+
+/// Search running on its own thread, dropping the future stops it
+///
+/// The search works on a fork of the engine, so the game is untouched and the
+/// caches it warms are the engine's own.
+pub struct SearchFuture {
+    shared: Arc<Mutex<Shared>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl SearchFuture {
+    /// Asks the search to end early, it then resolves with the best move found so far
+    ///
+    /// Stopping is not an error: a search stopped before scoring any move still resolves with the
+    /// first move of its static ordering, as `suggest_move` does.
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Future for SearchFuture {
+    type Output = Result<SearchReport, GomokuError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut shared = self.shared.lock().unwrap();
+
+        match shared.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for SearchFuture {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl Gomoku {
+    /// Searches the best move for the side to move on a dedicated thread
+    pub fn search(&self, limits: SearchLimits) -> SearchFuture {
+        let shared = Arc::new(Mutex::new(Shared::default()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut fork = self.clone();
+
+        fork.set_stop_flag(stop.clone());
+
+        let sender = shared.clone();
+
+        thread::spawn(move || {
+            let result = fork.suggest_move(limits);
+            let mut shared = sender.lock().unwrap();
+
+            shared.result = Some(result);
+
+            if let Some(waker) = shared.waker.take() {
+                waker.wake();
+            }
+        });
+
+        SearchFuture { shared, stop }
+    }
+}
+
+#[cfg(test)]
+mod async_search_tests {
+    use std::future::Future;
+    use std::pin::pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake};
+    use std::thread::{self, Thread};
+
+    use crate::goban::{Player, Position};
+    use crate::gomoku::{Gomoku, SearchLimits};
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let waker = Arc::new(ThreadWaker(thread::current())).into();
+        let mut context = Context::from_waker(&waker);
+        let mut future = pin!(future);
+
+        loop {
+            match future.as_mut().poll(&mut context) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    #[test]
+    fn it_awaits_a_search_without_touching_the_game() {
+        let mut gomoku = Gomoku::with_hash_size(1);
+
+        gomoku.play(Position::new(9, 9), Player::Opponent).unwrap();
+
+        let report = block_on(gomoku.search(SearchLimits::new(2))).unwrap();
+
+        assert!(gomoku.board().get(report.best_move.row, report.best_move.col).is_none());
+        assert_eq!(gomoku.history().len(), 1);
+    }
+
+    #[test]
+    fn it_stops_a_search() {
+        let mut gomoku = Gomoku::with_hash_size(1);
+
+        gomoku.play(Position::new(9, 9), Player::Opponent).unwrap();
+
+        let search = gomoku.search(SearchLimits::new(8));
+        search.stop();
+
+        assert!(block_on(search).is_ok());
+    }
+}
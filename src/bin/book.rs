@@ -0,0 +1,183 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use gomoku::book::{random_opening, OpeningBook};
+use gomoku::game::{Game, GameResult};
+use gomoku::goban::{Player, Position, Stone};
+use gomoku::gomoku::{GameState, Gomoku, PositionSetup, SearchLimits, DEFAULT_SEARCH_DEPTH};
+use gomoku::renju::{read_renlib, read_rif};
+use gomoku::sgf::read_sgf;
+
+/// Moves of a game black started, with its result once it is over
+type Imported = (Vec<Position>, Option<GameResult>);
+
+/// Builds an opening book from self-play games and from saved games, SGF files, RenLib libraries or RIF databases
+#[derive(Parser, Debug)]
+#[clap(version, about)]
+struct Args {
+    /// saved games, SGF files, RenLib libraries or RIF databases to import, unfinished games being skipped
+    #[clap(value_parser)]
+    files: Vec<PathBuf>,
+
+    /// file the book is written to
+    #[clap(long, value_parser)]
+    output: PathBuf,
+
+    /// adds to the book of --output rather than replacing it
+    #[clap(long)]
+    append: bool,
+
+    /// moves of every game added to the book
+    #[clap(long, value_parser, default_value_t = 12)]
+    max_ply: usize,
+
+    /// games a move must have been played in to stay in the book
+    #[clap(long, value_parser, default_value_t = 1)]
+    min_games: u32,
+
+    /// self-play games to add, each from random stones near the centre
+    #[clap(long, value_parser, default_value_t = 0)]
+    selfplay: usize,
+
+    /// search depth of the self-play games
+    #[clap(long, value_parser, default_value_t = DEFAULT_SEARCH_DEPTH)]
+    depth: usize,
+
+    /// random stones the self-play games start with
+    #[clap(long, value_parser, default_value_t = 2)]
+    random_stones: usize,
+
+    /// seed of the self-play openings
+    #[clap(long, value_parser, default_value_t = 0)]
+    seed: u64,
+}
+
+/// Reads the games of a file by its extension: an SGF file (`.sgf`), a RenLib library (`.lib`),
+/// a RIF database (`.rif`), or else a game written by the `gomoku` binary
+///
+/// A RenLib library keeps lines without results, they count as draws.
+fn load(path: &Path) -> Result<Vec<Imported>, String> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    let moves = |game: Game| -> Imported { (game.moves.iter().map(|played| played.position).collect(), game.result) };
+
+    match extension.as_deref() {
+        Some("sgf") => {
+            let text = fs::read_to_string(path).map_err(|error| error.to_string())?;
+
+            read_sgf(&text).map(|game| vec![moves(Game::from(game))]).map_err(|error| error.to_string())
+        }
+        Some("lib") => {
+            let data = fs::read(path).map_err(|error| error.to_string())?;
+            let lines = read_renlib(&data).map_err(|error| error.to_string())?;
+
+            Ok(lines.into_iter().map(|line| (line, Some(GameResult::Draw))).collect())
+        }
+        Some("rif") => {
+            let text = fs::read_to_string(path).map_err(|error| error.to_string())?;
+            let games = read_rif(&text).map_err(|error| error.to_string())?;
+
+            Ok(games
+                .into_iter()
+                .map(|game| (game.moves, game.result.and_then(GameResult::from_state)))
+                .collect())
+        }
+        _ => Game::load(path).map(|game| vec![moves(game)]).map_err(|error| error.to_string()),
+    }
+}
+
+fn winner(result: GameResult) -> Option<Stone> {
+    match result {
+        GameResult::BlackWon => Some(Stone::Black),
+        GameResult::WhiteWon => Some(Stone::White),
+        GameResult::Draw => None,
+    }
+}
+
+/// Plays a game of the engine against itself from `stones` random stones, returns its moves and winner
+fn selfplay(gomoku: &mut Gomoku, limits: SearchLimits, rng: &mut StdRng, stones: usize) -> (Vec<Position>, Option<Stone>) {
+    let mut moves = random_opening(rng, stones);
+    let side = match stones % 2 {
+        0 => Stone::Black,
+        _ => Stone::White,
+    };
+
+    gomoku.reset();
+    gomoku
+        .set_position(PositionSetup::Moves(moves.clone()), side)
+        .expect("openings are distinct stones");
+
+    loop {
+        let stone = gomoku.board().side_to_move();
+        let state = gomoku
+            .suggest_move(limits)
+            .and_then(|report| gomoku.play(report.best_move, Player::from(stone)).map(|state| (report.best_move, state)));
+
+        match state {
+            Ok((position, GameState::InProgress)) => moves.push(position),
+            Ok((position, GameState::Won(player))) => {
+                moves.push(position);
+                return (moves, Some(Stone::from(player)));
+            }
+            _ => return (moves, None),
+        }
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+    let mut book = match args.append && args.output.exists() {
+        true => match OpeningBook::load(&args.output) {
+            Ok(book) => book,
+            Err(error) => {
+                println!("Could not load {}: {}", args.output.display(), error);
+                return;
+            }
+        },
+        false => OpeningBook::new(),
+    };
+
+    for path in &args.files {
+        match load(path) {
+            Ok(games) => {
+                let mut skipped = 0;
+
+                for (moves, result) in games {
+                    match result {
+                        Some(result) => book.add_game(&moves, winner(result), args.max_ply),
+                        None => skipped += 1,
+                    }
+                }
+
+                if skipped > 0 {
+                    println!("Skipped {} unfinished games of {}", skipped, path.display());
+                }
+            }
+            Err(error) => println!("Could not load {}: {}", path.display(), error),
+        }
+    }
+
+    let mut gomoku = Gomoku::default();
+    let mut rng = StdRng::seed_from_u64(args.seed);
+    let limits = SearchLimits::new(args.depth);
+
+    for game in 1..=args.selfplay {
+        let (moves, winner) = selfplay(&mut gomoku, limits, &mut rng, args.random_stones);
+
+        book.add_game(&moves, winner, args.max_ply);
+        println!("Game {}/{}: {} moves, {:?}", game, args.selfplay, moves.len(), winner);
+    }
+
+    book.prune(args.min_games);
+
+    match book.save(&args.output) {
+        Ok(()) => println!("{} positions written to {}", book.len(), args.output.display()),
+        Err(error) => println!("Could not write {}: {}", args.output.display(), error),
+    }
+}
@@ -0,0 +1,39 @@
+use std::fs;
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use gomoku::elo::Tally;
+
+/// Estimates the Elo difference of two engines from the results files of their matches
+#[derive(Parser, Debug)]
+#[clap(version, about)]
+struct Args {
+    /// files of `W`, `D` or `L` lines from the first engine's point of view, as gomoku-match --results writes them
+    #[clap(required = true, value_parser)]
+    files: Vec<PathBuf>,
+}
+
+fn main() {
+    let args = Args::parse();
+    let mut tally = Tally::default();
+
+    for path in &args.files {
+        let read = fs::read_to_string(path)
+            .map_err(|error| error.to_string())
+            .and_then(|results| Tally::read(&results).map_err(|error| error.to_string()));
+
+        match read {
+            Ok(file) => tally += file,
+            Err(error) => {
+                println!("Could not read {}: {}", path.display(), error);
+                return;
+            }
+        }
+    }
+
+    match tally.elo() {
+        Some(elo) => println!("Games {}: {}, score {:.3}, Elo {} (95%)", tally.games(), tally, tally.score(), elo),
+        None => println!("No games"),
+    }
+}
@@ -0,0 +1,246 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+
+use clap::{Parser, ValueEnum};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use gomoku::book::random_opening;
+use gomoku::goban::{Goban, Player, Position, Stone, GOBAN_SIZE};
+use gomoku::gomoku::{GameState, Gomoku, PositionSetup, SearchLimits, DEFAULT_SEARCH_DEPTH};
+
+// Bytes of a bitboard of the binary format, one bit per intersection
+const PLANE_BYTES: usize = (GOBAN_SIZE * GOBAN_SIZE).div_ceil(8);
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    /// `board,side,score,outcome` lines after a header
+    Csv,
+    /// Fixed size little-endian records
+    Binary,
+}
+
+/// Plays the engine against itself on every core and writes each position it searched
+/// with its score and the outcome of the game, for training evaluators and tuning weights
+///
+/// Every record is seen from the side to move: its score, `i64::MAX` and `i64::MIN` for proven wins
+/// and losses, and the outcome of the game, 1 won, 0 drawn and -1 lost.
+///
+/// CSV boards are 361 characters row by row from A19, `x` black, `o` white and `.` empty.
+/// Binary records take 102 bytes: the black then the white stones as two 46 byte bitboards,
+/// the intersection of row `r` from the top and column `c` being bit `(19 * r + c) % 8` of byte `(19 * r + c) / 8`,
+/// then the side to move as a byte, 0 black and 1 white, the score as an i64 and the outcome as an i8.
+#[derive(Parser, Debug)]
+#[clap(version, about)]
+struct Args {
+    /// games to play
+    #[clap(long, value_parser, default_value_t = 100)]
+    games: usize,
+
+    /// threads playing games, every core by default
+    #[clap(long, value_parser)]
+    threads: Option<usize>,
+
+    /// search depth of every move
+    #[clap(long, value_parser, default_value_t = DEFAULT_SEARCH_DEPTH)]
+    depth: usize,
+
+    /// nodes visited after which a search stops
+    #[clap(long, value_parser)]
+    nodes: Option<usize>,
+
+    /// transposition table of each thread, in megabytes
+    #[clap(long, value_parser, default_value_t = 16)]
+    hash: usize,
+
+    /// random stones the games start with
+    #[clap(long, value_parser, default_value_t = 3)]
+    random_stones: usize,
+
+    #[clap(long, value_enum, default_value_t = Format::Csv)]
+    format: Format,
+
+    /// file the records are written to, stdout by default
+    #[clap(long, value_parser)]
+    output: Option<PathBuf>,
+
+    /// seed of the openings, each game using the seed plus its number
+    #[clap(long, value_parser, default_value_t = 0)]
+    seed: u64,
+}
+
+/// A searched position, seen from the side to move
+struct Record {
+    goban: Goban,
+    side: Stone,
+    score: isize,
+    /// 1 when the side to move went on to win, 0 for a draw and -1 for a loss
+    outcome: i8,
+}
+
+impl Record {
+    fn write(&self, format: Format, writer: &mut impl Write) -> io::Result<()> {
+        match format {
+            Format::Csv => {
+                let board: String = (0..GOBAN_SIZE)
+                    .flat_map(|row| (0..GOBAN_SIZE).map(move |col| (row, col)))
+                    .map(|(row, col)| match self.goban.get(row, col) {
+                        Some(Stone::Black) => 'x',
+                        Some(Stone::White) => 'o',
+                        None => '.',
+                    })
+                    .collect();
+                let side = match self.side {
+                    Stone::Black => "black",
+                    Stone::White => "white",
+                };
+
+                writeln!(writer, "{},{},{},{}", board, side, self.score, self.outcome)
+            }
+            Format::Binary => {
+                for stone in [Stone::Black, Stone::White] {
+                    let mut plane = [0u8; PLANE_BYTES];
+
+                    for row in 0..GOBAN_SIZE {
+                        for col in 0..GOBAN_SIZE {
+                            if self.goban.get(row, col) == Some(stone) {
+                                let index = row * GOBAN_SIZE + col;
+
+                                plane[index / 8] |= 1 << (index % 8);
+                            }
+                        }
+                    }
+
+                    writer.write_all(&plane)?;
+                }
+
+                writer.write_all(&[self.side as u8])?;
+                writer.write_all(&(self.score as i64).to_le_bytes())?;
+                writer.write_all(&self.outcome.to_le_bytes())
+            }
+        }
+    }
+}
+
+/// Plays a game from a random opening, returns every position searched once its outcome is known
+fn play_game(gomoku: &mut Gomoku, limits: SearchLimits, opening: Vec<Position>) -> Vec<Record> {
+    let side = match opening.len() % 2 {
+        0 => Stone::Black,
+        _ => Stone::White,
+    };
+    let mut records = Vec::new();
+
+    gomoku.reset();
+    gomoku
+        .set_position(PositionSetup::Moves(opening), side)
+        .expect("openings are distinct stones");
+
+    let state = loop {
+        let side = gomoku.board().side_to_move();
+        let report = match gomoku.suggest_move(limits) {
+            Ok(report) => report,
+            Err(_) => break GameState::Draw,
+        };
+
+        records.push(Record {
+            goban: gomoku.board().clone(),
+            side,
+            score: report.score,
+            outcome: 0,
+        });
+
+        match gomoku.play(report.best_move, Player::from(side)) {
+            Ok(GameState::InProgress) => {}
+            Ok(state) => break state,
+            Err(_) => break GameState::Draw,
+        }
+    };
+
+    if let GameState::Won(player) = state {
+        for record in &mut records {
+            record.outcome = match Stone::from(player) == record.side {
+                true => 1,
+                false => -1,
+            };
+        }
+    }
+
+    records
+}
+
+fn main() {
+    let args = Args::parse();
+    let threads = args
+        .threads
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, |threads| threads.get()));
+    let limits = SearchLimits {
+        nodes: args.nodes,
+        ..SearchLimits::new(args.depth)
+    };
+    let next_game = Arc::new(AtomicUsize::new(0));
+    let (sender, receiver) = mpsc::channel();
+
+    for _ in 0..threads {
+        let next_game = Arc::clone(&next_game);
+        let sender = sender.clone();
+        let (games, hash, stones, seed) = (args.games, args.hash, args.random_stones, args.seed);
+
+        thread::spawn(move || {
+            let mut gomoku = Gomoku::with_hash_size(hash);
+
+            loop {
+                let game = next_game.fetch_add(1, Ordering::Relaxed);
+
+                if game >= games {
+                    break;
+                }
+
+                let mut rng = StdRng::seed_from_u64(seed.wrapping_add(game as u64));
+                let records = play_game(&mut gomoku, limits, random_opening(&mut rng, stones));
+
+                if sender.send(records).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    // Only the workers' senders are left, the channel closes with the last of them
+    drop(sender);
+
+    let output: Box<dyn Write> = match &args.output {
+        Some(path) => match File::create(path) {
+            Ok(file) => Box::new(file),
+            Err(error) => {
+                eprintln!("Could not create {}: {}", path.display(), error);
+                return;
+            }
+        },
+        None => Box::new(io::stdout()),
+    };
+    let mut writer = BufWriter::new(output);
+
+    if args.format == Format::Csv {
+        writeln!(writer, "board,side,score,outcome").ok();
+    }
+
+    let mut positions = 0;
+
+    for (game, records) in receiver.iter().enumerate() {
+        for record in &records {
+            if let Err(error) = record.write(args.format, &mut writer) {
+                eprintln!("Could not write the records: {}", error);
+                return;
+            }
+        }
+
+        positions += records.len();
+        eprintln!("Game {}/{}: {} positions, {} in total", game + 1, args.games, records.len(), positions);
+    }
+
+    writer.flush().ok();
+}
@@ -0,0 +1,57 @@
+use std::time::{Duration, Instant};
+
+use gomoku::engine::BENCH_POSITIONS;
+use gomoku::goban::Stone;
+use gomoku::gomoku::{CacheRetention, Gomoku, PositionSetup, SearchLimits};
+
+pub const DEFAULT_BENCH_DEPTH: usize = 4;
+
+/// Searches a fixed set of positions to `depth`, printing the nodes, time and speed of each
+///
+/// The total node count is the signature of the search: it only changes when the search does,
+/// whatever the speed of the machine.
+pub fn bench(gomoku: &mut Gomoku, depth: usize) {
+    let limits = SearchLimits::new(depth);
+    let (mut nodes, mut time) = (0, Duration::ZERO);
+
+    // Book moves would skip the searches
+    gomoku.clear_book();
+
+    for (index, moves) in BENCH_POSITIONS.iter().enumerate() {
+        let setup: PositionSetup = moves.parse().expect("bench positions are valid move lists");
+        let side = match moves.split_whitespace().count() % 2 {
+            0 => Stone::Black,
+            _ => Stone::White,
+        };
+
+        gomoku.new_game(CacheRetention::Clear);
+        gomoku.set_position(setup, side).expect("bench positions are playable");
+
+        let start = Instant::now();
+        let report = match gomoku.suggest_move(limits) {
+            Ok(report) => report,
+            Err(error) => {
+                println!("{}", error);
+                return;
+            }
+        };
+        let elapsed = start.elapsed();
+
+        nodes += report.visited_nodes;
+        time += elapsed;
+
+        println!(
+            "Position {}/{}: {} nodes, best move {}, {} ms",
+            index + 1,
+            BENCH_POSITIONS.len(),
+            report.visited_nodes,
+            report.best_move,
+            elapsed.as_millis()
+        );
+    }
+
+    let nps = (nodes as f64 / time.as_secs_f64().max(f64::EPSILON)) as u64;
+
+    println!("Depth {}: {} ms, {} nps", depth, time.as_millis(), nps);
+    println!("Signature: {} nodes", nodes);
+}
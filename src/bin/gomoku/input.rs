@@ -0,0 +1,128 @@
+use std::str::FromStr;
+
+use rustyline::completion::{Completer, FilenameCompleter, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::history::DefaultHistory;
+use rustyline::{CompletionType, Config, Context, Editor, Helper, Highlighter, Hinter, Validator};
+
+use gomoku::goban::Position;
+
+const COMMANDS: [&str; 8] = ["undo", "hint", "show", "threats", "resign", "save", "load", "quit"];
+
+// Commands taking a file, completed with the file names
+const FILE_COMMANDS: [&str; 2] = ["save", "load"];
+
+/// A line typed during an interactive game
+#[derive(Debug, PartialEq)]
+pub enum Input {
+    Play(Position),
+    /// Takes back the last move of both sides
+    Undo,
+    Hint,
+    Show,
+    /// Shows the board with the threats of both sides
+    Threats,
+    Resign,
+    Save(String),
+    Load(String),
+    Quit,
+}
+
+impl FromStr for Input {
+    type Err = String;
+
+    /// Parses a command, or coordinates when the line is none
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (command, argument) = s.split_once(char::is_whitespace).unwrap_or((s, ""));
+        let argument = argument.trim();
+
+        let path = |command: fn(String) -> Input| match argument.is_empty() {
+            true => Err(format!("Missing file, expected `{} <file>`", s)),
+            false => Ok(command(argument.to_string())),
+        };
+
+        match command.to_lowercase().as_str() {
+            "undo" => Ok(Input::Undo),
+            "hint" => Ok(Input::Hint),
+            "show" => Ok(Input::Show),
+            "threats" => Ok(Input::Threats),
+            "resign" => Ok(Input::Resign),
+            "quit" | "exit" => Ok(Input::Quit),
+            "save" => path(Input::Save),
+            "load" => path(Input::Load),
+            word if word.len() > 1 && word.chars().all(|c| c.is_ascii_alphabetic()) => {
+                Err(format!("Unknown command `{}`", word))
+            }
+            _ => Position::from_coordinates(s).map(Input::Play).map_err(|error| error.to_string()),
+        }
+    }
+}
+
+/// Completes the commands, the legal coordinates and the files of `save` and `load`
+#[derive(Helper, Hinter, Highlighter, Validator)]
+struct InputHelper {
+    files: FilenameCompleter,
+    /// Coordinates of the legal moves
+    moves: Vec<String>,
+}
+
+impl Completer for InputHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, context: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let typed = &line[..pos];
+
+        if let Some((command, _)) = typed.split_once(char::is_whitespace) {
+            return match FILE_COMMANDS.contains(&command.to_lowercase().as_str()) {
+                true => self.files.complete(line, pos, context),
+                false => Ok((pos, Vec::new())),
+            };
+        }
+
+        let typed = typed.to_lowercase();
+        let candidates = COMMANDS
+            .iter()
+            .map(|command| command.to_string())
+            .chain(self.moves.iter().cloned())
+            .filter(|candidate| candidate.to_lowercase().starts_with(&typed))
+            .map(|candidate| Pair {
+                display: candidate.clone(),
+                replacement: candidate,
+            })
+            .collect();
+
+        Ok((0, candidates))
+    }
+}
+
+/// Prompt with a history browsed with the arrow keys and tab completion
+pub struct LineEditor {
+    editor: Editor<InputHelper, DefaultHistory>,
+}
+
+impl LineEditor {
+    pub fn new() -> rustyline::Result<Self> {
+        let config = Config::builder()
+            .auto_add_history(true)
+            .completion_type(CompletionType::List)
+            .build();
+        let mut editor = Editor::with_config(config)?;
+
+        editor.set_helper(Some(InputHelper {
+            files: FilenameCompleter::new(),
+            moves: Vec::new(),
+        }));
+
+        Ok(Self { editor })
+    }
+
+    /// Reads a line, completing the coordinates of `moves`
+    pub fn read(&mut self, prompt: &str, moves: &[Position]) -> Result<String, ReadlineError> {
+        if let Some(helper) = self.editor.helper_mut() {
+            helper.moves = moves.iter().map(Position::to_string).collect();
+        }
+
+        self.editor.readline(prompt)
+    }
+}
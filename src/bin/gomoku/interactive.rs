@@ -0,0 +1,543 @@
+use std::path::{Path, PathBuf};
+use std::{env, fs, io, process};
+use std::time::Instant;
+
+use rustyline::error::ReadlineError;
+use serde_json::{json, Value};
+
+use gomoku::clock::Clock;
+use gomoku::game::{Game, GameResult};
+use gomoku::goban::{Position, Stone};
+use gomoku::gomoku::{GameState, Gomoku, PositionSetup};
+
+use crate::input::{Input, LineEditor};
+use crate::level::{Resignation, Strength};
+use crate::output::{Output, Verbosity};
+use crate::overlay::ThreatOverlay;
+use crate::render::{eval_bar, Renderer};
+use crate::replay;
+
+/// Start of the autosave names of the user, shared temporary directories keeping those of others apart
+fn autosave_prefix() -> String {
+    match env::var("USER").or_else(|_| env::var("USERNAME")) {
+        Ok(user) if !user.is_empty() => format!("gomoku-autosave-{}-", user),
+        _ => "gomoku-autosave-".to_string(),
+    }
+}
+
+/// File the game in progress is written to after every move, one per process so concurrent games
+/// keep their own
+pub fn autosave_path() -> PathBuf {
+    env::temp_dir().join(format!("{}{}.json", autosave_prefix(), process::id()))
+}
+
+/// The latest unfinished game another session of the user left behind
+fn unfinished_autosave(own: &Path) -> Option<(PathBuf, Game)> {
+    let prefix = autosave_prefix();
+
+    fs::read_dir(env::temp_dir())
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path != own && path.extension().is_some_and(|extension| extension == "json"))
+        .filter(|path| path.file_name().and_then(|name| name.to_str()).is_some_and(|name| name.starts_with(&prefix)))
+        .filter_map(|path| {
+            let modified = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok()?;
+            let game = Game::load(&path).ok().filter(|game| game.result.is_none() && !game.moves.is_empty())?;
+
+            Some((modified, path, game))
+        })
+        .max_by_key(|(modified, _, _)| *modified)
+        .map(|(_, path, game)| (path, game))
+}
+
+/// Interactive game against the computer, the human playing `human`
+pub struct Interactive<'a> {
+    gomoku: &'a mut Gomoku,
+    strength: Strength,
+    human: Stone,
+    renderer: &'a dyn Renderer,
+    output: Output,
+    /// Think time of each move of the history, unknown for some loaded games
+    times: Vec<Option<u64>>,
+    clock: Option<Clock>,
+    resignation: Option<Resignation>,
+    /// Game record to continue instead of starting from the empty board
+    record: Option<Game>,
+    /// How the game ended, once it did
+    result: Option<GameResult>,
+    autosave: Option<PathBuf>,
+}
+
+impl<'a> Interactive<'a> {
+    pub fn new(gomoku: &'a mut Gomoku, strength: Strength, human: Stone, renderer: &'a dyn Renderer, output: Output) -> Self {
+        Self {
+            gomoku,
+            strength,
+            human,
+            renderer,
+            output,
+            times: Vec::new(),
+            clock: None,
+            resignation: None,
+            record: None,
+            result: None,
+            autosave: None,
+        }
+    }
+
+    /// How the game ended, `None` when it was left unfinished
+    pub fn result(&self) -> Option<GameResult> {
+        self.result
+    }
+
+    /// Continues `record` rather than starting a new game
+    pub fn with_record(mut self, record: Game) -> Self {
+        self.record = Some(record);
+        self
+    }
+
+    /// Plays under a time control, the side whose time runs out losing
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Writes the game to `path` after every move, and offers to restore the game left there by a crash
+    pub fn with_autosave(mut self, path: PathBuf) -> Self {
+        self.autosave = Some(path);
+        self
+    }
+
+    /// Lets the computer resign lost games
+    pub fn with_resignation(mut self, resignation: Resignation) -> Self {
+        self.resignation = Some(resignation);
+        self
+    }
+
+    fn show(&self) {
+        self.output.say(Verbosity::Normal, self.renderer.render(self.gomoku));
+
+        if let Some(clock) = &self.clock {
+            self.output.say(Verbosity::Normal, format!("Clock: {}", clock));
+        }
+    }
+
+    /// Announces the end of the game, `reason` telling how it ended in JSON
+    fn end(&mut self, result: GameResult, reason: &str, text: &str) {
+        self.result = Some(result);
+        self.output.say(Verbosity::Quiet, text);
+        self.output.event(json!({ "event": "end", "result": result, "reason": reason }));
+    }
+
+    fn move_event(&self, stone: Stone, position: Position, time_ms: u64, search: Option<Value>) {
+        self.output.event(json!({
+            "event": "move",
+            "stone": stone,
+            "move": position,
+            "timeMs": time_ms,
+            "search": search,
+        }));
+    }
+
+    /// Result of a game `winner` won
+    fn won_by(winner: Stone) -> GameResult {
+        match winner {
+            Stone::Black => GameResult::BlackWon,
+            Stone::White => GameResult::WhiteWon,
+        }
+    }
+
+    /// Stops the clock of `stone` once its move is played, returns whether its time ran out
+    fn press_clock(&mut self, stone: Stone) -> bool {
+        let in_time = self.clock.as_mut().is_none_or(|clock| clock.press(stone));
+
+        if !in_time {
+            self.show();
+
+            let text = match stone == self.human {
+                true => "Your time ran out, Computer Won !",
+                false => "The computer's time ran out, You Won !",
+            };
+
+            self.end(Self::won_by(stone.opponent()), "time", text);
+        }
+
+        !in_time
+    }
+
+    /// Displays the static evaluation from the computer's point of view
+    fn show_eval(&mut self) {
+        let eval = self.gomoku.evaluate_position();
+        let eval = match self.gomoku.board().side_to_move() == self.human {
+            true => eval.opposite(),
+            false => eval,
+        };
+
+        self.output.say(Verbosity::Normal, format!("Eval: {} for the computer", eval_bar(eval)));
+    }
+
+    /// Displays the outcome once the game is over, returns whether it is
+    fn display_end(&mut self, state: GameState) -> bool {
+        let outcome = match state {
+            GameState::InProgress => return false,
+            GameState::Won(player) if Stone::from(player) == self.human => "You Won !",
+            GameState::Won(_) => "Computer Won !",
+            GameState::Draw => "Draw !",
+        };
+
+        self.show();
+        self.end(GameResult::from_state(state).expect("the game is over"), "board", outcome);
+
+        true
+    }
+
+    /// Plays the computer's move, returns whether the game is over
+    fn computer_turn(&mut self) -> bool {
+        let t0 = Instant::now();
+        let computer = self.human.opponent();
+        let mut strength = self.strength;
+
+        // Thinks no longer than its share of the time left
+        if let Some(clock) = &self.clock {
+            let budget = clock.budget(computer);
+
+            strength.limits.movetime = Some(strength.limits.movetime.map_or(budget, |movetime| movetime.min(budget)));
+        }
+
+        let played = strength.choose(self.gomoku).and_then(|chosen| {
+            if self.resignation.as_mut().is_some_and(|resignation| resignation.record(chosen.score())) {
+                return Ok(None);
+            }
+
+            self.gomoku.play(chosen.position(), computer.into()).map(|state| Some((chosen, state)))
+        });
+
+        let (chosen, state) = match played {
+            Ok(Some(played)) => played,
+            Ok(None) => {
+                self.end(Self::won_by(self.human), "resign", "The computer resigned, You Won !");
+                return true;
+            }
+            Err(error) => {
+                self.output.message(error);
+                return true;
+            }
+        };
+        let elapsed = t0.elapsed().as_millis() as u64;
+
+        self.times.push(Some(elapsed));
+        self.autosave();
+        self.output.say(Verbosity::Normal, format!("Took: {} ms", elapsed));
+
+        match chosen.pv().split_first() {
+            Some((played, expected)) if !expected.is_empty() => {
+                let expected: Vec<String> = expected.iter().map(Position::to_string).collect();
+
+                self.output.say(Verbosity::Quiet, format!("I played {}; expecting {}", played, expected.join(" ")));
+            }
+            _ => self.output.say(Verbosity::Quiet, format!("I played {}", chosen.position())),
+        }
+
+        self.move_event(computer, chosen.position(), elapsed, Some(chosen.to_json()));
+
+        if self.press_clock(computer) || self.display_end(state) {
+            return true;
+        }
+
+        self.show();
+        self.show_eval();
+
+        false
+    }
+
+    /// The game with the time each move took, and its result when it ended off the board
+    fn record(&self) -> Game {
+        let mut game = Game::from_gomoku(self.gomoku);
+
+        for (played, time) in game.moves.iter_mut().zip(&self.times) {
+            played.time_ms = *time;
+        }
+
+        game.result = game.result.or(self.result);
+        game.clock = self.clock.as_ref().map(Clock::state);
+        game
+    }
+
+    fn save(&self, path: &str) -> io::Result<()> {
+        self.record().save(path)
+    }
+
+    fn autosave(&self) {
+        if let Some(path) = &self.autosave {
+            if let Err(error) = self.record().save(path) {
+                self.output.say(Verbosity::Verbose, format!("Could not autosave to {}: {}", path.display(), error));
+            }
+        }
+    }
+
+    /// Asks whether to continue the game an earlier run left unfinished, returns it if so
+    ///
+    /// The restored game's file becomes this session's autosave, so no other session restores it too.
+    fn restore(&self, editor: &mut LineEditor) -> Option<Game> {
+        let own = self.autosave.as_ref()?;
+        let (path, game) = unfinished_autosave(own)?;
+        let question = format!(
+            "An unfinished game of {} moves was saved to {}, restore it? [y/N] ",
+            game.moves.len(),
+            path.display()
+        );
+
+        match editor.read(&question, &[]) {
+            Ok(answer) if answer.trim().eq_ignore_ascii_case("y") => fs::rename(&path, own).ok().map(|_| game),
+            _ => None,
+        }
+    }
+
+    /// Prints the result, the moves and the average time each side took once the game ended
+    fn summarize(&self) {
+        let game = self.record();
+        let result = match game.result {
+            Some(GameResult::BlackWon) => "Black won",
+            Some(GameResult::WhiteWon) => "White won",
+            Some(GameResult::Draw) => "Draw",
+            None => return,
+        };
+        let average = |stone| game.average_time_ms(stone).map_or("-".to_string(), |time| format!("{} ms", time));
+
+        self.output.say(Verbosity::Quiet, format!("{} after {} moves", result, game.moves.len()));
+        self.output.say(
+            Verbosity::Quiet,
+            format!("Average think time: Black {}, White {}", average(Stone::Black), average(Stone::White)),
+        );
+
+        if let Some(clock) = &self.clock {
+            self.output.say(Verbosity::Quiet, format!("Clock: {}", clock));
+        }
+
+        self.output.say(Verbosity::Quiet, format!("Moves: {}", game.move_list()));
+        self.output.event(json!({
+            "event": "summary",
+            "result": game.result,
+            "moves": game.moves.iter().map(|played| played.position).collect::<Vec<_>>(),
+            "averageTimeMs": {
+                "black": game.average_time_ms(Stone::Black),
+                "white": game.average_time_ms(Stone::White),
+            },
+        }));
+    }
+
+    /// Asks for a file to save the finished game to, scripts writing JSON are not asked
+    fn offer_save(&self, editor: &mut LineEditor) {
+        if self.output.is_json() {
+            return;
+        }
+
+        let path = match editor.read("Save the game to (leave empty to skip): ", &[]) {
+            Ok(path) => path.trim().to_string(),
+            Err(_) => return,
+        };
+
+        if path.is_empty() {
+            return;
+        }
+
+        match self.save(&path) {
+            Ok(()) => self.output.message(format!("Saved to {}", path)),
+            Err(error) => self.output.message(format!("Could not save {}: {}", path, error)),
+        }
+    }
+
+    /// Resumes a saved game or an SGF file, returns whether it is over
+    fn load(&mut self, path: &str) -> bool {
+        match replay::load(Path::new(path)) {
+            Ok(game) => self.resume(&game),
+            Err(error) => {
+                self.output.message(format!("Could not load {}: {}", path, error));
+                false
+            }
+        }
+    }
+
+    /// Replays `game` and goes on from its last position, returns whether it is over
+    fn resume(&mut self, game: &Game) -> bool {
+        match game.replay(self.gomoku) {
+            Ok(state) => {
+                self.times = game.moves.iter().map(|played| played.time_ms).collect();
+
+                // A timed game goes on with the time it had left
+                if let Some(clock) = &game.clock {
+                    self.clock = Some(Clock::from_state(clock));
+                }
+
+                if let Some(resignation) = &mut self.resignation {
+                    resignation.reset();
+                }
+                self.show();
+
+                // The computer's move may be the one pending
+                self.display_end(state) || (self.gomoku.board().side_to_move() != self.human && self.computer_turn())
+            }
+            Err(error) => {
+                self.output.message(error);
+                false
+            }
+        }
+    }
+
+    /// Takes back the human's last move and the computer's answer, returns whether there was one
+    fn undo(&mut self) -> bool {
+        let history = self.gomoku.history();
+        let kept = match history.iter().rposition(|played| played.stone == self.human) {
+            Some(kept) => kept,
+            None => return false,
+        };
+        let moves = history[..kept].iter().map(|played| played.position).collect();
+
+        self.gomoku
+            .set_position(PositionSetup::Moves(moves), self.human)
+            .expect("a prefix of the game is playable");
+        self.times.truncate(kept);
+
+        if let Some(resignation) = &mut self.resignation {
+            resignation.reset();
+        }
+
+        true
+    }
+
+    pub fn run(&mut self) {
+        if self.output.verbosity == Verbosity::Verbose && !self.output.is_json() {
+            self.gomoku.set_logger(|message| println!("{}", message));
+        }
+
+        if let Some(clock) = &mut self.clock {
+            clock.restart();
+        }
+
+        let mut editor = LineEditor::new().expect("Could not read from stdin");
+
+        // Scripts reading JSON are not asked
+        if self.output.is_json() {
+            self.autosave = None;
+        }
+
+        if self.record.is_none() {
+            self.record = self.restore(&mut editor);
+        }
+
+        let over = match self.record.take() {
+            Some(record) => self.resume(&record),
+            None => {
+                self.show();
+                self.human == Stone::White && self.computer_turn()
+            }
+        };
+
+        if !over {
+            self.play(&mut editor);
+        }
+
+        // An unfinished game stays to be restored by the next run
+        if let (Some(path), Some(_)) = (&self.autosave, self.result) {
+            let _ = fs::remove_file(path);
+        }
+
+        if self.result.is_some() {
+            self.summarize();
+            self.offer_save(&mut editor);
+        }
+    }
+
+    /// Reads and plays the human's moves and commands until the game ends or the human leaves
+    fn play(&mut self, editor: &mut LineEditor) {
+        let prompt = match self.output.is_json() {
+            true => "",
+            false => "> ",
+        };
+
+        loop {
+            self.output
+                .say(Verbosity::Normal, "Input: col row, undo, hint, show, threats, resign, save <file>, load <file> or quit");
+            let asked = Instant::now();
+
+            let input = match editor.read(prompt, &self.gomoku.legal_moves()) {
+                Ok(input) => input,
+                Err(ReadlineError::Interrupted) => {
+                    self.output.message("Interrupted, type quit or press Ctrl-D to leave");
+                    continue;
+                }
+                Err(ReadlineError::Eof) => break,
+                Err(error) => {
+                    self.output.message(error);
+                    break;
+                }
+            };
+
+            let input = match input.parse::<Input>() {
+                Ok(input) => input,
+                Err(message) => {
+                    self.output.message(message);
+                    continue;
+                }
+            };
+
+            match input {
+                Input::Play(position) => {
+                    let elapsed = asked.elapsed().as_millis() as u64;
+
+                    match self.gomoku.play(position, self.human.into()) {
+                        Ok(state) => {
+                            self.move_event(self.human, position, elapsed, None);
+                            self.times.push(Some(elapsed));
+                            self.autosave();
+
+                            if self.press_clock(self.human) || self.display_end(state) {
+                                break;
+                            }
+
+                            self.show_eval();
+                        }
+                        Err(message) => {
+                            self.output.message(message);
+                            self.show();
+                            continue;
+                        }
+                    }
+
+                    if self.computer_turn() {
+                        break;
+                    }
+                }
+                Input::Undo => match self.undo() {
+                    true => self.show(),
+                    false => self.output.message("Nothing to undo"),
+                },
+                Input::Hint => match self.gomoku.suggest_move(self.strength.limits) {
+                    Ok(report) => {
+                        self.output.say(Verbosity::Quiet, format!("hint: {}", report.best_move));
+                        self.output.event(json!({ "event": "hint", "move": report.best_move }));
+                    }
+                    Err(error) => self.output.message(error),
+                },
+                // Asked for, so shown whatever the verbosity
+                Input::Show => self.output.message(self.renderer.render(self.gomoku)),
+                Input::Threats => self.output.message(ThreatOverlay { inner: self.renderer }.render(self.gomoku)),
+                Input::Resign => {
+                    self.end(Self::won_by(self.human.opponent()), "resign", "You resigned, Computer Won !");
+                    break;
+                }
+                Input::Save(path) => match self.save(&path) {
+                    Ok(()) => self.output.message(format!("Saved to {}", path)),
+                    Err(error) => self.output.message(format!("Could not save {}: {}", path, error)),
+                },
+                Input::Load(path) => {
+                    if self.load(&path) {
+                        break;
+                    }
+                }
+                Input::Quit => break,
+            }
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use std::io::{self, BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use gomoku::error::GomokuError;
+use gomoku::goban::{Player, Position};
+use gomoku::gomoku::{GameState, Gomoku, SearchEvent, SearchLimits};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+// Errors of the engine itself, such as an illegal move
+const ENGINE_ERROR: i64 = 1;
+
+const DEFAULT_CANDIDATES: usize = 3;
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn invalid_params(message: &str) -> Self {
+        Self {
+            code: INVALID_PARAMS,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<GomokuError> for RpcError {
+    fn from(error: GomokuError) -> Self {
+        Self {
+            code: ENGINE_ERROR,
+            message: error.to_string(),
+        }
+    }
+}
+
+fn send(message: Value) {
+    let mut stdout = io::stdout().lock();
+
+    writeln!(stdout, "{}", message).ok();
+    stdout.flush().ok();
+}
+
+/// Answers a request, notifications without an `id` get no answer
+fn respond(id: &Option<Value>, result: Result<Value, RpcError>) {
+    let id = match id {
+        Some(id) => id,
+        None => return,
+    };
+
+    send(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({ "jsonrpc": "2.0", "id": id, "error": { "code": error.code, "message": error.message } }),
+    });
+}
+
+fn color(player: Player) -> &'static str {
+    match player {
+        Player::Opponent => "black",
+        Player::Computer => "white",
+    }
+}
+
+fn state(state: GameState) -> Value {
+    match state {
+        GameState::InProgress => json!({ "state": "inProgress" }),
+        GameState::Won(player) => json!({ "state": "won", "winner": color(player) }),
+        GameState::Draw => json!({ "state": "draw" }),
+    }
+}
+
+fn notation(positions: &[Position]) -> Vec<String> {
+    positions.iter().map(Position::to_string).collect()
+}
+
+/// Line-delimited JSON-RPC 2.0 session, one request or notification per line
+///
+/// Black is the first player, `suggest` and `analyze` answer once their search ends and
+/// meanwhile stream `info` notifications, so `stop` can be sent while they run.
+pub struct JsonRpc<'a> {
+    gomoku: &'a mut Gomoku,
+    stop: Arc<AtomicBool>,
+    search: Option<JoinHandle<()>>,
+}
+
+impl<'a> JsonRpc<'a> {
+    pub fn new(gomoku: &'a mut Gomoku) -> Self {
+        gomoku.set_search_observer(|event| {
+            if let SearchEvent::Iteration(info) = event {
+                send(json!({
+                    "jsonrpc": "2.0",
+                    "method": "info",
+                    "params": {
+                        "depth": info.depth,
+                        "move": info.best_move.to_string(),
+                        "score": info.score,
+                        "pv": notation(&info.pv),
+                        "nodes": info.nodes,
+                        "nps": info.metrics.nps,
+                        "time": info.metrics.time_ms,
+                        "hashfull": info.metrics.hashfull,
+                        "ttHitRate": info.metrics.tt_hit_rate(),
+                        "branchingFactor": info.metrics.branching_factor,
+                        "iterations": info.metrics.iterations,
+                    },
+                }));
+            }
+        });
+
+        Self {
+            gomoku,
+            stop: Arc::new(AtomicBool::new(false)),
+            search: None,
+        }
+    }
+
+    /// Serves requests until stdin is closed, waiting for the last search to answer
+    pub fn run(mut self) {
+        let stdin = io::stdin();
+
+        for line in stdin.lock().lines().map_while(Result::ok) {
+            if !line.trim().is_empty() {
+                self.handle(&line);
+            }
+        }
+
+        self.wait();
+    }
+
+    fn handle(&mut self, line: &str) {
+        let request: Value = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(error) => return respond(&Some(Value::Null), Err(RpcError { code: PARSE_ERROR, message: error.to_string() })),
+        };
+
+        let id = request.get("id").cloned();
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+        let method = match request.get("method").and_then(Value::as_str) {
+            Some(method) => method,
+            None => {
+                let error = RpcError {
+                    code: INVALID_REQUEST,
+                    message: "missing method".to_string(),
+                };
+                return respond(&id.or(Some(Value::Null)), Err(error));
+            }
+        };
+
+        let result = match method {
+            "newGame" => {
+                self.gomoku.reset();
+                Ok(state(GameState::InProgress))
+            }
+            "play" => self.play(&params),
+            "suggest" => return self.suggest(id, &params),
+            "analyze" => return self.analyze(id, &params),
+            "stop" => {
+                self.stop.store(true, Ordering::Relaxed);
+                Ok(Value::Null)
+            }
+            method => Err(RpcError {
+                code: METHOD_NOT_FOUND,
+                message: format!("unknown method {}", method),
+            }),
+        };
+
+        respond(&id, result);
+    }
+
+    /// `{"move": "j10", "color": "black"}`, the color defaulting to the side to move
+    fn play(&mut self, params: &Value) -> Result<Value, RpcError> {
+        let position = params
+            .get("move")
+            .and_then(Value::as_str)
+            .ok_or_else(|| RpcError::invalid_params("expected a move"))?;
+        let position = Position::from_coordinates(position).map_err(GomokuError::from)?;
+
+        let player = match params.get("color").and_then(Value::as_str) {
+            Some("black") => Player::Opponent,
+            Some("white") => Player::Computer,
+            Some(_) => return Err(RpcError::invalid_params("color must be black or white")),
+            None if self.gomoku.history().len().is_multiple_of(2) => Player::Opponent,
+            None => Player::Computer,
+        };
+
+        Ok(state(self.gomoku.play(position, player)?))
+    }
+
+    /// `{"depth": 4, "movetime": 1000, "nodes": 100000, "mateIn": 3}`, all optional
+    fn limits(&self, params: &Value) -> SearchLimits {
+        let field = |name| params.get(name).and_then(Value::as_u64);
+        let mut limits = SearchLimits::new(field("depth").map_or(self.gomoku.search_depth(), |depth| depth as usize));
+
+        if let Some(nodes) = field("nodes") {
+            limits = limits.with_nodes(nodes as usize);
+        }
+        if let Some(milliseconds) = field("movetime") {
+            limits = limits.with_movetime(Duration::from_millis(milliseconds));
+        }
+        if let Some(moves) = field("mateIn") {
+            limits = limits.with_mate_in(moves as usize);
+        }
+
+        limits
+    }
+
+    fn suggest(&mut self, id: Option<Value>, params: &Value) {
+        let limits = self.limits(params);
+
+        self.spawn(move |gomoku| {
+            let report = gomoku.suggest_move(limits)?;
+
+            Ok(json!({
+                "move": report.best_move.to_string(),
+                "score": report.score,
+                "depth": report.depth,
+                "nodes": report.visited_nodes,
+            }))
+        }, id);
+    }
+
+    /// Takes the search limits plus `count`, the number of ranked moves wanted
+    fn analyze(&mut self, id: Option<Value>, params: &Value) {
+        let limits = self.limits(params);
+        let count = params.get("count").and_then(Value::as_u64).map_or(DEFAULT_CANDIDATES, |count| count as usize);
+
+        self.spawn(move |gomoku| {
+            let candidates = gomoku.analyze(limits, count)?;
+
+            Ok(candidates
+                .iter()
+                .map(|candidate| json!({
+                    "move": candidate.position.to_string(),
+                    "score": candidate.score,
+                    "pv": notation(&candidate.pv),
+                }))
+                .collect())
+        }, id);
+    }
+
+    /// Searches on a fork of the game, one search at a time
+    fn spawn<F>(&mut self, search: F, id: Option<Value>)
+    where
+        F: FnOnce(&mut Gomoku) -> Result<Value, RpcError> + Send + 'static,
+    {
+        self.wait();
+        self.stop.store(false, Ordering::Relaxed);
+
+        let mut fork = self.gomoku.clone();
+        fork.set_stop_flag(self.stop.clone());
+
+        self.search = Some(thread::spawn(move || respond(&id, search(&mut fork))));
+    }
+
+    fn wait(&mut self) {
+        if let Some(search) = self.search.take() {
+            search.join().ok();
+        }
+    }
+}
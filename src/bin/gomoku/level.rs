@@ -0,0 +1,163 @@
+use std::time::Duration;
+
+use clap::ValueEnum;
+use rand::seq::SliceRandom;
+use serde_json::Value;
+
+use gomoku::bots::Bot;
+use gomoku::engine::Engine;
+use gomoku::error::GomokuError;
+use gomoku::goban::Position;
+use gomoku::gomoku::{CandidateMove, Gomoku, SearchLimits, SearchReport};
+
+use crate::MAX_DEPTH;
+
+// Moves ranked when the computer may not play its best one
+const NOISY_CANDIDATES: usize = 5;
+
+/// Score under which the engine counts a move as lost, about the opponent's open four
+pub const DEFAULT_RESIGN_THRESHOLD: isize = -400_000;
+
+/// Difficulty presets, for players who would rather not tune the search
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    /// Plays any move next to the stones
+    Random,
+    /// Plays the move with the best static evaluation, without looking at the replies
+    Greedy,
+    Easy,
+    Medium,
+    Hard,
+    Max,
+}
+
+/// How the computer chooses its moves
+#[derive(Clone, Copy, Debug)]
+pub struct Strength {
+    pub limits: SearchLimits,
+    /// Moves scoring within this margin of the best one are played at random, zero always plays the best
+    pub noise: isize,
+    /// Baseline bot playing instead of the search
+    pub bot: Option<Bot>,
+}
+
+impl From<Level> for Strength {
+    fn from(level: Level) -> Self {
+        let (limits, noise) = match level {
+            Level::Random | Level::Greedy => (SearchLimits::new(2), 0),
+            Level::Easy => (SearchLimits::new(2).with_nodes(5_000), 20_000),
+            Level::Medium => (SearchLimits::new(4).with_movetime(Duration::from_secs(1)), 5_000),
+            Level::Hard => (SearchLimits::new(6).with_movetime(Duration::from_secs(3)), 0),
+            Level::Max => (SearchLimits::new(MAX_DEPTH).with_movetime(Duration::from_secs(10)), 0),
+        };
+
+        let bot = match level {
+            Level::Random => Some(Bot::Random),
+            Level::Greedy => Some(Bot::Greedy),
+            _ => None,
+        };
+
+        Self { limits, noise, bot }
+    }
+}
+
+/// Move the computer chose, with the report of the whole search when it is the best one
+pub enum Choice {
+    Best(SearchReport),
+    Noisy(CandidateMove),
+}
+
+impl Choice {
+    pub fn position(&self) -> Position {
+        match self {
+            Choice::Best(report) => report.best_move,
+            Choice::Noisy(candidate) => candidate.position,
+        }
+    }
+
+    /// Score of the chosen move from the computer's point of view
+    pub fn score(&self) -> isize {
+        match self {
+            Choice::Best(report) => report.score,
+            Choice::Noisy(candidate) => candidate.score,
+        }
+    }
+
+    /// Expected continuation, starting with the chosen move
+    pub fn pv(&self) -> &[Position] {
+        match self {
+            Choice::Best(report) => &report.pv,
+            Choice::Noisy(candidate) => &candidate.pv,
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        let value = match self {
+            Choice::Best(report) => serde_json::to_value(report),
+            Choice::Noisy(candidate) => serde_json::to_value(candidate),
+        };
+
+        value.expect("searches serialize")
+    }
+}
+
+impl Strength {
+    /// Searches the move to play, at random among the moves within `noise` of the best one
+    ///
+    /// Forced wins and defences are kept, the other moves scoring far below them. Bots give no score
+    /// nor continuation.
+    pub fn choose(&self, gomoku: &mut Gomoku) -> Result<Choice, GomokuError> {
+        if let Some(mut bot) = self.bot {
+            let position = bot.choose_move(gomoku)?;
+
+            return Ok(Choice::Noisy(CandidateMove { position, score: 0, pv: vec![position] }));
+        }
+
+        if self.noise == 0 {
+            return gomoku.suggest_move(self.limits).map(Choice::Best);
+        }
+
+        let candidates = gomoku.analyze(self.limits, NOISY_CANDIDATES)?;
+        let best = candidates.first().ok_or(GomokuError::NoMoveAvailable)?.score;
+        let close: Vec<&CandidateMove> = candidates
+            .iter()
+            .filter(|candidate| candidate.score >= best.saturating_sub(self.noise))
+            .collect();
+
+        let chosen = close.choose(&mut gomoku.rng()).expect("the best move is close to itself");
+
+        Ok(Choice::Noisy((*chosen).clone()))
+    }
+}
+
+/// Makes the engine resign once its moves scored below `threshold` for `moves` moves in a row
+///
+/// A proven loss counts as a lost move whatever the threshold.
+#[derive(Clone, Copy, Debug)]
+pub struct Resignation {
+    pub threshold: isize,
+    pub moves: usize,
+    /// Lost moves in a row so far
+    lost: usize,
+}
+
+impl Resignation {
+    pub fn new(threshold: isize, moves: usize) -> Self {
+        Self { threshold, moves, lost: 0 }
+    }
+
+    /// Counts the score of the engine's latest move, returns whether it should resign rather than play it
+    pub fn record(&mut self, score: isize) -> bool {
+        self.lost = match score == isize::MIN || score < self.threshold {
+            true => self.lost + 1,
+            false => 0,
+        };
+
+        self.lost >= self.moves
+    }
+
+    /// Forgets the moves counted so far, once the game went back or changed
+    pub fn reset(&mut self) {
+        self.lost = 0;
+    }
+}
@@ -0,0 +1,563 @@
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use clap::{Parser, Subcommand, ValueEnum};
+
+use gomoku::book::OpeningBook;
+use gomoku::clock::{Clock, TimeControl};
+use gomoku::game::{Game, GameResult, BLUNDER_THRESHOLD};
+use gomoku::goban::{Position, Stone};
+use gomoku::gomoku::{GameState, Gomoku, GomokuBuilder, PositionSetup, SearchLimits, DEFAULT_SEARCH_DEPTH};
+use gomoku::transposition_table::DEFAULT_SIZE_MB;
+
+mod bench;
+mod input;
+mod interactive;
+mod json_rpc;
+mod level;
+mod output;
+mod overlay;
+mod render;
+mod replay;
+mod review;
+mod selfplay;
+
+use bench::{bench, DEFAULT_BENCH_DEPTH};
+use interactive::Interactive;
+use json_rpc::JsonRpc;
+use level::{Level, Resignation, Strength, DEFAULT_RESIGN_THRESHOLD};
+use output::{Format, Output, Verbosity};
+use overlay::ThreatOverlay;
+use render::{Charset, Renderer};
+use review::review;
+use selfplay::selfplay;
+
+// With a movetime the search deepens until it runs out of time, this only bounds it on trivial positions
+const MAX_DEPTH: usize = 20;
+
+#[derive(Parser, Debug)]
+#[clap(name = "Gomoku")]
+#[clap(author = "angauber")]
+struct Args {
+    /// minmax search tree depth
+    #[clap(short, long, value_parser, default_value_t = DEFAULT_SEARCH_DEPTH)]
+    search_depth: usize,
+
+    /// transposition table size in megabytes
+    #[clap(long, value_parser, default_value_t = DEFAULT_SIZE_MB)]
+    hash_size: usize,
+
+    /// seed of the zobrist keys and of the random choices among book moves and close moves, drawn at
+    /// random when not given and saved with the games to replay them
+    #[clap(long, value_parser)]
+    seed: Option<u64>,
+
+    /// transposition table file, loaded on start when it exists and saved once the game is over
+    #[clap(long, value_parser)]
+    hash_file: Option<PathBuf>,
+
+    /// opening book built by gomoku-book, its moves played without searching
+    #[clap(long, value_parser)]
+    book: Option<PathBuf>,
+
+    /// from 0, always the book move of the highest weight, to 1, each book move as often as its weight
+    #[clap(long, value_parser, default_value_t = 0.5, requires = "book")]
+    book_randomness: f64,
+
+    /// count every finished game in the book and write it back, so the engine stops repeating the openings it lost
+    #[clap(long, requires = "book")]
+    book_learning: bool,
+
+    /// thinking time per move in milliseconds, the search deepening until it runs out instead of stopping at the search depth
+    #[clap(long, value_parser)]
+    movetime: Option<u64>,
+
+    /// main time of each side in seconds, a side losing once its time and byo-yomi periods ran out
+    #[clap(long, value_parser)]
+    time: Option<u64>,
+
+    /// seconds added to the main time after every move
+    #[clap(long, value_parser, requires = "time")]
+    increment: Option<u64>,
+
+    /// seconds per move once the main time ran out
+    #[clap(long, value_parser)]
+    byoyomi: Option<u64>,
+
+    /// byo-yomi periods, one being lost whenever a move overruns the byo-yomi, one by default
+    #[clap(long, value_parser, requires = "byoyomi")]
+    periods: Option<u32>,
+
+    /// let the engine play <SELFPLAY> games against itself, one by default
+    #[clap(long, value_parser, min_values = 0, default_missing_value = "1")]
+    selfplay: Option<usize>,
+
+    /// search depth of black during self-play, the search depth by default
+    #[clap(long, value_parser, requires = "selfplay")]
+    black_depth: Option<usize>,
+
+    /// search depth of white during self-play, the search depth by default
+    #[clap(long, value_parser, requires = "selfplay")]
+    white_depth: Option<usize>,
+
+    /// color of the human, black playing first
+    #[clap(long, value_enum, default_value_t = Color::Black)]
+    play_as: Color,
+
+    /// let the engine open the game with black, as --play-as white
+    #[clap(long, conflicts_with = "play-as")]
+    engine_first: bool,
+
+    /// difficulty preset, replacing the search depth and the movetime
+    #[clap(long, value_enum, conflicts_with_all = &["search-depth", "movetime"])]
+    level: Option<Level>,
+
+    /// let the engine resign once its score stayed below --resign-threshold for <RESIGN> moves in a row,
+    /// or a loss is proven
+    #[clap(long, value_parser, value_name = "MOVES")]
+    resign: Option<usize>,
+
+    /// score of a lost move for --resign, from the engine's point of view
+    #[clap(long, value_parser, allow_hyphen_values = true, default_value_t = DEFAULT_RESIGN_THRESHOLD)]
+    resign_threshold: isize,
+
+    /// characters the board is drawn with, ASCII on dumb terminals and non UTF-8 locales by default
+    #[clap(long, value_enum)]
+    charset: Option<Charset>,
+
+    /// draw the board with plain ASCII characters instead of a Unicode grid, as --charset ascii
+    #[clap(long, conflicts_with = "charset")]
+    ascii: bool,
+
+    /// print the number of the move on each stone, as a game diagram
+    #[clap(long)]
+    numbers: bool,
+
+    /// mark where either side would complete a five, fork or open a four on every board printed
+    #[clap(long)]
+    threats: bool,
+
+    /// continue the game of a saved game or an SGF file
+    #[clap(long = "continue", value_parser, value_name = "FILE", conflicts_with_all = &["selfplay", "json"])]
+    resume: Option<PathBuf>,
+
+    /// neither write the game in progress to a temporary file after every move nor offer to restore it
+    #[clap(long)]
+    no_autosave: bool,
+
+    /// speak line-delimited JSON-RPC on stdin/stdout instead of playing interactively
+    #[clap(long)]
+    json: bool,
+
+    /// print only the moves, the result and the answers to commands
+    #[clap(short, long, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// print the engine diagnostics of every search too
+    #[clap(short, long)]
+    verbose: bool,
+
+    /// print the game and self-play as text or as one JSON event per line, each move of the engine with its search report
+    #[clap(long, value_enum, default_value_t = Format::Text)]
+    output: Format,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Color {
+    Black,
+    White,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// step forward and backward through a saved game or an SGF file
+    Replay {
+        file: PathBuf,
+
+        /// search the best move of every position
+        #[clap(long)]
+        eval: bool,
+    },
+    /// print the analysis of every position of a saved game or an SGF file as JSON
+    Export {
+        file: PathBuf,
+
+        /// ranked moves of each position
+        #[clap(long, value_parser, default_value_t = 3)]
+        count: usize,
+    },
+    /// flag the moves of a saved game or an SGF file scoring well below the engine's best move
+    Review {
+        file: PathBuf,
+
+        /// score a move may lose to the best one before it is flagged
+        #[clap(long, value_parser, default_value_t = BLUNDER_THRESHOLD)]
+        threshold: isize,
+
+        /// nodes visited after which each search stops
+        #[clap(long, value_parser)]
+        nodes: Option<usize>,
+
+        /// writes the game with marks, evals and continuations to this file, as SGF with a `.sgf`
+        /// extension and as markdown otherwise
+        #[clap(long, value_parser)]
+        annotate: Option<PathBuf>,
+    },
+    /// print the best moves of a position, given as a move list, a saved game or an SGF file
+    Analyze {
+        #[clap(required = true)]
+        position: Vec<String>,
+
+        /// ranked moves
+        #[clap(long, value_parser, default_value_t = 3)]
+        count: usize,
+
+        /// nodes visited after which the search stops
+        #[clap(long, value_parser)]
+        nodes: Option<usize>,
+
+        /// writes the tree of the search to this file, as JSON with a `.json` extension and as
+        /// Graphviz dot otherwise
+        #[clap(long, value_parser)]
+        tree: Option<PathBuf>,
+
+        /// nodes of the tree written, the deeper ones being left out
+        #[clap(long, value_parser, default_value_t = 10_000, requires = "tree")]
+        tree_limit: usize,
+    },
+    /// search a fixed set of positions, printing the nodes, time and speed, the total node count
+    /// changing only when the search does
+    Bench {
+        /// search depth of every position
+        #[clap(long, value_parser, default_value_t = DEFAULT_BENCH_DEPTH)]
+        depth: usize,
+    },
+    /// count the positions the move generator reaches from a position, at every depth up to <DEPTH>
+    Perft {
+        depth: usize,
+
+        /// move list, saved game or SGF file, the empty board by default
+        position: Vec<String>,
+
+        /// print the count below each candidate move at the last depth
+        #[clap(long)]
+        divide: bool,
+    },
+}
+
+fn export(gomoku: &mut Gomoku, game: &Game, count: usize, limits: SearchLimits) {
+    match game.analyze(gomoku, limits, count) {
+        Ok(analysis) => println!("{}", serde_json::to_string_pretty(&analysis).expect("analyses serialize")),
+        Err(error) => println!("{}", error),
+    }
+}
+
+/// Sets up the position of a saved game, an SGF file or a move list such as `j10 k11 h9`
+fn set_up(gomoku: &mut Gomoku, position: &[String]) -> Result<GameState, String> {
+    if let [file] = position {
+        let path = Path::new(file);
+
+        if path.is_file() {
+            return replay::load(path)?.replay(gomoku).map_err(|error| error.to_string());
+        }
+    }
+
+    let moves = position
+        .join(" ")
+        .split_whitespace()
+        .map(Position::from_coordinates)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| error.to_string())?;
+    let side_to_move = match moves.len() % 2 {
+        0 => Stone::Black,
+        _ => Stone::White,
+    };
+
+    gomoku
+        .set_position(PositionSetup::Moves(moves), side_to_move)
+        .map_err(|error| error.to_string())
+}
+
+fn perft(gomoku: &mut Gomoku, position: &[String], depth: usize, divide: bool) {
+    if !position.is_empty() {
+        if let Err(error) = set_up(gomoku, position) {
+            println!("{}", error);
+            return;
+        }
+    }
+
+    for depth in 1..=depth {
+        let start = Instant::now();
+        let nodes = gomoku.perft(depth);
+
+        println!("perft({}) = {} in {} ms", depth, nodes, start.elapsed().as_millis());
+    }
+
+    if divide {
+        for (position, nodes) in gomoku.perft_divide(depth) {
+            println!("{}: {}", position, nodes);
+        }
+    }
+}
+
+fn analyze(gomoku: &mut Gomoku, position: &[String], count: usize, limits: SearchLimits, renderer: &dyn Renderer) {
+    match set_up(gomoku, position) {
+        Ok(GameState::InProgress) => {}
+        Ok(_) => {
+            println!("{}", renderer.render(gomoku));
+            println!("The game is over");
+            return;
+        }
+        Err(error) => {
+            println!("{}", error);
+            return;
+        }
+    }
+
+    println!("{}", renderer.render(gomoku));
+    println!("{:?} to move", gomoku.board().side_to_move());
+
+    match gomoku.analyze(limits, count) {
+        Ok(candidates) => {
+            for (rank, candidate) in candidates.iter().enumerate() {
+                let pv: Vec<String> = candidate.pv.iter().map(Position::to_string).collect();
+
+                println!("{}. {} score {} pv {}", rank + 1, candidate.position, candidate.score, pv.join(" "));
+            }
+        }
+        Err(error) => println!("{}", error),
+    }
+}
+
+/// Writes the tree of the last search, as JSON or Graphviz dot depending on the extension of `path`
+fn write_tree(gomoku: &Gomoku, path: &Path) {
+    let tree = match gomoku.search_tree() {
+        Some(tree) if !tree.nodes().is_empty() => tree,
+        _ => {
+            println!("No search to write to {}", path.display());
+            return;
+        }
+    };
+    let text = match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) if extension.eq_ignore_ascii_case("json") => {
+            serde_json::to_string_pretty(tree.nodes()).expect("tree nodes serialize")
+        }
+        _ => tree.to_dot(),
+    };
+
+    match std::fs::write(path, text) {
+        Ok(()) if tree.is_truncated() => {
+            println!("Search tree written to {}, truncated to {} nodes", path.display(), tree.nodes().len())
+        }
+        Ok(()) => println!("Search tree written to {}, {} nodes", path.display(), tree.nodes().len()),
+        Err(error) => println!("Could not write {}: {}", path.display(), error),
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let mut builder = GomokuBuilder::new()
+        .hash_size_mb(args.hash_size)
+        .search_depth(args.search_depth);
+
+    if let Some(seed) = args.seed {
+        builder = builder.seed(seed).zobrist_seed(seed);
+    }
+
+    let mut gomoku = match builder.build() {
+        Ok(gomoku) => gomoku,
+        Err(error) => {
+            println!("{}", error);
+            return;
+        }
+    };
+
+    if let Some(path) = args.hash_file.as_ref().filter(|path| path.exists()) {
+        if let Err(error) = gomoku.load_transposition_table(path) {
+            println!("Could not load {}: {}", path.display(), error);
+        }
+    }
+
+    if let Some(path) = &args.book {
+        match OpeningBook::load(path) {
+            Ok(book) => gomoku.set_book(Arc::new(book), args.book_randomness),
+            Err(error) => println!("Could not load {}: {}", path.display(), error),
+        }
+    }
+
+    let time_control = match (args.time, args.byoyomi) {
+        (None, None) => None,
+        (time, byoyomi) => {
+            let control = TimeControl::new(Duration::from_secs(time.unwrap_or(0)))
+                .with_increment(Duration::from_secs(args.increment.unwrap_or(0)));
+
+            Some(match byoyomi {
+                Some(byoyomi) => control.with_byoyomi(Duration::from_secs(byoyomi), args.periods.unwrap_or(1)),
+                None => control,
+            })
+        }
+    };
+
+    let strength = match (args.level, args.movetime) {
+        (Some(level), _) => Strength::from(level),
+        // The clock gives the time of every move
+        (None, None) if time_control.is_some() => Strength {
+            limits: SearchLimits::new(MAX_DEPTH),
+            noise: 0,
+            bot: None,
+        },
+        (None, Some(milliseconds)) => Strength {
+            limits: SearchLimits::new(MAX_DEPTH).with_movetime(Duration::from_millis(milliseconds)),
+            noise: 0,
+            bot: None,
+        },
+        (None, None) => Strength {
+            limits: SearchLimits::new(gomoku.search_depth()),
+            noise: 0,
+            bot: None,
+        },
+    };
+
+    let resignation = args
+        .resign
+        .map(|moves| Resignation::new(args.resign_threshold, moves.max(1)));
+
+    let charset = match args.ascii {
+        true => Charset::Ascii,
+        false => args.charset.unwrap_or_else(Charset::detect),
+    };
+    // Colors would only clutter redirected output
+    let board = render::renderer(charset, args.numbers, io::stdout().is_terminal());
+    let overlaid = ThreatOverlay { inner: board.as_ref() };
+    let renderer: &dyn Renderer = match args.threats {
+        true => &overlaid,
+        false => board.as_ref(),
+    };
+
+    let output = Output {
+        verbosity: match (args.quiet, args.verbose) {
+            (true, _) => Verbosity::Quiet,
+            (false, true) => Verbosity::Verbose,
+            (false, false) => Verbosity::Normal,
+        },
+        format: args.output,
+    };
+
+    match args.command {
+        Some(Command::Replay { file, eval }) => match replay::load(&file) {
+            Ok(game) => replay::replay(&mut gomoku, &game, eval, strength.limits, renderer),
+            Err(error) => println!("Could not load {}: {}", file.display(), error),
+        },
+        Some(Command::Export { file, count }) => match replay::load(&file) {
+            Ok(game) => export(&mut gomoku, &game, count, strength.limits),
+            Err(error) => println!("Could not load {}: {}", file.display(), error),
+        },
+        Some(Command::Review { file, threshold, nodes, annotate }) => match replay::load(&file) {
+            Ok(game) => {
+                let limits = match nodes {
+                    Some(nodes) => strength.limits.with_nodes(nodes),
+                    None => strength.limits,
+                };
+
+                review(&mut gomoku, &game, limits, threshold, annotate.as_deref())
+            }
+            Err(error) => println!("Could not load {}: {}", file.display(), error),
+        },
+        Some(Command::Analyze { position, count, nodes, tree, tree_limit }) => {
+            let limits = match nodes {
+                Some(nodes) => strength.limits.with_nodes(nodes),
+                None => strength.limits,
+            };
+
+            if tree.is_some() {
+                gomoku.record_search_tree(tree_limit);
+            }
+
+            analyze(&mut gomoku, &position, count, limits, renderer);
+
+            if let Some(path) = tree {
+                write_tree(&gomoku, &path);
+            }
+        }
+        Some(Command::Bench { depth }) => bench(&mut gomoku, depth),
+        Some(Command::Perft { depth, position, divide }) => perft(&mut gomoku, &position, depth, divide),
+        None => match args.selfplay {
+            Some(games) => {
+                let side = |depth: Option<usize>| match depth {
+                    Some(depth) => Strength {
+                        limits: SearchLimits { depth, ..strength.limits },
+                        ..strength
+                    },
+                    None => strength,
+                };
+
+                let strengths = [side(args.black_depth), side(args.white_depth)];
+
+                selfplay(&mut gomoku, games, strengths, resignation, args.book_learning, renderer, output);
+            }
+            None if args.json => JsonRpc::new(&mut gomoku).run(),
+            None => {
+                let human = match (args.play_as, args.engine_first) {
+                    (Color::White, _) | (_, true) => Stone::White,
+                    (Color::Black, false) => Stone::Black,
+                };
+
+                let mut interactive = Interactive::new(&mut gomoku, strength, human, renderer, output);
+
+                if !args.no_autosave {
+                    interactive = interactive.with_autosave(interactive::autosave_path());
+                }
+
+                if let Some(resignation) = resignation {
+                    interactive = interactive.with_resignation(resignation);
+                }
+
+                if let Some(control) = time_control {
+                    interactive = interactive.with_clock(Clock::new(control));
+                }
+
+                if let Some(path) = &args.resume {
+                    match replay::load(path) {
+                        Ok(record) => interactive = interactive.with_record(record),
+                        Err(error) => {
+                            println!("Could not load {}: {}", path.display(), error);
+                            return;
+                        }
+                    }
+                }
+
+                interactive.run();
+
+                if let Some(result) = interactive.result().filter(|_| args.book_learning) {
+                    let winner = match result {
+                        GameResult::BlackWon => Some(Stone::Black),
+                        GameResult::WhiteWon => Some(Stone::White),
+                        GameResult::Draw => None,
+                    };
+
+                    gomoku.learn_book(winner);
+                }
+            }
+        },
+    }
+
+    let book_learning = args.book_learning;
+    let learned_book = args.book.as_ref().zip(gomoku.book()).filter(|_| book_learning);
+
+    if let Some((path, book)) = learned_book {
+        if let Err(error) = book.save(path) {
+            println!("Could not save {}: {}", path.display(), error);
+        }
+    }
+
+    if let Some(path) = args.hash_file {
+        if let Err(error) = gomoku.save_transposition_table(&path) {
+            println!("Could not save {}: {}", path.display(), error);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use std::fmt;
+
+use clap::ValueEnum;
+use serde_json::{json, Value};
+
+/// How much the game modes print besides the moves and the result
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// The moves, the result and the answers to commands
+    Quiet,
+    /// The board, the clocks and the evaluation after every move too
+    Normal,
+    /// The engine diagnostics of every search too
+    Verbose,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    /// One JSON object per line, for scripts driving the binary
+    Json,
+}
+
+/// Where the game modes print, as free-form text up to a verbosity or as JSON events
+#[derive(Clone, Copy, Debug)]
+pub struct Output {
+    pub verbosity: Verbosity,
+    pub format: Format,
+}
+
+impl Output {
+    pub fn is_json(&self) -> bool {
+        self.format == Format::Json
+    }
+
+    /// Prints `text` when writing text of at least `verbosity`
+    pub fn say<T: fmt::Display>(&self, verbosity: Verbosity, text: T) {
+        if !self.is_json() && verbosity <= self.verbosity {
+            println!("{}", text);
+        }
+    }
+
+    /// Prints `event` as a line of JSON when writing JSON
+    pub fn event(&self, event: Value) {
+        if self.is_json() {
+            println!("{}", event);
+        }
+    }
+
+    /// Answers the user, as a `message` event when writing JSON
+    pub fn message<T: fmt::Display>(&self, text: T) {
+        self.say(Verbosity::Quiet, &text);
+        self.event(json!({ "event": "message", "text": text.to_string() }));
+    }
+}
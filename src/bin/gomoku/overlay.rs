@@ -0,0 +1,129 @@
+use std::collections::HashSet;
+
+use gomoku::goban::{Goban, Move, Position, Stone, GOBAN_SIZE};
+use gomoku::gomoku::Gomoku;
+use gomoku::threat_evaluator::Threat;
+
+use crate::render::Renderer;
+
+// Intersections further from the side's stones cannot add to its lines
+const REACH: usize = 2;
+
+pub const LEGEND: &str = "W completes a five, F forks, S opens a four; upper case for black, lower case for white";
+
+/// What a stone on an empty intersection would make, from the weakest to the strongest
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Mark {
+    /// An open four, out of an open three
+    OpenFour,
+    /// Threats along two lines at once
+    Fork,
+    Five,
+}
+
+impl Mark {
+    pub fn glyph(self, stone: Stone) -> char {
+        let glyph = match self {
+            Mark::OpenFour => 'S',
+            Mark::Fork => 'F',
+            Mark::Five => 'W',
+        };
+
+        match stone {
+            Stone::Black => glyph,
+            Stone::White => glyph.to_ascii_lowercase(),
+        }
+    }
+}
+
+/// Marks of the empty intersections, indexed by row then column
+pub type Overlay = [[Option<(Stone, Mark)>; GOBAN_SIZE]; GOBAN_SIZE];
+
+pub const NO_OVERLAY: Overlay = [[None; GOBAN_SIZE]; GOBAN_SIZE];
+
+fn near(goban: &Goban, stone: Stone, position: Position) -> bool {
+    let rows = position.row.saturating_sub(REACH)..=(position.row + REACH).min(GOBAN_SIZE - 1);
+
+    rows.flat_map(|row| {
+        let cols = position.col.saturating_sub(REACH)..=(position.col + REACH).min(GOBAN_SIZE - 1);
+
+        cols.map(move |col| (row, col))
+    })
+    .any(|(row, col)| goban.get(row, col) == Some(stone))
+}
+
+/// What `stone` would make by playing `position`
+fn mark(goban: &Goban, stone: Stone, position: Position) -> Option<Mark> {
+    let mut child = goban.clone();
+
+    child.apply_move(Move::new(stone, position));
+
+    if child.has_five(stone) {
+        return Some(Mark::Five);
+    }
+
+    let made: Vec<_> = child
+        .threats()
+        .into_iter()
+        .filter(|threat| threat.stone == stone && threat.stones.contains(&position))
+        .collect();
+
+    // Windows of one line may report several threats, a fork needs two lines
+    let lines: HashSet<(isize, isize)> = made
+        .iter()
+        .filter_map(|threat| match threat.stones.as_slice() {
+            [first, second, ..] => Some((
+                (second.row as isize - first.row as isize).signum(),
+                (second.col as isize - first.col as isize).signum(),
+            )),
+            _ => None,
+        })
+        .collect();
+
+    match (lines.len(), made.iter().any(|threat| threat.threat == Threat::StraightFour)) {
+        (2.., _) => Some(Mark::Fork),
+        (_, true) => Some(Mark::OpenFour),
+        _ => None,
+    }
+}
+
+/// Marks the intersections where either side would complete a five, fork or open a four
+///
+/// When both sides have a mark on the same intersection, the strongest is kept, the side to move's on ties.
+pub fn threat_overlay(goban: &Goban) -> Overlay {
+    let mut overlay = NO_OVERLAY;
+    let side_to_move = goban.side_to_move();
+
+    for stone in [side_to_move, side_to_move.opponent()] {
+        for (row, marks) in overlay.iter_mut().enumerate() {
+            for (col, marked) in marks.iter_mut().enumerate() {
+                let position = Position::new(row, col);
+
+                if goban.get(row, col).is_some() || !near(goban, stone, position) {
+                    continue;
+                }
+
+                if let Some(mark) = mark(goban, stone, position) {
+                    if marked.is_none_or(|(_, strongest)| mark > strongest) {
+                        *marked = Some((stone, mark));
+                    }
+                }
+            }
+        }
+    }
+
+    overlay
+}
+
+/// Draws the board of another renderer with the threats of both sides and their legend
+pub struct ThreatOverlay<'a> {
+    pub inner: &'a dyn Renderer,
+}
+
+impl Renderer for ThreatOverlay<'_> {
+    fn render_overlay(&self, gomoku: &Gomoku, _: &Overlay) -> String {
+        let board = self.inner.render_overlay(gomoku, &threat_overlay(gomoku.board()));
+
+        format!("{}{}\n", board, LEGEND)
+    }
+}
@@ -0,0 +1,306 @@
+use std::env;
+
+use clap::ValueEnum;
+use crossterm::style::{Color, Stylize};
+
+use gomoku::evaluator::Eval;
+use gomoku::goban::{CoordinateStyle, Position, Stone, GOBAN_SIZE};
+use gomoku::gomoku::Gomoku;
+
+use crate::overlay::{Overlay, NO_OVERLAY};
+
+// Rows and columns of the star points
+const HOSHI: [usize; 3] = [3, GOBAN_SIZE / 2, GOBAN_SIZE - 4];
+
+const LAST: usize = GOBAN_SIZE - 1;
+
+const EVAL_BAR_WIDTH: usize = 20;
+
+/// Draws the board of a game as text, rows from the top one with their number on the right
+pub trait Renderer {
+    /// Draws the board with the marks of `overlay` on its empty intersections
+    fn render_overlay(&self, gomoku: &Gomoku, overlay: &Overlay) -> String;
+
+    fn render(&self, gomoku: &Gomoku) -> String {
+        self.render_overlay(gomoku, &NO_OVERLAY)
+    }
+}
+
+/// Characters the boards are drawn with
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Charset {
+    /// Box drawing characters and round stones, colored on terminals
+    Unicode,
+    /// Plain ASCII without colors, for dumb terminals, logs and bots relaying the board as text
+    Ascii,
+}
+
+impl Charset {
+    /// ASCII on dumb terminals and under a locale without UTF-8, Unicode otherwise
+    pub fn detect() -> Self {
+        if env::var("TERM").is_ok_and(|term| term == "dumb") {
+            return Charset::Ascii;
+        }
+
+        // The first of these variables which is set decides of the encoding
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty());
+
+        match locale.map(|locale| locale.to_lowercase()) {
+            Some(locale) if !locale.contains("utf-8") && !locale.contains("utf8") => Charset::Ascii,
+            _ => Charset::Unicode,
+        }
+    }
+}
+
+/// Renderer drawing with `charset`, numbering the stones when `numbers`
+///
+/// Every renderer draws the same overlays and highlights, so falling back to ASCII loses no information.
+pub fn renderer(charset: Charset, numbers: bool, color: bool) -> Box<dyn Renderer> {
+    let color = color && charset == Charset::Unicode;
+
+    match (numbers, charset) {
+        (true, _) => Box::new(NumberedRenderer { color }),
+        (false, Charset::Ascii) => Box::new(AsciiRenderer),
+        (false, Charset::Unicode) => Box::new(UnicodeRenderer { color }),
+    }
+}
+
+/// Bar filled from the left as `eval` gets better for its side, followed by the score
+pub fn eval_bar(eval: Eval) -> String {
+    let filled = ((eval.normalized() + 1.0) / 2.0 * EVAL_BAR_WIDTH as f64).round() as usize;
+    let score = match eval {
+        Eval::Won => "win".to_string(),
+        Eval::Lost => "loss".to_string(),
+        Eval::Score(score) => format!("{:+}", score),
+    };
+
+    format!("[{}{}] {}", "#".repeat(filled), "-".repeat(EVAL_BAR_WIDTH - filled), score)
+}
+
+/// Stones drawn apart from the others
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Highlight {
+    None,
+    LastMove,
+    /// One of the five of a won game, the last move included
+    WinningLine,
+}
+
+/// Highlight of every intersection, indexed by row then column
+fn highlights(gomoku: &Gomoku) -> [[Highlight; GOBAN_SIZE]; GOBAN_SIZE] {
+    let mut highlights = [[Highlight::None; GOBAN_SIZE]; GOBAN_SIZE];
+
+    if let Some(played) = gomoku.last_move() {
+        highlights[played.position.row][played.position.col] = Highlight::LastMove;
+    }
+
+    for Position { row, col } in gomoku.board().winning_line().unwrap_or_default() {
+        highlights[row][col] = Highlight::WinningLine;
+    }
+
+    highlights
+}
+
+fn labels() -> String {
+    let labels: Vec<String> = (0..GOBAN_SIZE).map(|col| CoordinateStyle::Letters.column_label(col)).collect();
+
+    labels.join(" ")
+}
+
+/// `X` for black, `O` for white and `.` for empty intersections, safe on any terminal
+///
+/// The last move is put between parentheses and the stones of the winning line are lowercase.
+pub struct AsciiRenderer;
+
+impl Renderer for AsciiRenderer {
+    fn render_overlay(&self, gomoku: &Gomoku, overlay: &Overlay) -> String {
+        let goban = gomoku.board();
+        let highlights = highlights(gomoku);
+        let mut board = String::new();
+
+        for (row, highlights) in highlights.iter().enumerate() {
+            // Every intersection sits between two separators, which the last move replaces
+            let mut line = vec![' '; 2 * GOBAN_SIZE + 1];
+
+            for (col, highlight) in highlights.iter().enumerate() {
+                let cell = match (goban.get(row, col), highlight) {
+                    (None, _) => overlay[row][col].map_or('.', |(stone, mark)| mark.glyph(stone)),
+                    (Some(Stone::Black), Highlight::WinningLine) => 'x',
+                    (Some(Stone::White), Highlight::WinningLine) => 'o',
+                    (Some(Stone::Black), _) => 'X',
+                    (Some(Stone::White), _) => 'O',
+                };
+
+                line[2 * col + 1] = cell;
+
+                if gomoku.last_move().is_some_and(|played| played.position == Position::new(row, col)) {
+                    line[2 * col] = '(';
+                    line[2 * col + 2] = ')';
+                }
+            }
+
+            board.extend(line);
+            board.push_str(&format!("{}\n", GOBAN_SIZE - row));
+        }
+
+        board.push_str(&format!(" {}\n", labels()));
+        board
+    }
+}
+
+/// Grid of box drawing characters with star points, stones colored on a wooden board when `color`
+pub struct UnicodeRenderer {
+    pub color: bool,
+}
+
+impl UnicodeRenderer {
+    fn intersection(row: usize, col: usize) -> char {
+        match (row, col) {
+            (0, 0) => '┌',
+            (0, LAST) => '┐',
+            (0, _) => '┬',
+            (LAST, 0) => '└',
+            (LAST, LAST) => '┘',
+            (LAST, _) => '┴',
+            (_, 0) => '├',
+            (_, LAST) => '┤',
+            (row, col) if HOSHI.contains(&row) && HOSHI.contains(&col) => '╋',
+            _ => '┼',
+        }
+    }
+
+    fn foreground(stone: Stone) -> Color {
+        match stone {
+            Stone::Black => Color::Black,
+            Stone::White => Color::White,
+        }
+    }
+
+    fn paint(&self, text: String, foreground: Color) -> String {
+        self.paint_on(text, foreground, Color::DarkYellow)
+    }
+
+    fn paint_on(&self, text: String, foreground: Color, background: Color) -> String {
+        match self.color {
+            true => text.with(foreground).on(background).to_string(),
+            false => text,
+        }
+    }
+
+    /// Stone on a red intersection for the last move and a green one for the winning line,
+    /// or a square shape for both without colors
+    fn stone(&self, stone: Stone, highlight: Highlight) -> String {
+        let foreground = Self::foreground(stone);
+
+        match (self.color, highlight) {
+            (true, Highlight::None) => self.paint("●".to_string(), foreground),
+            (true, Highlight::LastMove) => self.paint_on("●".to_string(), foreground, Color::DarkRed),
+            (true, Highlight::WinningLine) => self.paint_on("●".to_string(), foreground, Color::DarkGreen),
+            (false, highlight) => {
+                let glyph = match (stone, highlight) {
+                    (Stone::Black, Highlight::None) => "●",
+                    (Stone::White, Highlight::None) => "○",
+                    (Stone::Black, Highlight::LastMove) => "◆",
+                    (Stone::White, Highlight::LastMove) => "◇",
+                    (Stone::Black, Highlight::WinningLine) => "■",
+                    (Stone::White, Highlight::WinningLine) => "□",
+                };
+
+                glyph.to_string()
+            }
+        }
+    }
+}
+
+impl Renderer for UnicodeRenderer {
+    fn render_overlay(&self, gomoku: &Gomoku, overlay: &Overlay) -> String {
+        let goban = gomoku.board();
+        let highlights = highlights(gomoku);
+        let mut board = String::new();
+
+        for (row, highlights) in highlights.iter().enumerate() {
+            for (col, highlight) in highlights.iter().enumerate() {
+                let cell = match (goban.get(row, col), overlay[row][col]) {
+                    (Some(stone), _) => self.stone(stone, *highlight),
+                    (None, Some((stone, mark))) => self.paint(mark.glyph(stone).to_string(), Self::foreground(stone)),
+                    (None, None) => self.paint(Self::intersection(row, col).to_string(), Color::Black),
+                };
+
+                board.push_str(&cell);
+
+                if col != LAST {
+                    board.push_str(&self.paint("─".to_string(), Color::Black));
+                }
+            }
+
+            board.push_str(&format!(" {}\n", GOBAN_SIZE - row));
+        }
+
+        board.push_str(&labels());
+        board.push('\n');
+        board
+    }
+}
+
+/// Game diagram, each stone showing the number of the move which played it
+///
+/// Numbers are colored as their stone when `color`, otherwise only the side who opened tells them apart.
+/// Stones set up without a history keep their `X` and `O`.
+pub struct NumberedRenderer {
+    pub color: bool,
+}
+
+impl NumberedRenderer {
+    fn paint(&self, text: String, stone: Option<Stone>, highlight: Highlight) -> String {
+        let foreground = match stone {
+            Some(Stone::White) => Color::White,
+            _ => Color::Black,
+        };
+        let background = match highlight {
+            Highlight::None => Color::DarkYellow,
+            Highlight::LastMove => Color::DarkRed,
+            Highlight::WinningLine => Color::DarkGreen,
+        };
+
+        match self.color {
+            true => text.with(foreground).on(background).to_string(),
+            false => text,
+        }
+    }
+}
+
+impl Renderer for NumberedRenderer {
+    fn render_overlay(&self, gomoku: &Gomoku, overlay: &Overlay) -> String {
+        let goban = gomoku.board();
+        let highlights = highlights(gomoku);
+        let mut numbers = [[None; GOBAN_SIZE]; GOBAN_SIZE];
+        let mut board = String::new();
+
+        for (number, played) in gomoku.history().iter().enumerate() {
+            numbers[played.position.row][played.position.col] = Some(number + 1);
+        }
+
+        for (row, highlights) in highlights.iter().enumerate() {
+            for (col, highlight) in highlights.iter().enumerate() {
+                let stone = goban.get(row, col);
+                let (cell, color) = match (stone, numbers[row][col], overlay[row][col]) {
+                    (Some(_), Some(number), _) => (format!("{:>3}", number), stone),
+                    (Some(Stone::Black), None, _) => ("  X".to_string(), stone),
+                    (Some(Stone::White), None, _) => ("  O".to_string(), stone),
+                    (None, _, Some((marked, mark))) => (format!("  {}", mark.glyph(marked)), Some(marked)),
+                    (None, _, None) => ("  .".to_string(), None),
+                };
+
+                board.push_str(&self.paint(cell, color, *highlight));
+            }
+
+            board.push_str(&format!(" {}\n", GOBAN_SIZE - row));
+        }
+
+        board.push_str(&format!("  {}\n", labels().replace(' ', "  ")));
+        board
+    }
+}
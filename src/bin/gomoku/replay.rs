@@ -0,0 +1,91 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use gomoku::game::{Game, GameResult};
+use gomoku::gomoku::{GameState, Gomoku, SearchLimits};
+use gomoku::sgf::read_sgf;
+
+use crate::render::Renderer;
+
+/// Reads a game written by `save`, or an SGF file when its extension is `.sgf`
+pub fn load(path: &Path) -> Result<Game, String> {
+    let is_sgf = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("sgf"));
+
+    match is_sgf {
+        true => {
+            let text = fs::read_to_string(path).map_err(|error| error.to_string())?;
+
+            read_sgf(&text).map(Game::from).map_err(|error| error.to_string())
+        }
+        false => Game::load(path).map_err(|error| error.to_string()),
+    }
+}
+
+/// Steps through the moves of `game`, searching the best move of each position when `evaluate`
+pub fn replay(gomoku: &mut Gomoku, game: &Game, evaluate: bool, limits: SearchLimits, renderer: &dyn Renderer) {
+    // Every prefix of a playable game is playable
+    if let Err(error) = game.replay(gomoku) {
+        println!("{}", error);
+        return;
+    }
+
+    let length = game.moves.len();
+    let mut ply = 0;
+
+    loop {
+        let shown = Game {
+            moves: game.moves[..ply].to_vec(),
+            ..game.clone()
+        };
+        let state = shown.replay(gomoku).expect("the game was played through");
+
+        println!("{}", renderer.render(gomoku));
+
+        match shown.moves.last() {
+            Some(played) => println!("Move {}/{}: {:?} {}", ply, length, played.stone, played.position),
+            None => println!("Start, {} moves", length),
+        }
+
+        if evaluate && state == GameState::InProgress {
+            match gomoku.suggest_move(limits) {
+                Ok(report) => println!("Best: {} score {}", report.best_move, report.score),
+                Err(error) => println!("{}", error),
+            }
+        }
+
+        if ply == length {
+            match game.result {
+                Some(GameResult::BlackWon) => println!("Black won"),
+                Some(GameResult::WhiteWon) => println!("White won"),
+                Some(GameResult::Draw) => println!("Draw"),
+                None => {}
+            }
+        }
+
+        println!("Enter or next, back, first, last, a move number, or quit");
+
+        let mut input = String::new();
+
+        if io::stdin().read_line(&mut input).map_or(true, |read| read == 0) {
+            break;
+        }
+
+        ply = match input.trim() {
+            "" | "next" => (ply + 1).min(length),
+            "back" => ply.saturating_sub(1),
+            "first" => 0,
+            "last" => length,
+            "quit" => break,
+            number => match number.parse::<usize>() {
+                Ok(number) if number <= length => number,
+                _ => {
+                    println!("Unknown command `{}`", number);
+                    ply
+                }
+            },
+        };
+    }
+}
@@ -0,0 +1,167 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use gomoku::evaluator::Eval;
+use gomoku::game::{Annotation, Game, MoveReview};
+use gomoku::goban::Position;
+use gomoku::gomoku::{Gomoku, SearchLimits};
+use gomoku::sgf::SgfGame;
+
+use crate::render::eval_bar;
+
+fn score(score: isize) -> String {
+    match score {
+        isize::MAX => "win".to_string(),
+        isize::MIN => "loss".to_string(),
+        score => format!("{:+}", score),
+    }
+}
+
+fn to_eval(score: isize) -> Eval {
+    match score {
+        isize::MAX => Eval::Won,
+        isize::MIN => Eval::Lost,
+        score => Eval::Score(score),
+    }
+}
+
+fn pv(review: &MoveReview) -> String {
+    review.best.pv.iter().map(Position::to_string).collect::<Vec<_>>().join(" ")
+}
+
+/// Move number and side, `12.` for black and `12...` for white
+fn number(review: &MoveReview) -> String {
+    let dots = match review.ply % 2 {
+        0 => ".",
+        _ => "...",
+    };
+
+    format!("{}{}", review.ply / 2 + 1, dots)
+}
+
+/// The played move with its mark, such as `K11??`
+fn marked(review: &MoveReview, threshold: isize) -> String {
+    match review.annotation(threshold) {
+        Some(annotation) => format!("{}{}", review.played, annotation),
+        None => review.played.to_string(),
+    }
+}
+
+/// What the engine thinks of a marked move
+fn comment(review: &MoveReview, annotation: Annotation) -> String {
+    match annotation {
+        Annotation::Good => format!("only move scoring {}, pv {}", score(review.score), pv(review)),
+        _ => format!(
+            "scores {}, {} was better scoring {}, pv {}",
+            score(review.score),
+            review.best.position,
+            score(review.best.score),
+            pv(review)
+        ),
+    }
+}
+
+/// Table of every move with its mark and an eval bar from black's point of view, then the engine's
+/// continuations at the marked moves
+fn markdown(reviews: &[MoveReview], threshold: isize) -> String {
+    let mut text = String::from("# Game review\n\n");
+
+    writeln!(text, "Eval from black's point of view, best moves scored for their side, `??` losing more than {}.\n", threshold).ok();
+    text.push_str("| Move | Played | Eval | Best |\n| --- | --- | --- | --- |\n");
+
+    for review in reviews {
+        writeln!(
+            text,
+            "| {} | {} | `{}` | {} {} |",
+            number(review),
+            marked(review, threshold),
+            eval_bar(to_eval(review.black_score())),
+            review.best.position,
+            score(review.best.score)
+        )
+        .ok();
+    }
+
+    text.push_str("\n## Critical moments\n\n");
+
+    for review in reviews {
+        if let Some(annotation) = review.annotation(threshold) {
+            let side = format!("{:?}", review.stone).to_lowercase();
+
+            writeln!(text, "- **{} {}** ({}) {}", number(review), marked(review, threshold), side, comment(review, annotation)).ok();
+        }
+    }
+
+    text
+}
+
+/// The game with the engine's view of each move: `BM` and `TE` marks, a comment and a `V` value between
+/// -1 and 1 from black's point of view, which viewers plot as an eval graph
+fn sgf(game: &Game, reviews: &[MoveReview], threshold: isize) -> String {
+    let mut record = SgfGame::from(game);
+
+    for (review, properties) in reviews.iter().zip(record.move_properties.iter_mut()) {
+        let mark = match review.annotation(threshold) {
+            Some(Annotation::Good) => Some(("TE", "1")),
+            Some(Annotation::Mistake) => Some(("BM", "1")),
+            Some(Annotation::Blunder) => Some(("BM", "2")),
+            None => None,
+        };
+        let text = match review.annotation(threshold) {
+            Some(annotation) => format!("{}{} {}", review.played, annotation, comment(review, annotation)),
+            None => format!("{} scores {}, best {}", review.played, score(review.score), review.best.position),
+        };
+
+        if let Some((name, value)) = mark {
+            properties.push((name.to_string(), value.to_string()));
+        }
+
+        properties.push(("C".to_string(), text));
+        properties.push(("V".to_string(), format!("{:.3}", to_eval(review.black_score()).normalized())));
+    }
+
+    record.to_string()
+}
+
+/// Re-analyzes every move of `game` under `limits` and prints those losing more than `threshold` to
+/// the engine's best move, with the better move and its continuation
+///
+/// `annotate` receives the annotated game, as SGF with a `.sgf` extension and as markdown otherwise.
+pub fn review(gomoku: &mut Gomoku, game: &Game, limits: SearchLimits, threshold: isize, annotate: Option<&Path>) {
+    let reviews = match game.review(gomoku, limits) {
+        Ok(reviews) => reviews,
+        Err(error) => {
+            println!("{}", error);
+            return;
+        }
+    };
+    let blunders: Vec<&MoveReview> = reviews.iter().filter(|review| review.is_blunder(threshold)).collect();
+
+    for blunder in &blunders {
+        println!(
+            "{} {} ({:?}) {}",
+            number(blunder),
+            blunder.played,
+            blunder.stone,
+            comment(blunder, Annotation::Blunder)
+        );
+    }
+
+    println!("{} moves reviewed, {} blunders", reviews.len(), blunders.len());
+
+    if let Some(path) = annotate {
+        let is_sgf = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("sgf"));
+        let text = match is_sgf {
+            true => sgf(game, &reviews, threshold),
+            false => markdown(&reviews, threshold),
+        };
+
+        match fs::write(path, text) {
+            Ok(()) => println!("Annotated game written to {}", path.display()),
+            Err(error) => println!("Could not write {}: {}", path.display(), error),
+        }
+    }
+}
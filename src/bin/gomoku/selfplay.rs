@@ -0,0 +1,117 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde_json::json;
+
+use gomoku::book::random_opening;
+use gomoku::game::GameResult;
+use gomoku::goban::{Player, Position, Stone};
+use gomoku::gomoku::{GameState, Gomoku, PositionSetup};
+
+use crate::level::{Resignation, Strength};
+use crate::output::{Output, Verbosity};
+use crate::render::Renderer;
+
+// Two black stones and a white one, white to move
+const OPENING_STONES: usize = 3;
+
+/// Plays `games` games of the engine against itself, black and white searching with `strengths`,
+/// then prints how many each side won
+///
+/// Each game starts from its own random opening, drawn from the engine's seed so `--seed` replays the
+/// series. With `learn`, every game is counted in the book of the engine.
+pub fn selfplay(
+    gomoku: &mut Gomoku,
+    games: usize,
+    strengths: [Strength; 2],
+    resignation: Option<Resignation>,
+    learn: bool,
+    renderer: &dyn Renderer,
+    output: Output,
+) {
+    let (mut black_wins, mut white_wins, mut draws) = (0, 0, 0);
+    let mut rng = StdRng::seed_from_u64(gomoku.seed());
+
+    for game in 1..=games {
+        let opening = random_opening(&mut rng, OPENING_STONES);
+        let stones: Vec<String> = opening.iter().map(Position::to_string).collect();
+
+        gomoku.reset();
+        gomoku
+            .set_position(PositionSetup::Moves(opening), Stone::White)
+            .expect("openings are distinct stones");
+        output.say(Verbosity::Quiet, format!("Game {}, opening {}", game, stones.join(" ")));
+
+        let mut resignations = [resignation; 2];
+        let (state, reason) = loop {
+            let stone = gomoku.board().side_to_move();
+            let player = Player::from(stone);
+            let chosen = match strengths[stone as usize].choose(gomoku) {
+                Ok(chosen) => chosen,
+                Err(error) => {
+                    output.message(error);
+                    return;
+                }
+            };
+
+            if resignations[stone as usize].as_mut().is_some_and(|resignation| resignation.record(chosen.score())) {
+                output.say(Verbosity::Quiet, format!("{:?} resigned", stone));
+                break (GameState::Won(Player::from(stone.opponent())), "resign");
+            }
+
+            let played = gomoku.play(chosen.position(), player).map(|state| (chosen, state));
+
+            match played {
+                Ok((chosen, state)) => {
+                    output.say(Verbosity::Quiet, format!("{}. {:?} {}", gomoku.history().len(), stone, chosen.position()));
+                    output.event(json!({
+                        "event": "move",
+                        "game": game,
+                        "stone": stone,
+                        "move": chosen.position(),
+                        "search": chosen.to_json(),
+                    }));
+
+                    if state != GameState::InProgress {
+                        break (state, "board");
+                    }
+                }
+                Err(error) => {
+                    output.message(error);
+                    return;
+                }
+            }
+        };
+
+        output.say(Verbosity::Normal, renderer.render(gomoku));
+
+        if learn {
+            let winner = match state {
+                GameState::Won(player) => Some(Stone::from(player)),
+                _ => None,
+            };
+
+            gomoku.learn_book(winner);
+        }
+
+        let text = match state {
+            GameState::Won(Player::Opponent) => {
+                black_wins += 1;
+                "Black won"
+            }
+            GameState::Won(Player::Computer) => {
+                white_wins += 1;
+                "White won"
+            }
+            _ => {
+                draws += 1;
+                "Draw"
+            }
+        };
+
+        output.say(Verbosity::Quiet, text);
+        output.event(json!({ "event": "end", "game": game, "result": GameResult::from_state(state), "reason": reason }));
+    }
+
+    output.say(Verbosity::Quiet, format!("Black {}, White {}, Draws {}", black_wins, white_wins, draws));
+    output.event(json!({ "event": "tally", "black": black_wins, "white": white_wins, "draws": draws }));
+}
@@ -0,0 +1,286 @@
+// tonic's handlers answer with its large Status by value, helpers follow them
+#![allow(clippy::result_large_err)]
+
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status};
+
+use gomoku::error::GomokuError;
+use gomoku::goban::{Player, Position, Stone};
+use gomoku::gomoku::{GameState, Gomoku, IterationMetrics, SearchEvent, SearchInfo, SearchLimits, SearchReport};
+use gomoku::sessions::{bounded, with_game, SessionError, Sessions};
+
+mod pb {
+    tonic::include_proto!("gomoku");
+}
+
+use pb::engine_server::{Engine, EngineServer};
+use pb::search_update::Update;
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:50051";
+
+const DEFAULT_CANDIDATES: usize = 3;
+
+/// Games in play, keyed by the id `NewGame` answers
+#[derive(Default)]
+struct Service {
+    sessions: Sessions,
+}
+
+fn session_status(error: SessionError) -> Status {
+    match error {
+        SessionError::NotFound(_) => Status::not_found(error.to_string()),
+        SessionError::TooManyGames(_) => Status::resource_exhausted(error.to_string()),
+        SessionError::Engine(error) => status(error),
+    }
+}
+
+fn status(error: GomokuError) -> Status {
+    match error {
+        GomokuError::ParseError(_) | GomokuError::InvalidDepth(_) => Status::invalid_argument(error.to_string()),
+        _ => Status::failed_precondition(error.to_string()),
+    }
+}
+
+fn color(stone: Stone) -> pb::Color {
+    match stone {
+        Stone::Black => pb::Color::Black,
+        Stone::White => pb::Color::White,
+    }
+}
+
+fn game(id: u64, gomoku: &Gomoku) -> pb::Game {
+    let (state, winner) = match gomoku.game_state() {
+        GameState::InProgress => (pb::State::InProgress, pb::Color::Unspecified),
+        GameState::Won(Player::Opponent) => (pb::State::Won, pb::Color::Black),
+        GameState::Won(Player::Computer) => (pb::State::Won, pb::Color::White),
+        GameState::Draw => (pb::State::Draw, pb::Color::Unspecified),
+    };
+    let to_move = match state {
+        pb::State::InProgress => color(gomoku.board().side_to_move()),
+        _ => pb::Color::Unspecified,
+    };
+
+    pb::Game {
+        id,
+        moves: gomoku
+            .history()
+            .iter()
+            .map(|played| pb::Move {
+                color: color(played.stone).into(),
+                position: played.position.to_string(),
+            })
+            .collect(),
+        state: state.into(),
+        winner: winner.into(),
+        to_move: to_move.into(),
+    }
+}
+
+fn limits(limits: Option<pb::Limits>, gomoku: &Gomoku) -> SearchLimits {
+    let limits = limits.unwrap_or_default();
+    let mut search_limits = SearchLimits::new(match limits.depth {
+        0 => gomoku.search_depth(),
+        depth => depth as usize,
+    });
+
+    if limits.nodes != 0 {
+        search_limits = search_limits.with_nodes(limits.nodes as usize);
+    }
+    if limits.movetime_ms != 0 {
+        search_limits = search_limits.with_movetime(Duration::from_millis(limits.movetime_ms));
+    }
+    if limits.mate_in != 0 {
+        search_limits = search_limits.with_mate_in(limits.mate_in as usize);
+    }
+
+    bounded(search_limits)
+}
+
+fn suggestion(report: &SearchReport) -> pb::Suggestion {
+    pb::Suggestion {
+        position: report.best_move.to_string(),
+        score: report.score as i64,
+        depth: report.depth as u32,
+        nodes: report.visited_nodes as u64,
+    }
+}
+
+fn iteration(iteration: &IterationMetrics) -> pb::Iteration {
+    pb::Iteration {
+        depth: iteration.depth as u32,
+        nodes: iteration.nodes as u64,
+        time_ms: iteration.time_ms,
+    }
+}
+
+fn search_info(info: &SearchInfo) -> pb::SearchInfo {
+    pb::SearchInfo {
+        depth: info.depth as u32,
+        best_move: info.best_move.to_string(),
+        score: info.score as i64,
+        pv: info.pv.iter().map(Position::to_string).collect(),
+        nodes: info.nodes as u64,
+        nps: info.metrics.nps as u64,
+        time_ms: info.metrics.time_ms,
+        hashfull: info.metrics.hashfull as u32,
+        tt_hit_rate: info.metrics.tt_hit_rate(),
+        branching_factor: info.metrics.branching_factor,
+        iterations: info.metrics.iterations.iter().map(iteration).collect(),
+    }
+}
+
+#[tonic::async_trait]
+impl Engine for Service {
+    type SuggestStreamStream = ReceiverStream<Result<pb::SearchUpdate, Status>>;
+
+    async fn new_game(&self, _: Request<pb::NewGameRequest>) -> Result<Response<pb::Game>, Status> {
+        let (id, gomoku) = self.sessions.create().map_err(session_status)?;
+        let answer = game(id, &*gomoku.lock().await);
+
+        Ok(Response::new(answer))
+    }
+
+    async fn delete_game(
+        &self,
+        request: Request<pb::DeleteGameRequest>,
+    ) -> Result<Response<pb::DeleteGameResponse>, Status> {
+        self.sessions.remove(request.into_inner().game_id).map_err(session_status)?;
+
+        Ok(Response::new(pb::DeleteGameResponse {}))
+    }
+
+    async fn play(&self, request: Request<pb::PlayRequest>) -> Result<Response<pb::Game>, Status> {
+        let request = request.into_inner();
+        let id = request.game_id;
+
+        with_game(self.sessions.get(id).map_err(session_status)?, move |gomoku| {
+            let position = Position::from_coordinates(&request.position).map_err(|error| status(error.into()))?;
+            let player = match request.color() {
+                pb::Color::Black => Player::Opponent,
+                pb::Color::White => Player::Computer,
+                pb::Color::Unspecified if gomoku.board().side_to_move() == Stone::Black => Player::Opponent,
+                pb::Color::Unspecified => Player::Computer,
+            };
+
+            gomoku.play(position, player).map_err(status)?;
+
+            Ok(Response::new(game(id, gomoku)))
+        })
+        .await
+    }
+
+    async fn suggest(&self, request: Request<pb::SuggestRequest>) -> Result<Response<pb::Suggestion>, Status> {
+        let request = request.into_inner();
+
+        with_game(self.sessions.get(request.game_id).map_err(session_status)?, move |gomoku| {
+            let report = gomoku.suggest_move(limits(request.limits, gomoku)).map_err(status)?;
+
+            Ok(Response::new(suggestion(&report)))
+        })
+        .await
+    }
+
+    async fn analyze(&self, request: Request<pb::AnalyzeRequest>) -> Result<Response<pb::Analysis>, Status> {
+        let request = request.into_inner();
+        let count = match request.count {
+            0 => DEFAULT_CANDIDATES,
+            count => count as usize,
+        };
+
+        with_game(self.sessions.get(request.game_id).map_err(session_status)?, move |gomoku| {
+            let candidates = gomoku.analyze(limits(request.limits, gomoku), count).map_err(status)?;
+
+            Ok(Response::new(pb::Analysis {
+                candidates: candidates
+                    .into_iter()
+                    .map(|candidate| pb::Candidate {
+                        position: candidate.position.to_string(),
+                        score: candidate.score as i64,
+                        pv: candidate.pv.iter().map(Position::to_string).collect(),
+                    })
+                    .collect(),
+            }))
+        })
+        .await
+    }
+
+    /// Searches a fork of the game, so the game stays playable while the stream runs
+    async fn suggest_stream(
+        &self,
+        request: Request<pb::SuggestRequest>,
+    ) -> Result<Response<Self::SuggestStreamStream>, Status> {
+        let request = request.into_inner();
+        let mut fork = self.sessions.get(request.game_id).map_err(session_status)?.lock().await.clone();
+        let (sender, receiver) = mpsc::channel(16);
+        let (info_sender, stop) = (sender.clone(), Arc::new(AtomicBool::new(false)));
+        let info_stop = stop.clone();
+
+        fork.set_stop_flag(stop.clone());
+        fork.set_search_observer(move |event| {
+            if let SearchEvent::Iteration(info) = event {
+                let update = pb::SearchUpdate {
+                    update: Some(Update::Info(search_info(info))),
+                };
+
+                if info_sender.blocking_send(Ok(update)).is_err() {
+                    info_stop.store(true, Ordering::Relaxed);
+                }
+            }
+        });
+
+        // A dropped stream has nobody to answer, its search stops without waiting for the next
+        // iteration. The watch ends with the search, so the stream closes after its result
+        let (searched, finished) = oneshot::channel::<()>();
+        let watched = sender.clone();
+
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = watched.closed() => stop.store(true, Ordering::Relaxed),
+                _ = finished => {}
+            }
+        });
+
+        tokio::task::spawn_blocking(move || {
+            let update = fork
+                .suggest_move(limits(request.limits, &fork))
+                .map(|report| pb::SearchUpdate {
+                    update: Some(Update::Result(suggestion(&report))),
+                })
+                .map_err(status);
+
+            sender.blocking_send(update).ok();
+            drop(searched);
+        });
+
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+}
+
+/// gRPC server hosting concurrent games, listening on the address given as first argument
+#[tokio::main]
+async fn main() {
+    let address: SocketAddr = match std::env::args().nth(1).as_deref().unwrap_or(DEFAULT_ADDRESS).parse() {
+        Ok(address) => address,
+        Err(error) => {
+            eprintln!("Invalid address: {}", error);
+            return;
+        }
+    };
+
+    println!("Listening on {}", address);
+
+    let server = Server::builder()
+        .add_service(EngineServer::new(Service::default()))
+        .serve(address);
+
+    if let Err(error) = server.await {
+        eprintln!("{}", error);
+    }
+}
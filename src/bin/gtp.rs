@@ -0,0 +1,185 @@
+use std::io::{self, BufRead, Write};
+
+use gomoku::engine::about;
+use gomoku::goban::{CoordinateStyle, Goban, Move, Position, Stone, GOBAN_SIZE};
+use gomoku::gomoku::{Gomoku, PositionSetup, SearchLimits};
+
+const COMMANDS: &[&str] = &[
+    "protocol_version",
+    "name",
+    "version",
+    "known_command",
+    "list_commands",
+    "boardsize",
+    "clear_board",
+    "play",
+    "genmove",
+    "undo",
+    "final_status",
+    "final_score",
+    "showboard",
+    "quit",
+];
+
+/// Go Text Protocol front end, vertices use the `A` to `T` columns without `I` and row 1 at the bottom
+struct Gtp {
+    gomoku: Gomoku,
+    goban: Goban,
+    moves: Vec<Move>,
+}
+
+impl Gtp {
+    fn new() -> Self {
+        Self {
+            gomoku: Gomoku::default(),
+            goban: Goban::new(),
+            moves: Vec::new(),
+        }
+    }
+
+    /// Runs one command, the boolean telling whether the session goes on
+    fn handle(&mut self, command: &str, arguments: &[&str]) -> (Result<String, String>, bool) {
+        let response = match (command, arguments) {
+            ("protocol_version", _) => Ok("2".to_string()),
+            ("name", _) => Ok(about().name.to_string()),
+            ("version", _) => Ok(about().version.to_string()),
+            ("known_command", [name]) => Ok(COMMANDS.contains(name).to_string()),
+            ("list_commands", _) => Ok(COMMANDS.join("\n")),
+            ("boardsize", [size]) => match size.parse::<usize>() {
+                Ok(GOBAN_SIZE) => Ok(String::new()),
+                _ => Err("unacceptable size".to_string()),
+            },
+            ("clear_board", _) => {
+                self.goban = Goban::new();
+                self.moves.clear();
+                self.gomoku.reset();
+                Ok(String::new())
+            }
+            ("play", [color, vertex]) => self.play(color, vertex),
+            ("genmove", [color]) => self.genmove(color),
+            ("undo", _) => match self.moves.pop() {
+                Some(undone) => {
+                    self.goban.clear(&undone.position);
+                    Ok(String::new())
+                }
+                None => Err("cannot undo".to_string()),
+            },
+            ("final_status", _) => Ok(self.final_status().to_string()),
+            ("final_score", _) => Ok(self.final_score().to_string()),
+            ("showboard", _) => Ok(format!("\n{}", self.goban.display(CoordinateStyle::LettersSkipI))),
+            ("quit", _) => return (Ok(String::new()), false),
+            (command, _) if COMMANDS.contains(&command) => Err("syntax error".to_string()),
+            _ => Err("unknown command".to_string()),
+        };
+
+        (response, true)
+    }
+
+    fn play(&mut self, color: &str, vertex: &str) -> Result<String, String> {
+        let stone = Self::parse_color(color)?;
+        let position = Position::from_coordinates_with(vertex, CoordinateStyle::LettersSkipI)
+            .map_err(|_| "illegal move".to_string())?;
+
+        if self.is_over() || self.goban.get(position.row, position.col).is_some() {
+            return Err("illegal move".to_string());
+        }
+
+        self.apply(Move::new(stone, position));
+
+        Ok(String::new())
+    }
+
+    fn genmove(&mut self, color: &str) -> Result<String, String> {
+        let stone = Self::parse_color(color)?;
+        let limits = SearchLimits::new(self.gomoku.search_depth());
+
+        let report = self
+            .gomoku
+            .set_position(PositionSetup::Board(Box::new(self.goban.clone())), stone)
+            .and_then(|_| self.gomoku.suggest_move(limits))
+            .map_err(|error| error.to_string())?;
+
+        self.apply(Move::new(stone, report.best_move));
+
+        Ok(report.best_move.to_notation(CoordinateStyle::LettersSkipI))
+    }
+
+    fn apply(&mut self, played: Move) {
+        self.goban.apply_move(played);
+        self.moves.push(played);
+    }
+
+    fn is_over(&self) -> bool {
+        self.final_score() != "?"
+    }
+
+    fn final_status(&self) -> &'static str {
+        match self.final_score() {
+            "B+" => "black wins",
+            "W+" => "white wins",
+            "0" => "draw",
+            _ => "playing",
+        }
+    }
+
+    /// The winner as `B+` or `W+`, `0` for a draw and `?` while the game goes on
+    fn final_score(&self) -> &'static str {
+        if self.goban.has_five(Stone::Black) {
+            "B+"
+        } else if self.goban.has_five(Stone::White) {
+            "W+"
+        } else if self.goban.is_full() {
+            "0"
+        } else {
+            "?"
+        }
+    }
+
+    fn parse_color(color: &str) -> Result<Stone, String> {
+        match color.to_lowercase().as_str() {
+            "b" | "black" => Ok(Stone::Black),
+            "w" | "white" => Ok(Stone::White),
+            _ => Err("invalid color".to_string()),
+        }
+    }
+}
+
+fn main() {
+    let mut gtp = Gtp::new();
+    let stdin = io::stdin();
+
+    for line in stdin.lock().lines().map_while(Result::ok) {
+        // Comments and control characters are dropped before parsing
+        let line: String = line
+            .split('#')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .filter(|c| !c.is_control() || *c == '\t')
+            .collect();
+
+        let mut words = line.split_whitespace().peekable();
+        let id = words.next_if(|word| word.chars().all(|c| c.is_ascii_digit())).unwrap_or_default();
+
+        let command = match words.next() {
+            Some(command) => command.to_lowercase(),
+            None => continue,
+        };
+        let arguments: Vec<&str> = words.collect();
+
+        let (response, running) = gtp.handle(&command, &arguments);
+
+        let mut stdout = io::stdout().lock();
+
+        match response {
+            Ok(result) => writeln!(stdout, "={} {}\n", id, result),
+            Err(message) => writeln!(stdout, "?{} {}\n", id, message),
+        }
+        .ok();
+        stdout.flush().ok();
+
+        if !running {
+            break;
+        }
+    }
+}
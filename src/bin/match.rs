@@ -0,0 +1,259 @@
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use clap::Parser;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use gomoku::book::random_opening;
+use gomoku::bots::Bot;
+use gomoku::elo::{GameOutcome, Sprt, SprtResult, Tally};
+use gomoku::engine::{Engine, Searcher};
+use gomoku::error::GomokuError;
+use gomoku::goban::{Player, Position, Stone};
+use gomoku::gomoku::{GameState, Gomoku, PositionSetup, SearchLimits, DEFAULT_SEARCH_DEPTH};
+use gomoku::transposition_table::DEFAULT_SIZE_MB;
+
+/// One side of the match, as `depth=6,movetime=500,nodes=20000,hash=16`, or a baseline bot as `random`
+/// or `greedy`
+#[derive(Clone, Copy, Debug)]
+struct EngineConfig {
+    limits: SearchLimits,
+    hash_mb: usize,
+    bot: Option<Bot>,
+}
+
+impl EngineConfig {
+    fn engine(&self) -> Box<dyn Engine> {
+        match self.bot {
+            Some(bot) => Box::new(bot),
+            None => Box::new(Searcher { limits: self.limits }),
+        }
+    }
+}
+
+impl FromStr for EngineConfig {
+    type Err = String;
+
+    fn from_str(config: &str) -> Result<Self, Self::Err> {
+        let mut engine = EngineConfig {
+            limits: SearchLimits::new(DEFAULT_SEARCH_DEPTH),
+            hash_mb: DEFAULT_SIZE_MB,
+            bot: None,
+        };
+
+        if let Ok(bot) = config.parse() {
+            engine.bot = Some(bot);
+            return Ok(engine);
+        }
+
+        for option in config.split(',').filter(|option| !option.is_empty()) {
+            let (key, value) = option
+                .split_once('=')
+                .ok_or_else(|| format!("Expected key=value, random or greedy, got {}", option))?;
+            let value: usize = value.parse().map_err(|_| format!("Invalid value for {}: {}", key, value))?;
+
+            match key {
+                "depth" => engine.limits.depth = value,
+                "nodes" => engine.limits.nodes = Some(value),
+                "movetime" => engine.limits.movetime = Some(Duration::from_millis(value as u64)),
+                "hash" => engine.hash_mb = value,
+                _ => return Err(format!("Unknown option {}, expected depth, nodes, movetime or hash", key)),
+            }
+        }
+
+        Ok(engine)
+    }
+}
+
+impl fmt::Display for EngineConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(bot) = self.bot {
+            return write!(f, "{}", bot);
+        }
+
+        write!(f, "depth={}", self.limits.depth)?;
+
+        if let Some(nodes) = self.limits.nodes {
+            write!(f, ",nodes={}", nodes)?;
+        }
+
+        if let Some(movetime) = self.limits.movetime {
+            write!(f, ",movetime={}", movetime.as_millis())?;
+        }
+
+        write!(f, ",hash={}", self.hash_mb)
+    }
+}
+
+/// Plays two engine configurations against each other until the SPRT accepts an hypothesis
+#[derive(Parser, Debug)]
+#[clap(version, about)]
+struct Args {
+    /// first engine, whose strength is tested
+    #[clap(long, value_parser, default_value = "depth=4")]
+    engine_a: EngineConfig,
+
+    /// second engine, the reference
+    #[clap(long, value_parser, default_value = "depth=4")]
+    engine_b: EngineConfig,
+
+    /// games after which the match stops without a verdict, played in pairs
+    #[clap(long, value_parser, default_value_t = 1000)]
+    games: u32,
+
+    /// Elo difference of the null hypothesis
+    #[clap(long, value_parser, allow_hyphen_values = true, default_value_t = 0.0)]
+    elo0: f64,
+
+    /// Elo difference of the alternative hypothesis
+    #[clap(long, value_parser, allow_hyphen_values = true, default_value_t = 10.0)]
+    elo1: f64,
+
+    /// probability of accepting the alternative hypothesis when the null one holds
+    #[clap(long, value_parser, default_value_t = 0.05)]
+    alpha: f64,
+
+    /// probability of accepting the null hypothesis when the alternative one holds
+    #[clap(long, value_parser, default_value_t = 0.05)]
+    beta: f64,
+
+    /// file the result of every game is appended to, one `W`, `D` or `L` of engine A per line, for gomoku-elo
+    #[clap(long, value_parser)]
+    results: Option<PathBuf>,
+
+    /// seed of the openings, to replay a match
+    #[clap(long, value_parser)]
+    seed: Option<u64>,
+}
+
+/// Plays a game from `opening`, `black` and `white` searching with their own tables
+fn play_game(
+    opening: &[Position],
+    black: (&mut Gomoku, EngineConfig),
+    white: (&mut Gomoku, EngineConfig),
+) -> Result<GameState, GomokuError> {
+    let (black, black_config) = black;
+    let (white, white_config) = white;
+    let (mut black_engine, mut white_engine) = (black_config.engine(), white_config.engine());
+
+    for gomoku in [&mut *black, &mut *white] {
+        gomoku.reset();
+        gomoku
+            .set_position(PositionSetup::Moves(opening.to_vec()), Stone::White)
+            .expect("openings are three distinct stones");
+    }
+
+    loop {
+        let stone = black.board().side_to_move();
+        let (to_move, engine) = match stone {
+            Stone::Black => (&mut *black, &mut black_engine),
+            Stone::White => (&mut *white, &mut white_engine),
+        };
+
+        let best_move = match engine.choose_move(to_move) {
+            Ok(position) => position,
+            // Nowhere left to play
+            Err(GomokuError::NoMoveAvailable) => return Ok(GameState::Draw),
+            Err(error) => return Err(error),
+        };
+
+        let state = black.play(best_move, Player::from(stone)).expect("the engine plays legal moves");
+
+        white.play(best_move, Player::from(stone)).expect("both engines share the position");
+
+        if state != GameState::InProgress {
+            return Ok(state);
+        }
+    }
+}
+
+/// Result of engine A, which played `a_stone`
+fn outcome(state: GameState, a_stone: Stone) -> GameOutcome {
+    match state {
+        GameState::Won(player) if Stone::from(player) == a_stone => GameOutcome::Win,
+        GameState::Won(_) => GameOutcome::Loss,
+        _ => GameOutcome::Draw,
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+    let sprt = Sprt::new(args.elo0, args.elo1, args.alpha, args.beta);
+    let (lower, upper) = sprt.bounds();
+    let mut rng = match args.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let (mut a, mut b) = (Gomoku::with_hash_size(args.engine_a.hash_mb), Gomoku::with_hash_size(args.engine_b.hash_mb));
+    let mut tally = Tally::default();
+    let mut results = match &args.results {
+        Some(path) => match OpenOptions::new().create(true).append(true).open(path) {
+            Ok(file) => Some(file),
+            Err(error) => {
+                println!("Could not open {}: {}", path.display(), error);
+                return;
+            }
+        },
+        None => None,
+    };
+
+    println!("A: {}, B: {}, H0: {} Elo, H1: {} Elo", args.engine_a, args.engine_b, args.elo0, args.elo1);
+
+    let result = loop {
+        if tally.games() + 2 > args.games {
+            break SprtResult::Continue;
+        }
+
+        // Two black stones and a white one, played once by each engine with either color to even out a lopsided one
+        let opening = random_opening(&mut rng, 3);
+
+        let games = [
+            play_game(&opening, (&mut a, args.engine_a), (&mut b, args.engine_b)).map(|state| outcome(state, Stone::Black)),
+            play_game(&opening, (&mut b, args.engine_b), (&mut a, args.engine_a)).map(|state| outcome(state, Stone::White)),
+        ];
+        let outcomes = match games {
+            [Ok(first), Ok(second)] => [first, second],
+            [Err(error), _] | [_, Err(error)] => {
+                println!("A game failed after {} games: {}", tally.games(), error);
+                return;
+            }
+        };
+
+        for outcome in outcomes {
+            tally.add(outcome);
+
+            if let Some(file) = &mut results {
+                if let Err(error) = writeln!(file, "{}", outcome) {
+                    println!("Could not write the result: {}", error);
+                }
+            }
+        }
+
+        println!(
+            "Games {}: {}, score {:.3}, Elo {}, LLR {:.2} [{:.2}, {:.2}]",
+            tally.games(),
+            tally,
+            tally.score(),
+            tally.elo().expect("games were played"),
+            sprt.llr(&tally),
+            lower,
+            upper
+        );
+
+        match sprt.test(&tally) {
+            SprtResult::Continue => {}
+            result => break result,
+        }
+    };
+
+    match result {
+        SprtResult::AcceptH1 => println!("H1 accepted: A is stronger than B by {} Elo or more", args.elo1),
+        SprtResult::AcceptH0 => println!("H0 accepted: A is not stronger than B by more than {} Elo", args.elo0),
+        SprtResult::Continue => println!("No verdict after {} games", tally.games()),
+    }
+}
@@ -0,0 +1,274 @@
+use std::io::{self, BufRead, Write};
+use std::time::Duration;
+
+use gomoku::engine::about;
+use gomoku::goban::{Goban, Move, Position, Stone, GOBAN_SIZE};
+use gomoku::gomoku::{Gomoku, PositionSetup, SearchLimits};
+
+// The search deepens until it runs out of time, this only bounds it on trivial positions
+const MAX_DEPTH: usize = 20;
+
+// Share of the time a move may take that is actually spent searching
+const TIME_MARGIN: f64 = 0.8;
+
+// Bits of the `INFO rule` mask for exact five, renju and caro, the engine plays freestyle only.
+// The continuous game bit only asks to keep playing on a full board and changes nothing here
+const UNSUPPORTED_RULES: u32 = 0b1 | 0b100 | 0b1000;
+
+/// Brain side of the Gomocup protocol, as spoken with Piskvork and the tournament manager
+///
+/// Coordinates are `x,y`, the column then the row counted from zero.
+struct Brain {
+    gomoku: Gomoku,
+    goban: Goban,
+    own: Stone,
+    timeout_turn: Option<Duration>,
+    /// Time of the whole match, for managers not sending `time_left`
+    timeout_match: Option<Duration>,
+    time_left: Option<Duration>,
+}
+
+impl Brain {
+    fn new() -> Self {
+        Self {
+            gomoku: Gomoku::default(),
+            goban: Goban::new(),
+            own: Stone::Black,
+            timeout_turn: None,
+            timeout_match: None,
+            time_left: None,
+        }
+    }
+
+    /// Answers one command, `None` once the manager ended the session
+    fn handle(&mut self, line: &str, input: &mut impl Iterator<Item = String>) -> Option<Vec<String>> {
+        let (command, arguments) = match line.trim().split_once(' ') {
+            Some((command, arguments)) => (command.to_uppercase(), arguments.trim()),
+            None => (line.trim().to_uppercase(), ""),
+        };
+
+        let answer = match command.as_str() {
+            "START" => match arguments.parse::<usize>() {
+                Ok(GOBAN_SIZE) => {
+                    self.restart();
+                    vec!["OK".to_string()]
+                }
+                _ => vec![format!("ERROR only {}x{} boards are supported", GOBAN_SIZE, GOBAN_SIZE)],
+            },
+            "RESTART" => {
+                self.restart();
+                vec!["OK".to_string()]
+            }
+            "BEGIN" => {
+                self.own = Stone::Black;
+                self.think()
+            }
+            "TURN" => match Self::parse_position(arguments) {
+                Some(position) if self.goban.get(position.row, position.col).is_none() => {
+                    self.own = self.stone_to_move().opponent();
+                    self.goban.apply_move(Move::new(self.own.opponent(), position));
+                    self.think()
+                }
+                _ => vec![format!("ERROR invalid move {}", arguments)],
+            },
+            "BOARD" => self.read_board(input),
+            "TAKEBACK" => match Self::parse_position(arguments) {
+                Some(position) => {
+                    self.goban.clear(&position);
+                    vec!["OK".to_string()]
+                }
+                None => vec![format!("ERROR invalid move {}", arguments)],
+            },
+            "INFO" => self.info(arguments),
+            "ABOUT" => vec![about().to_string()],
+            "END" => return None,
+            _ => vec![format!("UNKNOWN {}", command)],
+        };
+
+        Some(answer)
+    }
+
+    fn restart(&mut self) {
+        self.goban = Goban::new();
+        self.gomoku.reset();
+    }
+
+    /// Stones are given as `x,y,field`, field 1 being the brain's stones
+    fn read_board(&mut self, input: &mut impl Iterator<Item = String>) -> Vec<String> {
+        let mut own = Vec::new();
+        let mut opponent = Vec::new();
+
+        for line in input.by_ref() {
+            let line = line.trim();
+
+            if line.eq_ignore_ascii_case("DONE") {
+                break;
+            }
+
+            let (coordinates, field) = match line.rsplit_once(',') {
+                Some(split) => split,
+                None => return vec![format!("ERROR invalid board line {}", line)],
+            };
+
+            match (Self::parse_position(coordinates), field.trim()) {
+                (Some(position), "1") => own.push(position),
+                (Some(position), _) => opponent.push(position),
+                (None, _) => return vec![format!("ERROR invalid board line {}", line)],
+            }
+        }
+
+        // Black started, so it is to move whenever both sides have as many stones
+        self.own = match own.len() == opponent.len() {
+            true => Stone::Black,
+            false => Stone::White,
+        };
+        self.goban = Goban::new();
+
+        for position in own {
+            self.goban.apply_move(Move::new(self.own, position));
+        }
+        for position in opponent {
+            self.goban.apply_move(Move::new(self.own.opponent(), position));
+        }
+
+        self.think()
+    }
+
+    fn info(&mut self, arguments: &str) -> Vec<String> {
+        let (key, value) = arguments.split_once(' ').unwrap_or((arguments, ""));
+        let milliseconds = value.trim().parse::<u64>().ok().map(Duration::from_millis);
+
+        match key.to_lowercase().as_str() {
+            "timeout_turn" => self.timeout_turn = milliseconds.filter(|timeout| !timeout.is_zero()),
+            // Zero is a match without time limit
+            "timeout_match" => self.timeout_match = milliseconds.filter(|timeout| !timeout.is_zero()),
+            "time_left" => self.time_left = milliseconds,
+            "rule" => match value.trim().parse::<u32>() {
+                Ok(rule) if rule & UNSUPPORTED_RULES != 0 => {
+                    return vec![format!("ERROR rule {} is not supported, only freestyle is", rule)];
+                }
+                Ok(_) => {}
+                Err(_) => return vec![format!("ERROR invalid rule {}", value.trim())],
+            },
+            _ => {}
+        }
+
+        Vec::new()
+    }
+
+    fn stone_to_move(&self) -> Stone {
+        match self.goban.count(Stone::Black) == self.goban.count(Stone::White) {
+            true => Stone::Black,
+            false => Stone::White,
+        }
+    }
+
+    /// Searches and plays the brain's move, answered as `x,y`
+    fn think(&mut self) -> Vec<String> {
+        let mut answer = Vec::new();
+        let limits = self.limits();
+
+        let search = self
+            .gomoku
+            .set_position(PositionSetup::Board(Box::new(self.goban.clone())), self.own)
+            .and_then(|_| self.gomoku.suggest_move(limits));
+
+        let position = match search {
+            Ok(report) => {
+                answer.push(format!("MESSAGE depth {} score {} nodes {}", report.depth, report.score, report.visited_nodes));
+                report.best_move
+            }
+            Err(error) => return vec![format!("ERROR {}", error)],
+        };
+
+        self.goban.apply_move(Move::new(self.own, position));
+        answer.push(format!("{},{}", position.col, position.row));
+
+        answer
+    }
+
+    fn limits(&self) -> SearchLimits {
+        let budget = match (self.timeout_turn, self.time_left.or(self.timeout_match)) {
+            (Some(turn), Some(left)) => Some(turn.min(left / 10)),
+            (turn, left) => turn.or(left.map(|left| left / 10)),
+        };
+
+        match budget {
+            Some(budget) => SearchLimits::new(MAX_DEPTH).with_movetime(budget.mul_f64(TIME_MARGIN)),
+            None => SearchLimits::new(self.gomoku.search_depth()),
+        }
+    }
+
+    fn parse_position(coordinates: &str) -> Option<Position> {
+        let (x, y) = coordinates.trim().split_once(',')?;
+        let position = Position::new(y.trim().parse().ok()?, x.trim().parse().ok()?);
+
+        match position.row < GOBAN_SIZE && position.col < GOBAN_SIZE {
+            true => Some(position),
+            false => None,
+        }
+    }
+}
+
+fn main() {
+    let mut brain = Brain::new();
+    let stdin = io::stdin();
+    let mut input = stdin.lock().lines().map_while(Result::ok);
+
+    while let Some(line) = input.next() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let answer = match brain.handle(&line, &mut input) {
+            Some(answer) => answer,
+            None => break,
+        };
+
+        let mut stdout = io::stdout().lock();
+
+        for line in answer {
+            writeln!(stdout, "{}", line).ok();
+        }
+        stdout.flush().ok();
+    }
+}
+
+#[cfg(test)]
+mod brain_tests {
+    use std::time::Duration;
+
+    use crate::Brain;
+
+    #[test]
+    fn it_reads_the_info_the_manager_sends() {
+        let mut brain = Brain::new();
+
+        assert!(brain.info("timeout_turn 5000").is_empty());
+        assert!(brain.info("timeout_match 180000").is_empty());
+        assert_eq!(brain.timeout_turn, Some(Duration::from_secs(5)));
+        assert_eq!(brain.timeout_match, Some(Duration::from_secs(180)));
+        assert_eq!(brain.time_left, None);
+
+        // Without time_left, the match time bounds the move
+        assert_eq!(brain.limits().movetime, Some(Duration::from_secs(5).mul_f64(0.8)));
+
+        assert!(brain.info("time_left 20000").is_empty());
+        assert_eq!(brain.limits().movetime, Some(Duration::from_secs(2).mul_f64(0.8)));
+
+        assert!(brain.info("timeout_match 0").is_empty());
+        assert_eq!(brain.timeout_match, None);
+    }
+
+    #[test]
+    fn it_only_plays_freestyle() {
+        let mut brain = Brain::new();
+
+        assert!(brain.info("rule 0").is_empty());
+        assert!(brain.info("rule 2").is_empty());
+
+        for rule in ["1", "3", "4", "6", "8"] {
+            assert!(brain.info(&format!("rule {}", rule))[0].starts_with("ERROR"), "rule {}", rule);
+        }
+    }
+}
@@ -1,31 +1,175 @@
-use std::time::Instant;
-use gomoku::goban::{Player, Position};
-use gomoku::gomoku::{Gomoku};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
 
-fn main() {
-    let mut gomoku = Gomoku::default();
+use clap::Parser;
+
+use gomoku::game::Game;
+use gomoku::goban::{Position, Stone};
+use gomoku::gomoku::{CacheRetention, GameState, Gomoku, PositionSetup, SearchLimits, DEFAULT_SEARCH_DEPTH};
+use gomoku::sgf::read_sgf;
+use gomoku::transposition_table::DEFAULT_SIZE_MB;
+
+// Searched when no position is given, white to move
+const DEFAULT_POSITION: &str = "D16 C19 E15 D19 F14 G13 H16 B19 E19";
+
+/// Searches a position several times under fixed limits and prints the time and node statistics
+/// of every search, for profilers and timing changes to the engine
+#[derive(Parser, Debug)]
+#[clap(version, about)]
+struct Args {
+    /// move list such as `j10 k11 h9`, or a file holding one, a saved game or an SGF file
+    #[clap(value_parser)]
+    position: Vec<String>,
+
+    /// search depth
+    #[clap(long, value_parser, default_value_t = DEFAULT_SEARCH_DEPTH)]
+    depth: usize,
+
+    /// milliseconds after which a search stops
+    #[clap(long, value_parser)]
+    movetime: Option<u64>,
+
+    /// nodes visited after which a search stops
+    #[clap(long, value_parser)]
+    nodes: Option<usize>,
+
+    /// searches of the position
+    #[clap(long, value_parser, default_value_t = 1)]
+    iterations: usize,
+
+    /// transposition table size in megabytes
+    #[clap(long, value_parser, default_value_t = DEFAULT_SIZE_MB)]
+    hash: usize,
+
+    /// keeps the caches from one search to the next instead of clearing them
+    #[clap(long)]
+    warm: bool,
 
-    gomoku.play(Position {row: 3, col: 3}, Player::Opponent).ok();
-    gomoku.play(Position {row: 0, col: 2}, Player::Computer).ok();
+    /// prints the engine diagnostics of every search
+    #[clap(short, long)]
+    verbose: bool,
+}
+
+/// Moves of a saved game, an SGF file or a text file holding a move list
+fn load(path: &Path) -> Result<Vec<Position>, String> {
+    let extension = path.extension().and_then(|extension| extension.to_str()).map(str::to_ascii_lowercase);
+    let game = match extension.as_deref() {
+        Some("sgf") => {
+            let text = fs::read_to_string(path).map_err(|error| error.to_string())?;
+
+            read_sgf(&text).map(Game::from).map_err(|error| error.to_string())?
+        }
+        Some("json") => Game::load(path).map_err(|error| error.to_string())?,
+        _ => {
+            let text = fs::read_to_string(path).map_err(|error| error.to_string())?;
+
+            return parse(&text);
+        }
+    };
+
+    Ok(game.moves.iter().map(|played| played.position).collect())
+}
+
+fn parse(moves: &str) -> Result<Vec<Position>, String> {
+    moves
+        .split_whitespace()
+        .map(Position::from_coordinates)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| error.to_string())
+}
+
+fn main() {
+    let args = Args::parse();
+    let moves = match args.position.as_slice() {
+        [] => parse(DEFAULT_POSITION),
+        [file] if Path::new(file).is_file() => load(Path::new(file)),
+        moves => parse(&moves.join(" ")),
+    };
+    let moves = match moves {
+        Ok(moves) => moves,
+        Err(error) => {
+            println!("{}", error);
+            return;
+        }
+    };
+    let side = match moves.len() % 2 {
+        0 => Stone::Black,
+        _ => Stone::White,
+    };
+    let limits = SearchLimits {
+        nodes: args.nodes,
+        movetime: args.movetime.map(Duration::from_millis),
+        ..SearchLimits::new(args.depth)
+    };
+    let mut gomoku = Gomoku::with_hash_size(args.hash);
 
-    gomoku.play(Position {row: 4, col: 4}, Player::Opponent).ok();
-    gomoku.play(Position {row: 0, col: 3}, Player::Computer).ok();
+    if args.verbose {
+        gomoku.set_logger(|message| println!("{}", message));
+    }
 
-    gomoku.play(Position {row: 5, col: 5}, Player::Opponent).ok();
-    gomoku.play(Position {row: 6, col: 6}, Player::Computer).ok();
+    match gomoku.set_position(PositionSetup::Moves(moves.clone()), side) {
+        Ok(GameState::InProgress) => println!("{}", gomoku),
+        Ok(_) => {
+            println!("{}", gomoku);
+            println!("The game is over");
+            return;
+        }
+        Err(error) => {
+            println!("{}", error);
+            return;
+        }
+    }
 
-    gomoku.play(Position {row: 3, col: 7}, Player::Opponent).ok();
-    gomoku.play(Position {row: 0, col: 1}, Player::Computer).ok();
+    let mut times = Vec::new();
+    let mut total_nodes = 0;
 
-    gomoku.play(Position {row: 0, col: 4}, Player::Opponent).ok();
+    for iteration in 1..=args.iterations {
+        if !args.warm {
+            gomoku.new_game(CacheRetention::Clear);
+            gomoku
+                .set_position(PositionSetup::Moves(moves.clone()), side)
+                .expect("the position was set up once already");
+        }
 
-    gomoku.print_board();
+        let start = Instant::now();
+        let report = match gomoku.suggest_move(limits) {
+            Ok(report) => report,
+            Err(error) => {
+                println!("{}", error);
+                return;
+            }
+        };
+        let elapsed = start.elapsed();
 
-    let t0 = Instant::now();
+        println!(
+            "Search {}: {} at depth {}, score {}, {} nodes, {} evaluated, eval cache {} hits {} misses, {}",
+            iteration,
+            report.best_move,
+            report.depth,
+            report.score,
+            report.visited_nodes,
+            report.evaluated_nodes,
+            report.eval_cache_hits,
+            report.eval_cache_misses,
+            report.metrics
+        );
 
-    gomoku.play_computer_move(4);
+        times.push(elapsed);
+        total_nodes += report.visited_nodes;
+    }
 
-    println!("Took: {} ms", t0.elapsed().as_millis());
+    if let (Some(fastest), Some(slowest)) = (times.iter().min(), times.iter().max()) {
+        let total: Duration = times.iter().sum();
 
-    gomoku.print_board();
+        println!(
+            "{} searches: {} ms on average, {} to {} ms, {} nodes on average, {} nps",
+            times.len(),
+            total.as_millis() / times.len() as u128,
+            fastest.as_millis(),
+            slowest.as_millis(),
+            total_nodes / times.len(),
+            (total_nodes as f64 / total.as_secs_f64().max(f64::EPSILON)) as u64
+        );
+    }
 }
@@ -0,0 +1,340 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::time::Duration;
+
+use clap::Parser;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use gomoku::book::random_opening;
+use gomoku::elo::{GameOutcome, Tally};
+use gomoku::engine::BENCH_POSITIONS;
+use gomoku::goban::{Player, Position, Stone};
+use gomoku::gomoku::{GameState, Gomoku, PositionSetup, SearchLimits, DEFAULT_SEARCH_DEPTH};
+use gomoku::transposition_table::DEFAULT_SIZE_MB;
+
+/// Compares this build with a pinned reference engine speaking the protocol of gomoku-uci,
+/// on the bench positions then over a short match, to catch accidental regressions before a release
+#[derive(Parser, Debug)]
+#[clap(version, about)]
+struct Args {
+    /// reference engine, such as the gomoku-uci binary of the last release
+    #[clap(long, value_parser)]
+    reference: PathBuf,
+
+    /// search depth of both engines
+    #[clap(long, value_parser, default_value_t = DEFAULT_SEARCH_DEPTH)]
+    depth: usize,
+
+    /// nodes visited after which a search stops
+    #[clap(long, value_parser)]
+    nodes: Option<usize>,
+
+    /// milliseconds after which a search stops
+    #[clap(long, value_parser)]
+    movetime: Option<u64>,
+
+    /// transposition table of both engines, in megabytes
+    #[clap(long, value_parser, default_value_t = DEFAULT_SIZE_MB)]
+    hash: usize,
+
+    /// match games, played in pairs from the same opening, none to only compare the bench positions
+    #[clap(long, value_parser, default_value_t = 20)]
+    games: u32,
+
+    /// seed of the openings
+    #[clap(long, value_parser, default_value_t = 0)]
+    seed: u64,
+}
+
+/// What an engine answered to `go`
+struct Answer {
+    best_move: Position,
+    /// From the side to move's point of view, `isize::MAX` and `isize::MIN` for proven wins and losses
+    score: Option<isize>,
+    nodes: Option<usize>,
+}
+
+/// An engine running in another process, driven through the protocol of gomoku-uci
+struct UciEngine {
+    child: Child,
+    input: ChildStdin,
+    output: BufReader<ChildStdout>,
+}
+
+impl UciEngine {
+    fn spawn(path: &Path, hash: usize) -> io::Result<Self> {
+        let mut child = Command::new(path).stdin(Stdio::piped()).stdout(Stdio::piped()).spawn()?;
+        let input = child.stdin.take().expect("stdin is piped");
+        let output = BufReader::new(child.stdout.take().expect("stdout is piped"));
+        let mut engine = Self { child, input, output };
+
+        engine.send("uci")?;
+        engine.read_until("uciok")?;
+        engine.send(&format!("setoption name Hash value {}", hash))?;
+        engine.send("isready")?;
+        engine.read_until("readyok")?;
+
+        Ok(engine)
+    }
+
+    fn send(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.input, "{}", line)?;
+        self.input.flush()
+    }
+
+    /// Lines up to the first starting with `prefix`, which is returned last
+    fn read_until(&mut self, prefix: &str) -> io::Result<Vec<String>> {
+        let mut lines = Vec::new();
+
+        loop {
+            let mut line = String::new();
+
+            if self.output.read_line(&mut line)? == 0 {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "the reference engine quit"));
+            }
+
+            let line = line.trim().to_string();
+            let done = line.starts_with(prefix);
+
+            lines.push(line);
+
+            if done {
+                return Ok(lines);
+            }
+        }
+    }
+
+    fn new_game(&mut self) -> io::Result<()> {
+        self.send("ucinewgame")?;
+        self.send("isready")?;
+        self.read_until("readyok").map(|_| ())
+    }
+
+    fn search(&mut self, moves: &[Position], go: &str) -> io::Result<Answer> {
+        let moves: Vec<String> = moves.iter().map(Position::to_string).collect();
+
+        match moves.is_empty() {
+            true => self.send("position startpos")?,
+            false => self.send(&format!("position startpos moves {}", moves.join(" ")))?,
+        }
+
+        self.send(go)?;
+
+        let lines = self.read_until("bestmove")?;
+        let invalid = |line: &str| io::Error::new(io::ErrorKind::InvalidData, format!("unexpected answer `{}`", line));
+        let last = lines.last().expect("read_until returns the bestmove line");
+        let best_move = last
+            .split_whitespace()
+            .nth(1)
+            .and_then(|position| Position::from_coordinates(position).ok())
+            .ok_or_else(|| invalid(last))?;
+        let info = lines.iter().rev().find(|line| line.starts_with("info depth"));
+        let value = |key: &str| -> Option<Vec<&str>> {
+            let words: Vec<&str> = info?.split_whitespace().collect();
+            let index = words.iter().position(|word| *word == key)?;
+
+            Some(words[index + 1..].to_vec())
+        };
+        let score = value("score").and_then(|words| match words.as_slice() {
+            ["cp", score, ..] => score.parse().ok(),
+            ["mate", moves, ..] if moves.starts_with('-') => Some(isize::MIN),
+            ["mate", _, ..] => Some(isize::MAX),
+            _ => None,
+        });
+        let nodes = value("nodes").and_then(|words| words.first()?.parse().ok());
+
+        Ok(Answer { best_move, score, nodes })
+    }
+}
+
+impl Drop for UciEngine {
+    fn drop(&mut self) {
+        self.send("quit").ok();
+        self.child.wait().ok();
+    }
+}
+
+fn side_to_move(moves: &[Position]) -> Stone {
+    match moves.len() % 2 {
+        0 => Stone::Black,
+        _ => Stone::White,
+    }
+}
+
+fn score(score: Option<isize>) -> String {
+    match score {
+        Some(isize::MAX) => "win".to_string(),
+        Some(isize::MIN) => "loss".to_string(),
+        Some(score) => score.to_string(),
+        None => "-".to_string(),
+    }
+}
+
+fn percent(reference: usize, candidate: usize) -> String {
+    match reference {
+        0 => "-".to_string(),
+        _ => format!("{:+.1}%", 100.0 * (candidate as f64 - reference as f64) / reference as f64),
+    }
+}
+
+/// Searches the bench positions with both engines, printing how their nodes, scores and moves differ
+fn compare_positions(gomoku: &mut Gomoku, reference: &mut UciEngine, limits: SearchLimits, go: &str) -> io::Result<()> {
+    let (mut reference_nodes, mut candidate_nodes, mut different_moves) = (0, 0, 0);
+
+    for (index, moves) in BENCH_POSITIONS.iter().enumerate() {
+        let moves: Vec<Position> = moves
+            .split_whitespace()
+            .map(|position| Position::from_coordinates(position).expect("bench positions are valid move lists"))
+            .collect();
+
+        gomoku.reset();
+        gomoku
+            .set_position(PositionSetup::Moves(moves.clone()), side_to_move(&moves))
+            .expect("bench positions are playable");
+        reference.new_game()?;
+
+        let expected = reference.search(&moves, go)?;
+        let report = match gomoku.suggest_move(limits) {
+            Ok(report) => report,
+            Err(error) => return Err(io::Error::other(error.to_string())),
+        };
+        let nodes = expected.nodes.unwrap_or_default();
+
+        reference_nodes += nodes;
+        candidate_nodes += report.visited_nodes;
+
+        if report.best_move != expected.best_move {
+            different_moves += 1;
+        }
+
+        println!(
+            "Position {}/{}: nodes {} -> {} ({}), score {} -> {}, move {} -> {}{}",
+            index + 1,
+            BENCH_POSITIONS.len(),
+            nodes,
+            report.visited_nodes,
+            percent(nodes, report.visited_nodes),
+            score(expected.score),
+            score(Some(report.score)),
+            expected.best_move,
+            report.best_move,
+            if report.best_move != expected.best_move { ", differs" } else { "" }
+        );
+    }
+
+    println!(
+        "Bench: nodes {} -> {} ({}), {} of {} moves differ",
+        reference_nodes,
+        candidate_nodes,
+        percent(reference_nodes, candidate_nodes),
+        different_moves,
+        BENCH_POSITIONS.len()
+    );
+
+    Ok(())
+}
+
+/// Plays a game from `opening`, this build playing `candidate`, and returns its result for this build
+fn play_game(
+    gomoku: &mut Gomoku,
+    reference: &mut UciEngine,
+    opening: &[Position],
+    candidate: Stone,
+    limits: SearchLimits,
+    go: &str,
+) -> io::Result<GameOutcome> {
+    let mut moves = opening.to_vec();
+
+    gomoku.reset();
+    gomoku
+        .set_position(PositionSetup::Moves(moves.clone()), side_to_move(&moves))
+        .expect("openings are three distinct stones");
+    reference.new_game()?;
+
+    loop {
+        let stone = side_to_move(&moves);
+        let best_move = match stone == candidate {
+            true => match gomoku.suggest_move(limits) {
+                Ok(report) => report.best_move,
+                // Nowhere left to play
+                Err(_) => return Ok(GameOutcome::Draw),
+            },
+            false => reference.search(&moves, go)?.best_move,
+        };
+
+        let state = match gomoku.play(best_move, Player::from(stone)) {
+            Ok(state) => state,
+            // An illegal move of the reference forfeits the game
+            Err(_) if stone != candidate => return Ok(GameOutcome::Win),
+            Err(error) => return Err(io::Error::other(error.to_string())),
+        };
+
+        moves.push(best_move);
+
+        match state {
+            GameState::InProgress => {}
+            GameState::Won(player) if Stone::from(player) == candidate => return Ok(GameOutcome::Win),
+            GameState::Won(_) => return Ok(GameOutcome::Loss),
+            GameState::Draw => return Ok(GameOutcome::Draw),
+        }
+    }
+}
+
+fn run(args: &Args) -> io::Result<()> {
+    let depth = (args.depth + args.depth % 2).max(2);
+    let limits = SearchLimits {
+        nodes: args.nodes,
+        movetime: args.movetime.map(Duration::from_millis),
+        ..SearchLimits::new(depth)
+    };
+    let mut go = format!("go depth {}", depth);
+
+    if let Some(nodes) = args.nodes {
+        go += &format!(" nodes {}", nodes);
+    }
+
+    if let Some(movetime) = args.movetime {
+        go += &format!(" movetime {}", movetime);
+    }
+
+    let mut reference = UciEngine::spawn(&args.reference, args.hash)?;
+    let mut gomoku = Gomoku::with_hash_size(args.hash);
+
+    println!("Reference {}, candidate this build, {}", args.reference.display(), go);
+
+    compare_positions(&mut gomoku, &mut reference, limits, &go)?;
+
+    let mut rng = StdRng::seed_from_u64(args.seed);
+    let mut tally = Tally::default();
+
+    while tally.games() + 2 <= args.games {
+        // Two black stones and a white one, played once with either color
+        let opening = random_opening(&mut rng, 3);
+
+        for candidate in [Stone::Black, Stone::White] {
+            tally.add(play_game(&mut gomoku, &mut reference, &opening, candidate, limits, &go)?);
+        }
+
+        println!("Games {}: {}, score {:.3}", tally.games(), tally, tally.score());
+    }
+
+    if let Some(estimate) = tally.elo() {
+        println!("Match: {}, Elo {} against the reference", tally, estimate);
+
+        if estimate.elo + estimate.margin < 0.0 {
+            println!("The candidate is weaker than the reference");
+        }
+    }
+
+    Ok(())
+}
+
+fn main() {
+    let args = Args::parse();
+
+    if let Err(error) = run(&args) {
+        println!("{}: {}", args.reference.display(), error);
+    }
+}
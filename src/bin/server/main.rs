@@ -0,0 +1,345 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+
+use gomoku::error::GomokuError;
+use gomoku::goban::{Player, Position, Stone, GOBAN_SIZE};
+use gomoku::gomoku::{CandidateMove, GameState, Gomoku, SearchLimits, SearchReport};
+use gomoku::sessions::{bounded, with_game, Game, SessionError, Sessions};
+
+mod ws;
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:8080";
+
+const DEFAULT_CANDIDATES: usize = 3;
+
+enum ApiError {
+    Session(SessionError),
+    Engine(GomokuError),
+}
+
+impl From<GomokuError> for ApiError {
+    fn from(error: GomokuError) -> Self {
+        ApiError::Engine(error)
+    }
+}
+
+impl From<SessionError> for ApiError {
+    fn from(error: SessionError) -> Self {
+        match error {
+            SessionError::Engine(error) => ApiError::Engine(error),
+            error => ApiError::Session(error),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::Session(error @ SessionError::NotFound(_)) => (StatusCode::NOT_FOUND, error.to_string()),
+            ApiError::Session(error) => (StatusCode::SERVICE_UNAVAILABLE, error.to_string()),
+            ApiError::Engine(error) => (StatusCode::UNPROCESSABLE_ENTITY, error.to_string()),
+        };
+
+        (status, Json(ErrorBody { error: message })).into_response()
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum Color {
+    Black,
+    White,
+}
+
+impl From<Stone> for Color {
+    fn from(stone: Stone) -> Self {
+        match stone {
+            Stone::Black => Color::Black,
+            Stone::White => Color::White,
+        }
+    }
+}
+
+impl From<Color> for Player {
+    fn from(color: Color) -> Self {
+        match color {
+            Color::Black => Player::Opponent,
+            Color::White => Player::Computer,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct PlayedMove {
+    color: Color,
+    #[serde(rename = "move")]
+    position: String,
+}
+
+/// Snapshot of a game, `board` lists the rows from the top one, `X` being black
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GameView {
+    id: u64,
+    moves: Vec<PlayedMove>,
+    board: Vec<String>,
+    state: &'static str,
+    winner: Option<Color>,
+    to_move: Option<Color>,
+}
+
+impl GameView {
+    fn new(id: u64, gomoku: &Gomoku) -> Self {
+        let goban = gomoku.board();
+        let game_state = gomoku.game_state();
+
+        let board = (0..GOBAN_SIZE)
+            .map(|row| {
+                (0..GOBAN_SIZE)
+                    .map(|col| match goban.get(row, col) {
+                        Some(Stone::Black) => 'X',
+                        Some(Stone::White) => 'O',
+                        None => '.',
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let (state, winner) = match game_state {
+            GameState::InProgress => ("inProgress", None),
+            GameState::Won(Player::Opponent) => ("won", Some(Color::Black)),
+            GameState::Won(Player::Computer) => ("won", Some(Color::White)),
+            GameState::Draw => ("draw", None),
+        };
+
+        Self {
+            id,
+            moves: gomoku
+                .history()
+                .iter()
+                .map(|played| PlayedMove {
+                    color: played.stone.into(),
+                    position: played.position.to_string(),
+                })
+                .collect(),
+            board,
+            state,
+            winner,
+            to_move: match game_state {
+                GameState::InProgress => Some(goban.side_to_move().into()),
+                _ => None,
+            },
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct MoveRequest {
+    #[serde(rename = "move")]
+    position: String,
+    /// Defaults to the side to move
+    color: Option<Color>,
+}
+
+impl MoveRequest {
+    fn play(&self, gomoku: &mut Gomoku) -> Result<GameState, GomokuError> {
+        let position = Position::from_coordinates(&self.position)?;
+        let color = self.color.unwrap_or_else(|| gomoku.board().side_to_move().into());
+
+        gomoku.play(position, color.into())
+    }
+}
+
+/// Search limits, all optional, the engine's default depth applying without any
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct LimitsRequest {
+    depth: Option<usize>,
+    movetime: Option<u64>,
+    nodes: Option<usize>,
+    mate_in: Option<usize>,
+    /// Ranked moves wanted by an analysis
+    count: Option<usize>,
+}
+
+impl LimitsRequest {
+    /// The limits asked for, within the bounds of `sessions::bounded`
+    fn limits(&self, gomoku: &Gomoku) -> SearchLimits {
+        let mut limits = SearchLimits::new(self.depth.unwrap_or_else(|| gomoku.search_depth()));
+
+        if let Some(nodes) = self.nodes {
+            limits = limits.with_nodes(nodes);
+        }
+        if let Some(milliseconds) = self.movetime {
+            limits = limits.with_movetime(Duration::from_millis(milliseconds));
+        }
+        if let Some(moves) = self.mate_in {
+            limits = limits.with_mate_in(moves);
+        }
+
+        bounded(limits)
+    }
+}
+
+#[derive(Serialize)]
+struct EngineMove {
+    #[serde(rename = "move")]
+    position: String,
+    score: isize,
+    depth: usize,
+    nodes: usize,
+    game: GameView,
+}
+
+impl EngineMove {
+    /// The reported move once played in the game
+    fn new(report: &SearchReport, id: u64, gomoku: &Gomoku) -> Self {
+        Self {
+            position: report.best_move.to_string(),
+            score: report.score,
+            depth: report.depth,
+            nodes: report.visited_nodes,
+            game: GameView::new(id, gomoku),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Candidate {
+    #[serde(rename = "move")]
+    position: String,
+    score: isize,
+    pv: Vec<String>,
+}
+
+impl From<CandidateMove> for Candidate {
+    fn from(candidate: CandidateMove) -> Self {
+        Self {
+            position: candidate.position.to_string(),
+            score: candidate.score,
+            pv: candidate.pv.iter().map(Position::to_string).collect(),
+        }
+    }
+}
+
+async fn create_game(State(sessions): State<Sessions>) -> Result<(StatusCode, Json<GameView>), ApiError> {
+    let (id, game) = sessions.create()?;
+    let view = GameView::new(id, &*game.lock().await);
+
+    Ok((StatusCode::CREATED, Json(view)))
+}
+
+async fn show_game(State(sessions): State<Sessions>, Path(id): Path<u64>) -> Result<Json<GameView>, ApiError> {
+    let game = sessions.get(id)?;
+    let view = GameView::new(id, &*game.lock().await);
+
+    Ok(Json(view))
+}
+
+async fn delete_game(State(sessions): State<Sessions>, Path(id): Path<u64>) -> Result<StatusCode, ApiError> {
+    sessions.remove(id)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn play_move(
+    State(sessions): State<Sessions>,
+    Path(id): Path<u64>,
+    Json(request): Json<MoveRequest>,
+) -> Result<Json<GameView>, ApiError> {
+    let game = sessions.get(id)?;
+
+    with_game(game, move |gomoku| {
+        request.play(gomoku)?;
+
+        Ok(Json(GameView::new(id, gomoku)))
+    })
+    .await
+}
+
+/// Searches and plays the move of the side to move
+async fn engine_move(
+    State(sessions): State<Sessions>,
+    Path(id): Path<u64>,
+    request: Option<Json<LimitsRequest>>,
+) -> Result<Json<EngineMove>, ApiError> {
+    let game = sessions.get(id)?;
+    let Json(request) = request.unwrap_or_default();
+
+    with_game(game, move |gomoku| {
+        let color: Color = gomoku.board().side_to_move().into();
+        let report = gomoku.suggest_move(request.limits(gomoku))?;
+
+        gomoku.play(report.best_move, color.into())?;
+
+        Ok(Json(EngineMove::new(&report, id, gomoku)))
+    })
+    .await
+}
+
+/// Ranks the moves of the side to move without playing any
+async fn analyze(
+    State(sessions): State<Sessions>,
+    Path(id): Path<u64>,
+    request: Option<Json<LimitsRequest>>,
+) -> Result<Json<Vec<Candidate>>, ApiError> {
+    let game = sessions.get(id)?;
+    let Json(request) = request.unwrap_or_default();
+
+    with_game(game, move |gomoku| {
+        let count = request.count.unwrap_or(DEFAULT_CANDIDATES);
+        let candidates = gomoku.analyze(request.limits(gomoku), count)?;
+
+        Ok(Json(candidates.into_iter().map(Candidate::from).collect()))
+    })
+    .await
+}
+
+fn router(sessions: Sessions) -> Router {
+    Router::new()
+        .route("/games", post(create_game))
+        .route("/games/{id}", get(show_game).delete(delete_game))
+        .route("/games/{id}/moves", post(play_move))
+        .route("/games/{id}/engine-move", post(engine_move))
+        .route("/games/{id}/analysis", post(analyze))
+        .route("/games/{id}/ws", get(ws::connect))
+        .with_state(sessions)
+}
+
+/// REST server hosting concurrent games, listening on the address given as first argument
+#[tokio::main]
+async fn main() {
+    let address: SocketAddr = match std::env::args().nth(1).as_deref().unwrap_or(DEFAULT_ADDRESS).parse() {
+        Ok(address) => address,
+        Err(error) => {
+            eprintln!("Invalid address: {}", error);
+            return;
+        }
+    };
+
+    let listener = match tokio::net::TcpListener::bind(address).await {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("Could not listen on {}: {}", address, error);
+            return;
+        }
+    };
+
+    println!("Listening on http://{}", address);
+
+    if let Err(error) = axum::serve(listener, router(Sessions::default())).await {
+        eprintln!("{}", error);
+    }
+}
@@ -0,0 +1,224 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, State};
+use axum::response::Response;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{self, UnboundedSender};
+
+use gomoku::error::GomokuError;
+use gomoku::goban::Position;
+use gomoku::gomoku::{Gomoku, IterationMetrics, SearchEvent, SearchInfo};
+
+use crate::{ApiError, Candidate, Color, EngineMove, Game, GameView, LimitsRequest, MoveRequest, Sessions, DEFAULT_CANDIDATES};
+
+/// Sent by the browser, tagged by `type`
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum ClientMessage {
+    /// Plays a move, as `POST /games/{id}/moves`
+    Play(MoveRequest),
+    /// Searches and plays the engine's move, as `POST /games/{id}/engine-move`
+    Go(LimitsRequest),
+    /// Ranks moves without playing, as `POST /games/{id}/analysis`
+    Analyze(LimitsRequest),
+    /// Ends the connection's running searches early, they still answer
+    Stop,
+    /// Asks for the game again
+    Show,
+}
+
+/// Sent to the browser, tagged by `type`
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum ServerMessage {
+    Game(GameView),
+    Info(Info),
+    EngineMove(EngineMove),
+    Analysis { candidates: Vec<Candidate> },
+    Error { error: String },
+}
+
+/// An iteration of a running search
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Info {
+    depth: usize,
+    #[serde(rename = "move")]
+    position: String,
+    score: isize,
+    pv: Vec<String>,
+    nodes: usize,
+    nps: usize,
+    time: u64,
+    hashfull: usize,
+    tt_hit_rate: f64,
+    branching_factor: Option<f64>,
+    iterations: Vec<IterationMetrics>,
+}
+
+impl From<&SearchInfo> for Info {
+    fn from(info: &SearchInfo) -> Self {
+        Self {
+            depth: info.depth,
+            position: info.best_move.to_string(),
+            score: info.score,
+            pv: info.pv.iter().map(Position::to_string).collect(),
+            nodes: info.nodes,
+            nps: info.metrics.nps,
+            time: info.metrics.time_ms,
+            hashfull: info.metrics.hashfull,
+            tt_hit_rate: info.metrics.tt_hit_rate(),
+            branching_factor: info.metrics.branching_factor,
+            iterations: info.metrics.iterations.clone(),
+        }
+    }
+}
+
+impl From<GomokuError> for ServerMessage {
+    fn from(error: GomokuError) -> Self {
+        ServerMessage::Error { error: error.to_string() }
+    }
+}
+
+/// `GET /games/{id}/ws`, the game is sent on connection and after every move
+pub async fn connect(
+    upgrade: WebSocketUpgrade,
+    State(sessions): State<Sessions>,
+    Path(id): Path<u64>,
+) -> Result<Response, ApiError> {
+    let game = sessions.get(id)?;
+
+    Ok(upgrade.on_upgrade(move |socket| serve(socket, id, game)))
+}
+
+async fn serve(mut socket: WebSocket, id: u64, game: Game) {
+    let (sender, mut outgoing) = mpsc::unbounded_channel();
+    let stop = Arc::new(AtomicBool::new(false));
+
+    sender.send(ServerMessage::Game(GameView::new(id, &*game.lock().await))).ok();
+
+    loop {
+        tokio::select! {
+            Some(message) = outgoing.recv() => {
+                let text = serde_json::to_string(&message).expect("messages serialize");
+
+                if socket.send(Message::Text(text.into())).await.is_err() {
+                    break;
+                }
+            }
+            received = socket.recv() => match received {
+                Some(Ok(Message::Text(text))) => handle(&text, id, &game, &sender, &stop),
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+
+    // Searches of a closed connection have nobody to answer
+    stop.store(true, Ordering::Relaxed);
+}
+
+fn handle(text: &str, id: u64, game: &Game, sender: &UnboundedSender<ServerMessage>, stop: &Arc<AtomicBool>) {
+    let message = match serde_json::from_str(text) {
+        Ok(message) => message,
+        Err(error) => {
+            sender.send(ServerMessage::Error { error: error.to_string() }).ok();
+            return;
+        }
+    };
+
+    // Set here rather than in the search's task, so a stop sent right after a search always follows
+    // its reset
+    match message {
+        ClientMessage::Stop => {
+            stop.store(true, Ordering::Relaxed);
+            return;
+        }
+        ClientMessage::Go(_) | ClientMessage::Analyze(_) => stop.store(false, Ordering::Relaxed),
+        ClientMessage::Play(_) | ClientMessage::Show => {}
+    }
+
+    let (game, sender, stop) = (game.clone(), sender.clone(), stop.clone());
+
+    // Moves wait for the game's lock and searches run for long, neither blocks the socket
+    tokio::task::spawn_blocking(move || {
+        let answer = match message {
+            ClientMessage::Play(request) => {
+                let mut gomoku = game.blocking_lock();
+
+                match request.play(&mut gomoku) {
+                    Ok(_) => ServerMessage::Game(GameView::new(id, &gomoku)),
+                    Err(error) => error.into(),
+                }
+            }
+            ClientMessage::Go(request) => {
+                engine_move(&request, id, &game, &sender, stop).unwrap_or_else(|error| ServerMessage::Error { error })
+            }
+            ClientMessage::Analyze(request) => {
+                analyze(&request, &game, &sender, stop).unwrap_or_else(|error| ServerMessage::Error { error })
+            }
+            // Stops are handled before spawning
+            ClientMessage::Stop => return,
+            ClientMessage::Show => ServerMessage::Game(GameView::new(id, &game.blocking_lock())),
+        };
+
+        sender.send(answer).ok();
+    });
+}
+
+/// Searches a fork of the game streaming its progress, then plays the move if nobody moved meanwhile
+fn engine_move(
+    request: &LimitsRequest,
+    id: u64,
+    game: &Game,
+    sender: &UnboundedSender<ServerMessage>,
+    stop: Arc<AtomicBool>,
+) -> Result<ServerMessage, String> {
+    let mut fork = fork(game, sender, stop);
+    let color = fork.board().side_to_move();
+    let report = fork.suggest_move(request.limits(&fork)).map_err(|error| error.to_string())?;
+
+    let mut gomoku = game.blocking_lock();
+
+    if gomoku.history() != fork.history() {
+        return Err("The game changed during the search".to_string());
+    }
+
+    gomoku.play(report.best_move, Color::from(color).into()).map_err(|error| error.to_string())?;
+
+    Ok(ServerMessage::EngineMove(EngineMove::new(&report, id, &gomoku)))
+}
+
+fn analyze(
+    request: &LimitsRequest,
+    game: &Game,
+    sender: &UnboundedSender<ServerMessage>,
+    stop: Arc<AtomicBool>,
+) -> Result<ServerMessage, String> {
+    let mut fork = fork(game, sender, stop);
+    let limits = request.limits(&fork);
+    let candidates = fork
+        .analyze(limits, request.count.unwrap_or(DEFAULT_CANDIDATES))
+        .map_err(|error| error.to_string())?;
+
+    Ok(ServerMessage::Analysis {
+        candidates: candidates.into_iter().map(Candidate::from).collect(),
+    })
+}
+
+/// Copy of the game to search without holding its lock, reporting every iteration
+fn fork(game: &Game, sender: &UnboundedSender<ServerMessage>, stop: Arc<AtomicBool>) -> Gomoku {
+    let mut fork = game.blocking_lock().clone();
+    let sender = sender.clone();
+
+    fork.set_stop_flag(stop);
+    fork.set_search_observer(move |event| {
+        if let SearchEvent::Iteration(info) = event {
+            sender.send(ServerMessage::Info(info.into())).ok();
+        }
+    });
+
+    fork
+}
@@ -0,0 +1,143 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use clap::Parser;
+
+use gomoku::goban::{Position, Stone};
+use gomoku::gomoku::{CacheRetention, Gomoku, PositionSetup, SearchLimits, DEFAULT_SEARCH_DEPTH};
+use gomoku::suite::{read_suite, SuitePosition};
+use gomoku::transposition_table::DEFAULT_SIZE_MB;
+
+/// Runs the engine on every position of a tactical test suite and reports how many it solves
+///
+/// Each line of the suite holds the moves played from black, then the moves solving the position:
+/// `h8 h9 i8 j8 g8; bm f8 k8; id "open four";`, `am` listing moves that must not be played instead.
+#[derive(Parser, Debug)]
+#[clap(version, about)]
+struct Args {
+    /// suite file, one position per line
+    #[clap(value_parser)]
+    suite: PathBuf,
+
+    /// search depth of every position
+    #[clap(long, value_parser, default_value_t = DEFAULT_SEARCH_DEPTH)]
+    depth: usize,
+
+    /// nodes visited after which a search stops
+    #[clap(long, value_parser)]
+    nodes: Option<usize>,
+
+    /// milliseconds after which a search stops
+    #[clap(long, value_parser)]
+    movetime: Option<u64>,
+
+    /// transposition table size in megabytes, cleared before every position
+    #[clap(long, value_parser, default_value_t = DEFAULT_SIZE_MB)]
+    hash: usize,
+
+    /// only print the positions the engine failed
+    #[clap(short, long)]
+    quiet: bool,
+}
+
+fn load(path: &Path) -> Result<Vec<SuitePosition>, String> {
+    let text = fs::read_to_string(path).map_err(|error| error.to_string())?;
+
+    read_suite(&text).map_err(|error| error.to_string())
+}
+
+fn name(index: usize, position: &SuitePosition) -> String {
+    match &position.id {
+        Some(id) => format!("#{} {}", index + 1, id),
+        None => format!("#{}", index + 1),
+    }
+}
+
+fn expected(position: &SuitePosition) -> String {
+    let list = |moves: &[Position]| moves.iter().map(ToString::to_string).collect::<Vec<_>>().join(" ");
+
+    match (position.best_moves.is_empty(), position.avoid_moves.is_empty()) {
+        (false, true) => format!("bm {}", list(&position.best_moves)),
+        (true, false) => format!("am {}", list(&position.avoid_moves)),
+        _ => format!("bm {}, am {}", list(&position.best_moves), list(&position.avoid_moves)),
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+    let suite = match load(&args.suite) {
+        Ok(suite) => suite,
+        Err(error) => {
+            println!("Could not read {}: {}", args.suite.display(), error);
+            return;
+        }
+    };
+    let limits = SearchLimits {
+        nodes: args.nodes,
+        movetime: args.movetime.map(Duration::from_millis),
+        ..SearchLimits::new(args.depth)
+    };
+    let mut gomoku = Gomoku::with_hash_size(args.hash);
+    let (mut solved, mut total_time, mut total_nodes) = (0, Duration::ZERO, 0);
+
+    for (index, position) in suite.iter().enumerate() {
+        let side = match position.moves.len() % 2 {
+            0 => Stone::Black,
+            _ => Stone::White,
+        };
+
+        gomoku.new_game(CacheRetention::Clear);
+
+        if let Err(error) = gomoku.set_position(PositionSetup::Moves(position.moves.clone()), side) {
+            println!("{}: {}", name(index, position), error);
+            continue;
+        }
+
+        let start = Instant::now();
+        let report = gomoku.suggest_move(limits);
+        let elapsed = start.elapsed();
+
+        total_time += elapsed;
+
+        match report {
+            Ok(report) => {
+                let success = position.is_solved_by(report.best_move);
+
+                total_nodes += report.visited_nodes;
+
+                if success {
+                    solved += 1;
+                }
+
+                if !success || !args.quiet {
+                    println!(
+                        "{}: {} {}, expected {}, depth {}, {} nodes, {} ms",
+                        name(index, position),
+                        if success { "solved" } else { "failed" },
+                        report.best_move,
+                        expected(position),
+                        report.depth,
+                        report.visited_nodes,
+                        elapsed.as_millis()
+                    );
+                }
+            }
+            Err(error) => println!("{}: failed, {}", name(index, position), error),
+        }
+    }
+
+    let percent = match suite.len() {
+        0 => 0.0,
+        positions => 100.0 * solved as f64 / positions as f64,
+    };
+
+    println!(
+        "Solved {}/{} ({:.1}%) in {} ms, {} nodes",
+        solved,
+        suite.len(),
+        percent,
+        total_time.as_millis(),
+        total_nodes
+    );
+}
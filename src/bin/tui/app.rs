@@ -0,0 +1,140 @@
+use std::time::{Duration, Instant};
+
+use gomoku::evaluator::Eval;
+use gomoku::goban::{Position, Stone, GOBAN_SIZE};
+use gomoku::gomoku::{GameState, Gomoku, SearchInfo, SearchLimits};
+
+use crate::engine::{EngineMessage, EngineSearch};
+
+/// Game between the human and the engine, as shown by the interface
+pub struct App {
+    pub gomoku: Gomoku,
+    pub human: Stone,
+    pub limits: SearchLimits,
+    /// Intersection the next stone of the human goes to
+    pub cursor: Position,
+    /// Last iteration of the engine's latest search
+    pub info: Option<SearchInfo>,
+    /// Static evaluation from the engine's point of view, after the last move
+    pub eval: Eval,
+    /// Line at the bottom, the outcome of the game or why a move was refused
+    pub status: String,
+    /// Thinking time of black and white, without the running turn
+    clocks: [Duration; 2],
+    turn_start: Instant,
+    search: Option<EngineSearch>,
+    pub quit: bool,
+}
+
+impl App {
+    pub fn new(gomoku: Gomoku, human: Stone, limits: SearchLimits) -> Self {
+        let center = GOBAN_SIZE / 2;
+
+        Self {
+            gomoku,
+            human,
+            limits,
+            cursor: Position::new(center, center),
+            info: None,
+            eval: Eval::Score(0),
+            status: String::new(),
+            clocks: [Duration::ZERO; 2],
+            turn_start: Instant::now(),
+            search: None,
+            quit: false,
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.gomoku.game_state() != GameState::InProgress
+    }
+
+    pub fn is_thinking(&self) -> bool {
+        self.search.is_some()
+    }
+
+    /// Thinking time of `stone`, the running turn included
+    pub fn clock(&self, stone: Stone) -> Duration {
+        let spent = self.clocks[stone as usize];
+
+        match !self.is_over() && self.gomoku.board().side_to_move() == stone {
+            true => spent + self.turn_start.elapsed(),
+            false => spent,
+        }
+    }
+
+    pub fn move_cursor(&mut self, rows: isize, cols: isize) {
+        let clamp = |value: usize, step: isize| (value as isize + step).clamp(0, GOBAN_SIZE as isize - 1) as usize;
+
+        self.cursor = Position::new(clamp(self.cursor.row, rows), clamp(self.cursor.col, cols));
+    }
+
+    /// Plays `position` for whoever is to move, stopping the clock of the turn
+    fn play(&mut self, position: Position) {
+        let stone = self.gomoku.board().side_to_move();
+
+        match self.gomoku.play(position, stone.into()) {
+            Ok(state) => {
+                self.clocks[stone as usize] += self.turn_start.elapsed();
+                self.turn_start = Instant::now();
+                self.eval = match self.gomoku.evaluate_position() {
+                    eval if self.gomoku.board().side_to_move() == self.human => eval.opposite(),
+                    eval => eval,
+                };
+                self.status = match state {
+                    GameState::InProgress => String::new(),
+                    GameState::Won(player) if Stone::from(player) == self.human => "You won !".to_string(),
+                    GameState::Won(_) => "The engine won !".to_string(),
+                    GameState::Draw => "Draw !".to_string(),
+                };
+            }
+            Err(error) => self.status = error.to_string(),
+        }
+    }
+
+    /// Places the human's stone under the cursor
+    pub fn place(&mut self) {
+        if self.is_over() || self.gomoku.board().side_to_move() != self.human {
+            return;
+        }
+
+        self.play(self.cursor);
+    }
+
+    pub fn new_game(&mut self) {
+        self.search = None;
+        self.gomoku.reset();
+        self.info = None;
+        self.eval = Eval::Score(0);
+        self.status = String::new();
+        self.clocks = [Duration::ZERO; 2];
+        self.turn_start = Instant::now();
+    }
+
+    /// Starts the engine on its turn and plays its move once found, called between frames
+    pub fn tick(&mut self) {
+        if self.is_over() || self.gomoku.board().side_to_move() == self.human {
+            return;
+        }
+
+        if self.search.is_none() {
+            self.search = Some(EngineSearch::start(&self.gomoku, self.limits));
+        }
+
+        while let Some(message) = self.search.as_ref().and_then(EngineSearch::poll) {
+            match message {
+                EngineMessage::Info(info) => self.info = Some(info),
+                EngineMessage::Done(result) => {
+                    self.search = None;
+
+                    match result {
+                        Ok(report) => self.play(report.best_move),
+                        Err(error) => self.status = error.to_string(),
+                    }
+
+                    return;
+                }
+            }
+        }
+    }
+}
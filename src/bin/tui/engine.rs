@@ -0,0 +1,58 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+
+use gomoku::error::GomokuError;
+use gomoku::gomoku::{Gomoku, SearchEvent, SearchInfo, SearchLimits, SearchReport};
+
+/// What the search thread tells the interface
+pub enum EngineMessage {
+    /// An iteration completed
+    Info(SearchInfo),
+    Done(Result<SearchReport, GomokuError>),
+}
+
+/// Search running on a fork of the engine, the interface polling its progress between frames
+pub struct EngineSearch {
+    receiver: Receiver<EngineMessage>,
+    stop: Arc<AtomicBool>,
+}
+
+impl EngineSearch {
+    /// Searches the best move of the side to move of `gomoku` on its own thread
+    pub fn start(gomoku: &Gomoku, limits: SearchLimits) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut fork = gomoku.clone();
+        let observer = sender.clone();
+
+        fork.set_search_observer(move |event| {
+            if let SearchEvent::Iteration(info) = event {
+                observer.send(EngineMessage::Info(info.clone())).ok();
+            }
+        });
+        fork.set_stop_flag(stop.clone());
+
+        thread::spawn(move || {
+            sender.send(EngineMessage::Done(fork.suggest_move(limits))).ok();
+        });
+
+        Self { receiver, stop }
+    }
+
+    /// Next message of the search, `None` when it has nothing new
+    pub fn poll(&self) -> Option<EngineMessage> {
+        match self.receiver.try_recv() {
+            Ok(message) => Some(message),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(EngineMessage::Done(Err(GomokuError::NoMoveAvailable))),
+        }
+    }
+}
+
+impl Drop for EngineSearch {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,126 @@
+use std::io;
+use std::time::Duration;
+
+use clap::{Parser, ValueEnum};
+use crossterm::event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, MouseButton, MouseEventKind};
+use crossterm::execute;
+use ratatui::layout::Rect;
+use ratatui::DefaultTerminal;
+
+use gomoku::goban::Stone;
+use gomoku::gomoku::{GomokuBuilder, SearchLimits, DEFAULT_SEARCH_DEPTH};
+use gomoku::transposition_table::DEFAULT_SIZE_MB;
+
+mod app;
+mod engine;
+mod ui;
+
+use app::App;
+
+// With a movetime the search deepens until it runs out of time, this only bounds it on trivial positions
+const MAX_DEPTH: usize = 20;
+
+// Redraws while nothing happens, keeping the clock and the engine's progress current
+const FRAME: Duration = Duration::from_millis(100);
+
+#[derive(Parser, Debug)]
+#[clap(name = "Gomoku TUI")]
+#[clap(author = "angauber")]
+struct Args {
+    /// minmax search tree depth
+    #[clap(short, long, value_parser, default_value_t = DEFAULT_SEARCH_DEPTH)]
+    search_depth: usize,
+
+    /// transposition table size in megabytes
+    #[clap(long, value_parser, default_value_t = DEFAULT_SIZE_MB)]
+    hash_size: usize,
+
+    /// thinking time per move in milliseconds, the search deepening until it runs out instead of stopping at the search depth
+    #[clap(long, value_parser)]
+    movetime: Option<u64>,
+
+    /// color of the human, black playing first
+    #[clap(long, value_enum, default_value_t = Color::Black)]
+    play_as: Color,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Color {
+    Black,
+    White,
+}
+
+fn run(terminal: &mut DefaultTerminal, app: &mut App) -> io::Result<()> {
+    while !app.quit {
+        app.tick();
+        terminal.draw(|frame| ui::draw(frame, app))?;
+
+        if !event::poll(FRAME)? {
+            continue;
+        }
+
+        match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                KeyCode::Up | KeyCode::Char('k') => app.move_cursor(-1, 0),
+                KeyCode::Down | KeyCode::Char('j') => app.move_cursor(1, 0),
+                KeyCode::Left | KeyCode::Char('h') => app.move_cursor(0, -1),
+                KeyCode::Right | KeyCode::Char('l') => app.move_cursor(0, 1),
+                KeyCode::Enter | KeyCode::Char(' ') => app.place(),
+                KeyCode::Char('n') => app.new_game(),
+                KeyCode::Char('q') | KeyCode::Esc => app.quit = true,
+                _ => {}
+            },
+            Event::Mouse(mouse) => {
+                let size = terminal.size()?;
+
+                if let Some(position) = ui::intersection_at(Rect::new(0, 0, size.width, size.height), mouse.column, mouse.row) {
+                    app.cursor = position;
+
+                    if mouse.kind == MouseEventKind::Down(MouseButton::Left) {
+                        app.place();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn main() -> io::Result<()> {
+    let args = Args::parse();
+
+    let gomoku = match GomokuBuilder::new()
+        .hash_size_mb(args.hash_size)
+        .search_depth(args.search_depth)
+        .build()
+    {
+        Ok(gomoku) => gomoku,
+        Err(error) => {
+            println!("{}", error);
+            return Ok(());
+        }
+    };
+
+    let limits = match args.movetime {
+        Some(milliseconds) => SearchLimits::new(MAX_DEPTH).with_movetime(Duration::from_millis(milliseconds)),
+        None => SearchLimits::new(args.search_depth),
+    };
+    let human = match args.play_as {
+        Color::Black => Stone::Black,
+        Color::White => Stone::White,
+    };
+    let mut app = App::new(gomoku, human, limits);
+
+    // Restores the terminal on panics too
+    let mut terminal = ratatui::init();
+
+    execute!(io::stdout(), EnableMouseCapture)?;
+
+    let result = run(&mut terminal, &mut app);
+
+    execute!(io::stdout(), DisableMouseCapture)?;
+    ratatui::restore();
+    result
+}
@@ -0,0 +1,195 @@
+use std::time::Duration;
+
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Gauge, Paragraph, Wrap};
+use ratatui::Frame;
+
+use gomoku::evaluator::Eval;
+use gomoku::goban::{CoordinateStyle, Position, Stone, GOBAN_SIZE};
+
+use crate::app::App;
+
+// Rows and columns of the star points
+const HOSHI: [usize; 3] = [3, GOBAN_SIZE / 2, GOBAN_SIZE - 4];
+
+const LAST: usize = GOBAN_SIZE - 1;
+
+/// Columns taken by the board: row numbers, intersections and the lines between them, and the borders
+const BOARD_WIDTH: u16 = 3 + 2 * GOBAN_SIZE as u16 - 1 + 2;
+
+fn intersection(row: usize, col: usize) -> &'static str {
+    match (row, col) {
+        (0, 0) => "┌",
+        (0, LAST) => "┐",
+        (0, _) => "┬",
+        (LAST, 0) => "└",
+        (LAST, LAST) => "┘",
+        (LAST, _) => "┴",
+        (_, 0) => "├",
+        (_, LAST) => "┤",
+        (row, col) if HOSHI.contains(&row) && HOSHI.contains(&col) => "╋",
+        _ => "┼",
+    }
+}
+
+fn clock(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Wooden board with the stones, the cursor and the last move highlighted
+fn board(app: &App) -> Vec<Line<'static>> {
+    let goban = app.gomoku.board();
+    let wood = Style::default().fg(Color::Black).bg(Color::Yellow);
+    let winning_line = goban.winning_line().unwrap_or_default();
+    let last_move = app.gomoku.last_move().map(|played| played.position);
+    let mut lines = Vec::new();
+
+    for row in 0..GOBAN_SIZE {
+        let mut spans = vec![Span::raw(format!("{:>2} ", GOBAN_SIZE - row))];
+
+        for col in 0..GOBAN_SIZE {
+            let position = Position::new(row, col);
+            let (glyph, style) = match goban.get(row, col) {
+                Some(Stone::Black) => ("●", wood),
+                Some(Stone::White) => ("●", wood.fg(Color::White)),
+                None => (intersection(row, col), wood),
+            };
+            let style = match position {
+                _ if position == app.cursor => style.bg(Color::Cyan),
+                _ if winning_line.contains(&position) => style.bg(Color::Green),
+                _ if Some(position) == last_move => style.bg(Color::Red),
+                _ => style,
+            };
+
+            spans.push(Span::styled(glyph, style));
+
+            if col != LAST {
+                spans.push(Span::styled("─", wood));
+            }
+        }
+
+        lines.push(Line::from(spans));
+    }
+
+    let labels: Vec<String> = (0..GOBAN_SIZE).map(|col| CoordinateStyle::Letters.column_label(col)).collect();
+
+    lines.push(Line::from(format!("   {}", labels.join(" "))));
+    lines
+}
+
+/// Moves two by two, as many of the latest ones as `height` holds
+fn moves(app: &App, height: usize) -> Vec<Line<'static>> {
+    let lines: Vec<Line> = app
+        .gomoku
+        .history()
+        .chunks(2)
+        .enumerate()
+        .map(|(turn, moves)| {
+            let moves: Vec<String> = moves.iter().map(|played| played.position.to_string()).collect();
+
+            Line::from(format!("{:>3}. {}", turn + 1, moves.join(" ")))
+        })
+        .collect();
+
+    lines[lines.len().saturating_sub(height)..].to_vec()
+}
+
+/// Board, side and status areas of the screen
+fn split(area: Rect) -> [Rect; 3] {
+    let [main, status] = Layout::vertical([Constraint::Min(0), Constraint::Length(2)]).areas(area);
+    let [board, side] = Layout::horizontal([Constraint::Length(BOARD_WIDTH), Constraint::Min(24)]).areas(main);
+
+    [board, side, status]
+}
+
+/// Intersection drawn at `column` and `row` of a screen of size `area`, the line on its right belonging to it
+pub fn intersection_at(area: Rect, column: u16, row: u16) -> Option<Position> {
+    let [board, _, _] = split(area);
+    // Inside the border, after the row numbers
+    let col = column.checked_sub(board.x + 1 + 3)? as usize / 2;
+    let row = row.checked_sub(board.y + 1)? as usize;
+
+    match row < GOBAN_SIZE && col < GOBAN_SIZE {
+        true => Some(Position::new(row, col)),
+        false => None,
+    }
+}
+
+pub fn draw(frame: &mut Frame, app: &App) {
+    let [board_area, side, status] = split(frame.area());
+    let [clock_area, eval_area, pv_area, moves_area] = Layout::vertical([
+        Constraint::Length(4),
+        Constraint::Length(3),
+        Constraint::Length(5),
+        Constraint::Min(3),
+    ])
+    .areas(side);
+
+    frame.render_widget(Paragraph::new(board(app)).block(Block::default().borders(Borders::ALL).title(" Gomoku ")), board_area);
+
+    let to_move = |stone: Stone| match !app.is_over() && app.gomoku.board().side_to_move() == stone {
+        true => Style::default().add_modifier(Modifier::BOLD),
+        false => Style::default(),
+    };
+    let clocks = vec![
+        Line::styled(format!("Black {}", clock(app.clock(Stone::Black))), to_move(Stone::Black)),
+        Line::styled(format!("White {}", clock(app.clock(Stone::White))), to_move(Stone::White)),
+    ];
+
+    frame.render_widget(Paragraph::new(clocks).block(Block::default().borders(Borders::ALL).title(" Clock ")), clock_area);
+
+    // Filled with the engine's color as the evaluation favors it
+    let (engine, human) = match app.human {
+        Stone::Black => (Color::White, Color::Black),
+        Stone::White => (Color::Black, Color::White),
+    };
+    let score = match app.eval {
+        Eval::Won => "win".to_string(),
+        Eval::Lost => "loss".to_string(),
+        Eval::Score(score) => format!("{:+}", score),
+    };
+
+    frame.render_widget(
+        Gauge::default()
+            .block(Block::default().borders(Borders::ALL).title(" Engine eval "))
+            .gauge_style(Style::default().fg(engine).bg(human))
+            .ratio((app.eval.normalized() + 1.0) / 2.0)
+            .label(Span::styled(score, Style::default().fg(Color::Red))),
+        eval_area,
+    );
+
+    let pv = match &app.info {
+        Some(info) => {
+            let pv: Vec<String> = info.pv.iter().map(Position::to_string).collect();
+
+            vec![
+                Line::from(format!("depth {} score {:+} nodes {}", info.depth, info.score, info.nodes)),
+                Line::from(pv.join(" ")),
+            ]
+        }
+        None => Vec::new(),
+    };
+
+    frame.render_widget(
+        Paragraph::new(pv)
+            .wrap(Wrap { trim: true })
+            .block(Block::default().borders(Borders::ALL).title(" Principal variation ")),
+        pv_area,
+    );
+
+    let height = moves_area.height.saturating_sub(2) as usize;
+
+    frame.render_widget(Paragraph::new(moves(app, height)).block(Block::default().borders(Borders::ALL).title(" Moves ")), moves_area);
+
+    let status_line = match app.is_thinking() {
+        true => "Thinking...",
+        false => app.status.as_str(),
+    };
+    let help = "arrows or the mouse move the cursor, enter or a click plays, n starts a new game, q quits";
+
+    frame.render_widget(Paragraph::new(vec![Line::from(status_line.to_string()), Line::from(help)]), status);
+}
@@ -0,0 +1,314 @@
+use std::io::{self, BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use gomoku::book::OpeningBook;
+use gomoku::engine::about;
+use gomoku::goban::{Position, Stone};
+use gomoku::gomoku::{Gomoku, GomokuBuilder, PositionSetup, SearchEvent, SearchInfo, SearchLimits, SearchMetrics};
+use gomoku::transposition_table::DEFAULT_SIZE_MB;
+
+// The search deepens until it is stopped or runs out of time, this only bounds it on trivial positions
+const MAX_DEPTH: usize = 20;
+
+// Percentage of randomness among book moves, from always the best weighted to proportional to the weights
+const DEFAULT_BOOK_RANDOMNESS: u32 = 50;
+
+// Share of the remaining clock spent on one move when the manager gives no movetime
+const MOVES_TO_GO: u32 = 30;
+
+fn send(line: &str) {
+    let mut stdout = io::stdout().lock();
+
+    writeln!(stdout, "{}", line).ok();
+    stdout.flush().ok();
+}
+
+fn info_line(info: &SearchInfo) -> String {
+    // A proven result whose PV does not reach the five has no known distance, it keeps its score
+    let score = match info.mate {
+        Some(moves) if info.score == isize::MAX || info.score == isize::MIN => format!("mate {}", moves),
+        _ => format!("cp {}", info.score),
+    };
+    let pv: Vec<String> = info.pv.iter().map(Position::to_string).collect();
+
+    format!(
+        "info depth {} score {} nodes {} nps {} time {} hashfull {} pv {}",
+        info.depth,
+        score,
+        info.nodes,
+        info.metrics.nps,
+        info.metrics.time_ms,
+        info.metrics.hashfull,
+        pv.join(" ")
+    )
+}
+
+/// Metrics UCI has no field for, sent as a string after each `info` line
+fn metrics_line(metrics: &SearchMetrics) -> String {
+    let mut line = format!("info string tthits {:.1}%", 100.0 * metrics.tt_hit_rate());
+
+    if let Some(branching_factor) = metrics.branching_factor {
+        line += &format!(" ebf {:.2}", branching_factor);
+    }
+
+    if let Some(iteration) = metrics.iterations.last() {
+        line += &format!(" iterationnodes {} iterationtime {}", iteration.nodes, iteration.time_ms);
+    }
+
+    line
+}
+
+struct Search {
+    stop: Arc<AtomicBool>,
+    worker: JoinHandle<()>,
+}
+
+/// Engine side of a UCI-like protocol, moves use the engine's coordinates such as `j10`
+///
+/// Searches run on a fork of the engine so `stop` and `isready` are answered while thinking.
+struct Uci {
+    gomoku: Gomoku,
+    side_to_move: Stone,
+    search: Option<Search>,
+    /// Kept to give it to the engine again when the hash size changes
+    book: Option<Arc<OpeningBook>>,
+    book_randomness: u32,
+}
+
+impl Uci {
+    fn new() -> Self {
+        Self {
+            gomoku: Self::engine(GomokuBuilder::new()),
+            side_to_move: Stone::Black,
+            search: None,
+            book: None,
+            book_randomness: DEFAULT_BOOK_RANDOMNESS,
+        }
+    }
+
+    fn engine(builder: GomokuBuilder) -> Gomoku {
+        let mut gomoku = builder.build().expect("the default search depth is valid");
+
+        gomoku.set_search_observer(|event| {
+            if let SearchEvent::Iteration(info) = event {
+                send(&info_line(info));
+                send(&metrics_line(&info.metrics));
+            }
+        });
+
+        gomoku
+    }
+
+    /// Runs one command, `false` once the session is over
+    fn handle(&mut self, line: &str) -> bool {
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or_default();
+        let arguments: Vec<&str> = words.collect();
+
+        match command {
+            "uci" => {
+                let about = about();
+
+                send(&format!("id name {} {}", about.name, about.version));
+                send(&format!("id author {}", about.author));
+                send(&format!("option name Hash type spin default {} min 1 max 4096", DEFAULT_SIZE_MB));
+                send("option name Book type string default <empty>");
+                send(&format!("option name BookRandomness type spin default {} min 0 max 100", DEFAULT_BOOK_RANDOMNESS));
+                send("uciok");
+            }
+            "isready" => send("readyok"),
+            "setoption" => self.set_option(&arguments),
+            "ucinewgame" => {
+                self.wait();
+                self.gomoku.reset();
+                self.side_to_move = Stone::Black;
+            }
+            "position" => self.position(&arguments),
+            "go" => self.go(&arguments),
+            "stop" => {
+                if let Some(search) = &self.search {
+                    search.stop.store(true, Ordering::Relaxed);
+                }
+            }
+            "quit" => {
+                self.wait();
+                return false;
+            }
+            "" => {}
+            command => send(&format!("info string unknown command {}", command)),
+        }
+
+        true
+    }
+
+    /// Gives the book to the engine, or takes it back once there is none
+    fn apply_book(&mut self) {
+        match &self.book {
+            Some(book) => self.gomoku.set_book(Arc::clone(book), self.book_randomness as f64 / 100.0),
+            None => self.gomoku.clear_book(),
+        }
+    }
+
+    /// `Hash` in megabytes, `Book` as the path of a book built by gomoku-book, `<empty>` for none,
+    /// and `BookRandomness` as a percentage
+    fn set_option(&mut self, arguments: &[&str]) {
+        match arguments {
+            ["name", name, "value", size] if name.eq_ignore_ascii_case("hash") => match size.parse::<usize>() {
+                Ok(size_mb) => {
+                    self.wait();
+                    self.gomoku = Self::engine(GomokuBuilder::new().hash_size_mb(size_mb.max(1)));
+                    self.apply_book();
+                }
+                Err(_) => send(&format!("info string invalid hash size {}", size)),
+            },
+            ["name", name, "value", path @ ..] if name.eq_ignore_ascii_case("book") => {
+                let path = path.join(" ");
+
+                self.wait();
+                self.book = match path.as_str() {
+                    "" | "<empty>" => None,
+                    path => match OpeningBook::load(path) {
+                        Ok(book) => Some(Arc::new(book)),
+                        Err(error) => {
+                            send(&format!("info string could not load {}: {}", path, error));
+                            None
+                        }
+                    },
+                };
+                self.apply_book();
+            }
+            ["name", name, "value", percent] if name.eq_ignore_ascii_case("bookrandomness") => match percent.parse::<u32>() {
+                Ok(percent) => {
+                    self.wait();
+                    self.book_randomness = percent.min(100);
+                    self.apply_book();
+                }
+                Err(_) => send(&format!("info string invalid book randomness {}", percent)),
+            },
+            _ => send(&format!("info string unsupported option {}", arguments.join(" "))),
+        }
+    }
+
+    /// `position startpos [moves <move>...]`, Black playing the first move
+    fn position(&mut self, arguments: &[&str]) {
+        let moves = match arguments {
+            ["startpos"] => String::new(),
+            ["startpos", "moves", ..] => arguments[2..].join(" "),
+            _ => return send("info string expected position startpos [moves ...]"),
+        };
+
+        let setup = match moves.parse::<PositionSetup>() {
+            Ok(setup) => setup,
+            Err(error) => return send(&format!("info string {}", error)),
+        };
+
+        self.side_to_move = match arguments.len().saturating_sub(2) % 2 {
+            0 => Stone::Black,
+            _ => Stone::White,
+        };
+
+        self.wait();
+
+        if let Err(error) = self.gomoku.set_position(setup, self.side_to_move) {
+            send(&format!("info string {}", error));
+        }
+    }
+
+    /// Supports `depth`, `nodes`, `movetime`, `mate`, `infinite` and the `wtime`/`btime` clocks
+    fn go(&mut self, arguments: &[&str]) {
+        self.wait();
+
+        let limits = match self.limits(arguments) {
+            Ok(limits) => limits,
+            Err(error) => return send(&format!("info string {}", error)),
+        };
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut fork = self.gomoku.clone();
+
+        fork.set_stop_flag(stop.clone());
+
+        let worker = thread::spawn(move || match fork.suggest_move(limits) {
+            Ok(report) => send(&format!("bestmove {}", report.best_move)),
+            Err(error) => {
+                send(&format!("info string {}", error));
+                send("bestmove 0000");
+            }
+        });
+
+        self.search = Some(Search { stop, worker });
+    }
+
+    fn limits(&self, arguments: &[&str]) -> Result<SearchLimits, String> {
+        let mut depth = None;
+        let mut limits = SearchLimits::new(MAX_DEPTH);
+        let mut bounded = false;
+        let mut clock = None;
+        let mut increment = Duration::ZERO;
+
+        let (own_time, own_increment) = match self.side_to_move {
+            Stone::Black => ("btime", "binc"),
+            Stone::White => ("wtime", "winc"),
+        };
+
+        for pair in arguments.windows(2) {
+            let value = pair[1].parse::<u64>().ok();
+
+            match (pair[0], value) {
+                ("depth", Some(plies)) => depth = Some(plies as usize),
+                ("nodes", Some(nodes)) => limits = limits.with_nodes(nodes as usize),
+                ("movetime", Some(milliseconds)) => limits = limits.with_movetime(Duration::from_millis(milliseconds)),
+                ("mate", Some(0)) => return Err("mate 0 has no move to search, expected mate 1 or more".to_string()),
+                ("mate", Some(moves)) => limits = limits.with_mate_in(moves as usize),
+                (key, Some(milliseconds)) if key == own_time => clock = Some(Duration::from_millis(milliseconds)),
+                (key, Some(milliseconds)) if key == own_increment => increment = Duration::from_millis(milliseconds),
+                _ => continue,
+            }
+
+            bounded = true;
+        }
+
+        if arguments.contains(&"infinite") {
+            bounded = true;
+        }
+
+        if let (Some(clock), false) = (clock, arguments.contains(&"movetime")) {
+            limits = limits.with_movetime(clock / MOVES_TO_GO + increment);
+        }
+
+        // The search only stops on even depths, odd ones are rounded up
+        limits.depth = match (depth, bounded) {
+            (Some(plies), _) => (plies + plies % 2).max(2),
+            (None, true) => MAX_DEPTH,
+            (None, false) => self.gomoku.search_depth(),
+        };
+
+        Ok(limits)
+    }
+
+    /// Stops the running search, if any, once it answered
+    fn wait(&mut self) {
+        if let Some(search) = self.search.take() {
+            search.stop.store(true, Ordering::Relaxed);
+            search.worker.join().ok();
+        }
+    }
+}
+
+fn main() {
+    let mut uci = Uci::new();
+    let stdin = io::stdin();
+
+    for line in stdin.lock().lines().map_while(Result::ok) {
+        if !uci.handle(&line) {
+            break;
+        }
+    }
+
+    // Input ended without quit, a piped script still gets its last answer
+    if let Some(search) = uci.search.take() {
+        search.worker.join().ok();
+    }
+}
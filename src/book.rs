@@ -0,0 +1,399 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+
+use crate::goban::{Goban, Move, Position, Stone, GOBAN_SIZE};
+use crate::zobrist_hashing::{hasher, ZobristHash};
+
+const FILE_MAGIC: &[u8; 8] = b"GMKBOOK\x01";
+
+/// Distance from the centre within which `random_opening` draws its stones
+pub const OPENING_RADIUS: usize = 3;
+
+/// A move out of a book position, with the results of the games which played it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookMove {
+    pub position: Position,
+    pub games: u32,
+    /// Games won by the side playing the move
+    pub wins: u32,
+    pub draws: u32,
+}
+
+impl BookMove {
+    /// Share of the points the side playing the move won, a draw counting half
+    pub fn win_rate(&self) -> f64 {
+        match self.games {
+            0 => 0.5,
+            games => (self.wins as f64 + self.draws as f64 / 2.0) / games as f64,
+        }
+    }
+}
+
+/// Moves of opening positions aggregated over many games
+///
+/// Positions are keyed by their canonical hash and their moves kept in the canonical orientation,
+/// so the eight symmetric versions of an opening share their statistics.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OpeningBook {
+    entries: HashMap<ZobristHash, Vec<BookMove>>,
+}
+
+impl OpeningBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Positions in the book
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Adds the first `max_ply` moves of a game black started, `winner` being `None` for a draw
+    pub fn add_game(&mut self, moves: &[Position], winner: Option<Stone>, max_ply: usize) {
+        let mut goban = Goban::new();
+
+        for &position in moves.iter().take(max_ply) {
+            let stone = goban.side_to_move();
+            let (hash, symmetry) = goban.get_canonical_hash();
+            let canonical = symmetry.apply(&position);
+            let moves = self.entries.entry(hash).or_default();
+            let index = match moves.iter().position(|played| played.position == canonical) {
+                Some(index) => index,
+                None => {
+                    moves.push(BookMove {
+                        position: canonical,
+                        games: 0,
+                        wins: 0,
+                        draws: 0,
+                    });
+                    moves.len() - 1
+                }
+            };
+            let played = &mut moves[index];
+
+            played.games += 1;
+
+            match winner {
+                Some(winner) if winner == stone => played.wins += 1,
+                Some(_) => {}
+                None => played.draws += 1,
+            }
+
+            goban.apply_move(Move::new(stone, position));
+        }
+    }
+
+    /// Counts a game along the moves it played while in the book, rewarding the moves of the winner
+    /// and penalizing those of the loser, without adding positions
+    pub fn learn(&mut self, moves: &[Position], winner: Option<Stone>) {
+        let mut goban = Goban::new();
+
+        for &position in moves {
+            let stone = goban.side_to_move();
+            let (hash, symmetry) = goban.get_canonical_hash();
+            let canonical = symmetry.apply(&position);
+            let played = match self.entries.get_mut(&hash).and_then(|moves| moves.iter_mut().find(|played| played.position == canonical)) {
+                Some(played) => played,
+                None => return,
+            };
+
+            played.games += 1;
+
+            match winner {
+                Some(winner) if winner == stone => played.wins += 1,
+                Some(_) => {}
+                None => played.draws += 1,
+            }
+
+            goban.apply_move(Move::new(stone, position));
+        }
+    }
+
+    /// Drops the moves played in fewer than `min_games` games, and the positions left without moves
+    pub fn prune(&mut self, min_games: u32) {
+        for moves in self.entries.values_mut() {
+            moves.retain(|played| played.games >= min_games);
+        }
+
+        self.entries.retain(|_, moves| !moves.is_empty());
+    }
+
+    /// Moves of the position of `goban`, in its orientation, the most played first
+    pub fn moves(&self, goban: &Goban) -> Vec<BookMove> {
+        let (hash, symmetry) = goban.get_canonical_hash();
+        let mut moves: Vec<BookMove> = self
+            .entries
+            .get(&hash)
+            .map(|moves| {
+                moves
+                    .iter()
+                    .map(|played| BookMove {
+                        position: symmetry.inverse().apply(&played.position),
+                        ..*played
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        moves.sort_by(|a, b| b.games.cmp(&a.games).then(b.wins.cmp(&a.wins)));
+        moves
+    }
+
+    /// Picks a move of the position of `goban` by how often it was played and how well it scored
+    ///
+    /// A move weighs its games times the square of its win rate, so losses outweigh the games they add.
+    ///
+    /// `randomness` goes from 0, always the move of the highest weight, to 1, every move as often as its
+    /// weight. Moves which never scored are left out, `None` meaning the position is out of the book.
+    pub fn choose<R: Rng>(&self, goban: &Goban, randomness: f64, rng: &mut R) -> Option<BookMove> {
+        let moves: Vec<(BookMove, f64)> = self
+            .moves(goban)
+            .into_iter()
+            .filter(|played| goban.get(played.position.row, played.position.col).is_none())
+            .map(|played| (played, played.games as f64 * played.win_rate().powi(2)))
+            .filter(|(_, weight)| *weight > 0.0)
+            .collect();
+
+        if randomness <= 0.0 {
+            return moves
+                .into_iter()
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(played, _)| played);
+        }
+
+        let weights = moves.iter().map(|(_, weight)| weight.powf(1.0 / randomness.min(1.0)));
+        let index = WeightedIndex::new(weights).ok()?.sample(rng);
+
+        Some(moves[index].0)
+    }
+
+    /// Writes every position and its moves, tagged with the fingerprint of the zobrist keys
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        writer.write_all(FILE_MAGIC)?;
+        writer.write_all(&hasher().fingerprint().to_le_bytes())?;
+        writer.write_all(&(self.entries.len() as u64).to_le_bytes())?;
+
+        for (hash, moves) in &self.entries {
+            writer.write_all(&hash.to_le_bytes())?;
+            writer.write_all(&(moves.len() as u32).to_le_bytes())?;
+
+            for played in moves {
+                writer.write_all(&(played.position.index() as u32).to_le_bytes())?;
+                writer.write_all(&played.games.to_le_bytes())?;
+                writer.write_all(&played.wins.to_le_bytes())?;
+                writer.write_all(&played.draws.to_le_bytes())?;
+            }
+        }
+
+        writer.flush()
+    }
+
+    /// Reads a book written by `save` with the same zobrist keys
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut magic = [0; 8];
+        let invalid = |message| io::Error::new(io::ErrorKind::InvalidData, message);
+
+        reader.read_exact(&mut magic)?;
+
+        if &magic != FILE_MAGIC {
+            return Err(invalid("not an opening book file"));
+        }
+
+        if u64::from_le_bytes(read_bytes(&mut reader)?) != hasher().fingerprint() {
+            return Err(invalid("opening book was built with different zobrist keys"));
+        }
+
+        let mut book = OpeningBook::new();
+
+        for _ in 0..u64::from_le_bytes(read_bytes(&mut reader)?) {
+            let hash = ZobristHash::from_le_bytes(read_bytes(&mut reader)?);
+            let count = u32::from_le_bytes(read_bytes(&mut reader)?);
+            let mut moves = Vec::new();
+
+            for _ in 0..count {
+                let index = u32::from_le_bytes(read_bytes(&mut reader)?) as usize;
+
+                moves.push(BookMove {
+                    position: Position::from_index(index).ok_or_else(|| invalid("invalid book move"))?,
+                    games: u32::from_le_bytes(read_bytes(&mut reader)?),
+                    wins: u32::from_le_bytes(read_bytes(&mut reader)?),
+                    draws: u32::from_le_bytes(read_bytes(&mut reader)?),
+                });
+            }
+
+            book.entries.insert(hash, moves);
+        }
+
+        Ok(book)
+    }
+}
+
+/// `stones` distinct stones drawn near the centre, black's first, so games played from them differ
+pub fn random_opening<R: Rng>(rng: &mut R, stones: usize) -> Vec<Position> {
+    let center = GOBAN_SIZE / 2;
+    let mut opening = Vec::new();
+
+    while opening.len() < stones {
+        let row = rng.gen_range(center - OPENING_RADIUS..=center + OPENING_RADIUS);
+        let col = rng.gen_range(center - OPENING_RADIUS..=center + OPENING_RADIUS);
+        let position = Position::new(row, col);
+
+        if !opening.contains(&position) {
+            opening.push(position);
+        }
+    }
+
+    opening
+}
+
+fn read_bytes<const N: usize, R: Read>(reader: &mut R) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod book_tests {
+    use std::env;
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::book::{random_opening, OpeningBook, OPENING_RADIUS};
+    use crate::goban::{Goban, Move, Position, Stone, GOBAN_SIZE};
+
+    #[test]
+    fn it_draws_distinct_stones_near_the_centre() {
+        let opening = random_opening(&mut StdRng::seed_from_u64(3), 5);
+        let center = GOBAN_SIZE / 2;
+
+        assert_eq!(opening.len(), 5);
+        assert!(opening.iter().enumerate().all(|(index, position)| !opening[..index].contains(position)));
+        assert!(opening.iter().all(|position| position.row.abs_diff(center) <= OPENING_RADIUS && position.col.abs_diff(center) <= OPENING_RADIUS));
+        assert_eq!(opening, random_opening(&mut StdRng::seed_from_u64(3), 5));
+    }
+
+    #[test]
+    fn it_shares_statistics_between_symmetric_openings() {
+        let mut book = OpeningBook::new();
+        let last = GOBAN_SIZE - 1;
+        let opening = [Position::new(9, 9), Position::new(8, 10), Position::new(10, 9)];
+        let mirrored: Vec<Position> = opening.iter().map(|position| Position::new(position.row, last - position.col)).collect();
+
+        book.add_game(&opening, Some(Stone::Black), 10);
+        book.add_game(&mirrored, Some(Stone::White), 10);
+
+        // The empty board, the centre and the first white answer in either orientation
+        assert_eq!(book.len(), 3);
+
+        let mut goban = Goban::new();
+
+        goban.apply_move(Move::new(Stone::Black, opening[0]));
+
+        let answers = book.moves(&goban);
+
+        assert_eq!(answers.len(), 2);
+        assert_eq!(answers.iter().map(|played| played.games).sum::<u32>(), 2);
+        assert!(answers.iter().any(|played| played.position == opening[1] && played.wins == 0));
+        assert!(answers.iter().any(|played| played.position == mirrored[1] && played.wins == 1));
+    }
+
+    #[test]
+    fn it_chooses_the_moves_which_scored() {
+        let mut book = OpeningBook::new();
+        let mut rng = StdRng::seed_from_u64(1);
+        let centre = Position::new(9, 9);
+
+        for _ in 0..3 {
+            book.add_game(&[centre], Some(Stone::Black), 1);
+        }
+
+        book.add_game(&[Position::new(9, 10)], Some(Stone::Black), 1);
+        book.add_game(&[Position::new(3, 3)], Some(Stone::White), 1);
+
+        let goban = Goban::new();
+
+        assert_eq!(book.choose(&goban, 0.0, &mut rng).map(|played| played.position), Some(centre));
+
+        for _ in 0..20 {
+            let chosen = book.choose(&goban, 1.0, &mut rng).unwrap();
+
+            assert_ne!(chosen.position, Position::new(3, 3));
+        }
+
+        let mut out_of_book = Goban::new();
+
+        out_of_book.apply_move(Move::new(Stone::Black, Position::new(0, 0)));
+
+        assert_eq!(book.choose(&out_of_book, 1.0, &mut rng), None);
+    }
+
+    #[test]
+    fn it_learns_from_the_results_of_the_games() {
+        let mut book = OpeningBook::new();
+        let refuted = [Position::new(9, 9), Position::new(8, 8)];
+        let sound = [Position::new(9, 9), Position::new(9, 10)];
+
+        book.add_game(&refuted, Some(Stone::White), 2);
+        book.add_game(&sound, Some(Stone::White), 2);
+
+        let mut goban = Goban::new();
+
+        goban.apply_move(Move::new(Stone::Black, refuted[0]));
+
+        let mut rng = StdRng::seed_from_u64(1);
+        let before = book.choose(&goban, 0.0, &mut rng).unwrap();
+
+        // White keeps losing after the move the book preferred, the other one takes over
+        for _ in 0..3 {
+            book.learn(&[refuted[0], before.position, Position::new(0, 0)], Some(Stone::Black));
+        }
+
+        let after = book.choose(&goban, 0.0, &mut rng).unwrap();
+
+        assert_ne!(after.position, before.position);
+        assert_eq!(book.len(), 2);
+        assert_eq!(book.moves(&Goban::new())[0].wins, 3);
+    }
+
+    #[test]
+    fn it_saves_and_loads_a_book() {
+        let mut book = OpeningBook::new();
+        let opening = [Position::new(9, 9), Position::new(8, 8), Position::new(9, 10)];
+
+        book.add_game(&opening, None, 2);
+        book.add_game(&opening[..1], Some(Stone::Black), 2);
+
+        let centre = book.moves(&Goban::new());
+
+        assert_eq!(centre.len(), 1);
+        assert_eq!((centre[0].games, centre[0].wins, centre[0].draws), (2, 1, 1));
+        assert_eq!(centre[0].win_rate(), 0.75);
+
+        let path = env::temp_dir().join(format!("gomoku-book-{}.bin", std::process::id()));
+
+        book.save(&path).unwrap();
+
+        let loaded = OpeningBook::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, book);
+
+        book.prune(2);
+
+        assert_eq!(book.len(), 1);
+    }
+}
@@ -0,0 +1,105 @@
+use std::fmt;
+use std::str::FromStr;
+
+use rand::seq::SliceRandom;
+
+use crate::engine::Engine;
+use crate::error::GomokuError;
+use crate::evaluator::Eval;
+use crate::goban::{Move, Position};
+use crate::gomoku::Gomoku;
+
+/// Trivially weak opponents, sparring partners for beginners and baselines for the match runner
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bot {
+    /// Plays any of the candidate moves with the same probability, drawn from `Gomoku::rng`
+    Random,
+    /// Plays the candidate move after which the static evaluation is best, without looking at the replies
+    Greedy,
+}
+
+impl FromStr for Bot {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "random" => Ok(Bot::Random),
+            "greedy" => Ok(Bot::Greedy),
+            _ => Err(format!("Unknown bot {}, expected random or greedy", name)),
+        }
+    }
+}
+
+impl fmt::Display for Bot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Bot::Random => write!(f, "random"),
+            Bot::Greedy => write!(f, "greedy"),
+        }
+    }
+}
+
+/// Static evaluation after `position` for the side playing it
+fn score_after(gomoku: &mut Gomoku, position: Position) -> isize {
+    let mut child = gomoku.board().clone();
+
+    child.apply_move(Move::new(child.side_to_move(), position));
+
+    // The opponent is to move in the child
+    match gomoku.evaluate_board(&child) {
+        Eval::Won => isize::MIN,
+        Eval::Lost => isize::MAX,
+        Eval::Score(score) => -score,
+    }
+}
+
+impl Engine for Bot {
+    fn choose_move(&mut self, gomoku: &mut Gomoku) -> Result<Position, GomokuError> {
+        let candidates = gomoku.candidates();
+        let chosen = match self {
+            Bot::Random => candidates.choose(&mut gomoku.rng()).copied(),
+            Bot::Greedy => candidates.into_iter().max_by_key(|&position| score_after(gomoku, position)),
+        };
+
+        chosen.ok_or(GomokuError::NoMoveAvailable)
+    }
+}
+
+#[cfg(test)]
+mod bots_tests {
+    use crate::bots::Bot;
+    use crate::engine::Engine;
+    use crate::error::GomokuError;
+    use crate::goban::{Position, Stone};
+    use crate::gomoku::{Gomoku, PositionSetup};
+
+    #[test]
+    fn it_plays_candidate_moves() {
+        let mut gomoku = Gomoku::with_hash_size(1);
+
+        assert_eq!(Bot::Random.choose_move(&mut gomoku), Ok(Position::new(9, 9)));
+
+        gomoku.set_position("J10 K11 K10".parse().unwrap(), Stone::White).unwrap();
+
+        let chosen = Bot::Random.choose_move(&mut gomoku).unwrap();
+
+        assert!(gomoku.candidates().contains(&chosen));
+        assert_eq!(Bot::Random.choose_move(&mut gomoku), Ok(chosen));
+    }
+
+    #[test]
+    fn it_completes_fives_greedily() {
+        let mut gomoku = Gomoku::with_hash_size(1);
+        let setup: PositionSetup = "J10 A1 K10 A3 L10 A5 M10 A7".parse().unwrap();
+
+        gomoku.set_position(setup, Stone::Black).unwrap();
+
+        let chosen = Bot::Greedy.choose_move(&mut gomoku).unwrap();
+
+        assert!(["I10", "N10"].contains(&chosen.to_string().as_str()));
+
+        gomoku.play(chosen, Stone::Black.into()).unwrap();
+
+        assert_eq!(Bot::Greedy.choose_move(&mut gomoku), Err(GomokuError::NoMoveAvailable));
+    }
+}
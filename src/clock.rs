@@ -0,0 +1,278 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::goban::Stone;
+
+// Share of the main time spent on one move
+const MOVES_TO_GO: u32 = 30;
+
+// Keeps some of the budget for the moves to reach the board
+const TIME_MARGIN: f64 = 0.9;
+
+/// Time each side gets for the game, sudden death unless it has an increment or byo-yomi periods
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeControl {
+    /// Time for the whole game
+    pub main: Duration,
+    /// Fischer increment, added to the main time after every move
+    pub increment: Duration,
+    /// Time per move once the main time ran out, each overrun costing a period
+    pub byoyomi: Duration,
+    pub periods: u32,
+}
+
+impl TimeControl {
+    pub fn new(main: Duration) -> Self {
+        Self {
+            main,
+            increment: Duration::ZERO,
+            byoyomi: Duration::ZERO,
+            periods: 0,
+        }
+    }
+
+    pub fn with_increment(mut self, increment: Duration) -> Self {
+        self.increment = increment;
+        self
+    }
+
+    pub fn with_byoyomi(mut self, byoyomi: Duration, periods: u32) -> Self {
+        self.byoyomi = byoyomi;
+        self.periods = periods;
+        self
+    }
+}
+
+/// Chess clock of both sides, the side to move pressing it once its move is on the board
+#[derive(Debug, Clone)]
+pub struct Clock {
+    control: TimeControl,
+    remaining: [Duration; 2],
+    periods: [u32; 2],
+    flagged: Option<Stone>,
+    turn_start: Instant,
+}
+
+impl Clock {
+    pub fn new(control: TimeControl) -> Self {
+        Self {
+            control,
+            remaining: [control.main; 2],
+            periods: [control.periods; 2],
+            flagged: None,
+            turn_start: Instant::now(),
+        }
+    }
+
+    pub fn control(&self) -> TimeControl {
+        self.control
+    }
+
+    /// Main time left to `stone`, the running turn excluded
+    pub fn remaining(&self, stone: Stone) -> Duration {
+        self.remaining[stone as usize]
+    }
+
+    /// Byo-yomi periods left to `stone`
+    pub fn periods(&self, stone: Stone) -> u32 {
+        self.periods[stone as usize]
+    }
+
+    /// Side whose time ran out
+    pub fn flagged(&self) -> Option<Stone> {
+        self.flagged
+    }
+
+    /// Starts the running turn again, the time since it started being nobody's
+    pub fn restart(&mut self) {
+        self.turn_start = Instant::now();
+    }
+
+    /// Ends the turn of `stone`, returns whether it played in time
+    pub fn press(&mut self, stone: Stone) -> bool {
+        let elapsed = self.turn_start.elapsed();
+
+        self.turn_start = Instant::now();
+        self.charge(stone, elapsed)
+    }
+
+    /// Takes `elapsed` from the time of `stone`, returns whether it played in time
+    pub fn charge(&mut self, stone: Stone, elapsed: Duration) -> bool {
+        let side = stone as usize;
+
+        if self.flagged.is_some() {
+            return self.flagged != Some(stone);
+        }
+
+        match self.remaining[side].checked_sub(elapsed) {
+            Some(remaining) => self.remaining[side] = remaining,
+            None => {
+                let overrun = elapsed - self.remaining[side];
+
+                self.remaining[side] = Duration::ZERO;
+
+                // A move within the period keeps it, every period overrun is lost
+                let lost = match self.control.byoyomi.is_zero() {
+                    true => u32::MAX,
+                    false => (overrun.as_nanos() / self.control.byoyomi.as_nanos()) as u32,
+                };
+
+                match self.periods[side].checked_sub(lost) {
+                    Some(periods) if periods > 0 => self.periods[side] = periods,
+                    _ => {
+                        self.periods[side] = 0;
+                        self.flagged = Some(stone);
+
+                        return false;
+                    }
+                }
+            }
+        }
+
+        self.remaining[side] += self.control.increment;
+
+        true
+    }
+
+    /// Time `stone` may think about its next move, a share of its main time or its byo-yomi period
+    pub fn budget(&self, stone: Stone) -> Duration {
+        let remaining = self.remaining(stone);
+        let main = (remaining / MOVES_TO_GO + self.control.increment).min(remaining / 2);
+        let period = match self.periods(stone) {
+            0 => Duration::ZERO,
+            _ => self.control.byoyomi,
+        };
+
+        main.max(period).mul_f64(TIME_MARGIN)
+    }
+}
+
+/// A clock as saved with a game, in milliseconds, the running turn excluded
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "camelCase"))]
+pub struct ClockState {
+    pub main_ms: u64,
+    pub increment_ms: u64,
+    pub byoyomi_ms: u64,
+    pub periods: u32,
+    /// Main time left to black and white
+    pub remaining_ms: [u64; 2],
+    /// Byo-yomi periods left to black and white
+    pub periods_left: [u32; 2],
+    pub flagged: Option<Stone>,
+}
+
+impl Clock {
+    pub fn state(&self) -> ClockState {
+        ClockState {
+            main_ms: self.control.main.as_millis() as u64,
+            increment_ms: self.control.increment.as_millis() as u64,
+            byoyomi_ms: self.control.byoyomi.as_millis() as u64,
+            periods: self.control.periods,
+            remaining_ms: self.remaining.map(|remaining| remaining.as_millis() as u64),
+            periods_left: self.periods,
+            flagged: self.flagged,
+        }
+    }
+
+    /// The clock `state` was saved from, its turn starting now
+    pub fn from_state(state: &ClockState) -> Self {
+        let control = TimeControl::new(Duration::from_millis(state.main_ms))
+            .with_increment(Duration::from_millis(state.increment_ms))
+            .with_byoyomi(Duration::from_millis(state.byoyomi_ms), state.periods);
+
+        Self {
+            control,
+            remaining: state.remaining_ms.map(Duration::from_millis),
+            periods: state.periods_left,
+            flagged: state.flagged,
+            turn_start: Instant::now(),
+        }
+    }
+}
+
+fn minutes(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+impl fmt::Display for Clock {
+    /// Main time of both sides, with their byo-yomi periods when the control has some
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (stone, separator) in [(Stone::Black, ", "), (Stone::White, "")] {
+            write!(f, "{:?} {}", stone, minutes(self.remaining(stone)))?;
+
+            if self.control.periods > 0 {
+                write!(f, " ({} x {}s)", self.periods(stone), self.control.byoyomi.as_secs())?;
+            }
+
+            write!(f, "{}", separator)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod clock_tests {
+    use std::time::Duration;
+
+    use crate::clock::{Clock, ClockState, TimeControl};
+    use crate::goban::Stone;
+
+    #[test]
+    fn it_flags_in_sudden_death() {
+        let mut clock = Clock::new(TimeControl::new(Duration::from_secs(60)));
+
+        assert!(clock.charge(Stone::Black, Duration::from_secs(50)));
+        assert_eq!(clock.remaining(Stone::Black), Duration::from_secs(10));
+        assert_eq!(clock.remaining(Stone::White), Duration::from_secs(60));
+        assert!(!clock.charge(Stone::Black, Duration::from_secs(11)));
+        assert_eq!(clock.flagged(), Some(Stone::Black));
+    }
+
+    #[test]
+    fn it_adds_the_increment_after_each_move() {
+        let mut clock = Clock::new(TimeControl::new(Duration::from_secs(60)).with_increment(Duration::from_secs(5)));
+
+        assert!(clock.charge(Stone::White, Duration::from_secs(20)));
+        assert_eq!(clock.remaining(Stone::White), Duration::from_secs(45));
+        assert!(clock.budget(Stone::White) < clock.remaining(Stone::White));
+    }
+
+    #[test]
+    fn it_spends_byoyomi_periods() {
+        let control = TimeControl::new(Duration::from_secs(10)).with_byoyomi(Duration::from_secs(30), 3);
+        let mut clock = Clock::new(control);
+
+        // Within the first period once the main time ran out
+        assert!(clock.charge(Stone::Black, Duration::from_secs(35)));
+        assert_eq!(clock.remaining(Stone::Black), Duration::ZERO);
+        assert_eq!(clock.periods(Stone::Black), 3);
+        assert_eq!(clock.budget(Stone::Black), Duration::from_secs(27));
+
+        assert!(clock.charge(Stone::Black, Duration::from_secs(40)));
+        assert_eq!(clock.periods(Stone::Black), 2);
+
+        assert!(!clock.charge(Stone::Black, Duration::from_secs(61)));
+        assert_eq!(clock.flagged(), Some(Stone::Black));
+    }
+
+    #[test]
+    fn it_restores_a_saved_clock() {
+        let control = TimeControl::new(Duration::from_secs(60)).with_byoyomi(Duration::from_secs(10), 2);
+        let mut clock = Clock::new(control);
+
+        clock.charge(Stone::Black, Duration::from_millis(65_500));
+        clock.charge(Stone::White, Duration::from_secs(20));
+
+        let state = clock.state();
+        let restored = Clock::from_state(&state);
+
+        assert_eq!(state.remaining_ms, [0, 40_000]);
+        assert_eq!(restored.control(), control);
+        assert_eq!((restored.remaining(Stone::White), restored.periods(Stone::Black)), (Duration::from_secs(40), 2));
+        assert_eq!(restored.state(), ClockState { flagged: None, ..state });
+    }
+}
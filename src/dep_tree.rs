@@ -1,19 +1,13 @@
-use gomoku::goban::{Player, Position};
-use gomoku::gomoku::{Gomoku};
+use gomoku::goban::{Goban, Move, ParseError, Position, Stone};
 
-fn main() -> Result<(),String> {
-    let mut gomoku = Gomoku::default();
+fn main() -> Result<(), ParseError> {
+    let mut goban = Goban::new();
 
-    gomoku.play(Position::from_coordinates("a15")?, Player::Opponent).ok();
-    gomoku.play(Position::from_coordinates("b15")?, Player::Opponent).ok();
-    gomoku.play(Position::from_coordinates("c15")?, Player::Opponent).ok();
+    for coordinates in ["a15", "b15", "c15", "f14", "g13", "i10", "i9"].iter() {
+        goban.apply_move(Move::new(Stone::Black, Position::from_coordinates(coordinates)?));
+    }
 
-    gomoku.play(Position::from_coordinates("f14")?, Player::Opponent).ok();
-    gomoku.play(Position::from_coordinates("g13")?, Player::Opponent).ok();
-    gomoku.play(Position::from_coordinates("i10")?, Player::Opponent).ok();
-    gomoku.play(Position::from_coordinates("i9")?, Player::Opponent).ok();
-
-    gomoku.print_board();
+    println!("{:?}", goban);
 
     Ok(())
 }
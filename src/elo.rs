@@ -0,0 +1,297 @@
+use std::fmt;
+use std::ops::AddAssign;
+use std::str::FromStr;
+
+// Normal quantile of a two-sided 95% confidence interval
+const Z_95: f64 = 1.959964;
+
+/// Result of one game for the first engine, written `W`, `D` or `L` in results files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameOutcome {
+    Win,
+    Draw,
+    Loss,
+}
+
+/// A result other than `W`, `D` or `L`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidOutcome(pub String);
+
+impl fmt::Display for InvalidOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid result `{}`, expected W, D or L", self.0)
+    }
+}
+
+impl std::error::Error for InvalidOutcome {}
+
+impl FromStr for GameOutcome {
+    type Err = InvalidOutcome;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "W" => Ok(GameOutcome::Win),
+            "D" => Ok(GameOutcome::Draw),
+            "L" => Ok(GameOutcome::Loss),
+            _ => Err(InvalidOutcome(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for GameOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameOutcome::Win => write!(f, "W"),
+            GameOutcome::Draw => write!(f, "D"),
+            GameOutcome::Loss => write!(f, "L"),
+        }
+    }
+}
+
+/// Wins, draws and losses of a match, from the first engine's point of view
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl Tally {
+    /// Counts the results of a results file, one per line, skipping blank lines and `#` comments
+    pub fn read(results: &str) -> Result<Self, InvalidOutcome> {
+        let mut tally = Tally::default();
+
+        for line in results.lines().map(str::trim) {
+            if !line.is_empty() && !line.starts_with('#') {
+                tally.add(line.parse()?);
+            }
+        }
+
+        Ok(tally)
+    }
+
+    pub fn add(&mut self, outcome: GameOutcome) {
+        match outcome {
+            GameOutcome::Win => self.wins += 1,
+            GameOutcome::Draw => self.draws += 1,
+            GameOutcome::Loss => self.losses += 1,
+        }
+    }
+
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Share of the points won, a draw counting half
+    pub fn score(&self) -> f64 {
+        match self.games() {
+            0 => 0.5,
+            games => (self.wins as f64 + self.draws as f64 / 2.0) / games as f64,
+        }
+    }
+
+    /// Elo difference the score suggests, `None` before the first game
+    ///
+    /// A score of all wins or all losses has an infinite difference and margin.
+    pub fn elo(&self) -> Option<EloEstimate> {
+        let games = self.games();
+
+        if games == 0 {
+            return None;
+        }
+
+        let deviation = (self.variance() / games as f64).sqrt();
+        let (low, high) = (self.score() - Z_95 * deviation, self.score() + Z_95 * deviation);
+
+        Some(EloEstimate {
+            elo: elo_difference(self.score()),
+            margin: (elo_difference(high) - elo_difference(low)) / 2.0,
+        })
+    }
+
+    /// Variance of the points of one game
+    fn variance(&self) -> f64 {
+        let games = self.games() as f64;
+        let score = self.score();
+
+        (self.wins as f64 * (1.0 - score).powi(2) + self.draws as f64 * (0.5 - score).powi(2) + self.losses as f64 * score.powi(2))
+            / games
+    }
+}
+
+impl AddAssign for Tally {
+    fn add_assign(&mut self, other: Tally) {
+        self.wins += other.wins;
+        self.draws += other.draws;
+        self.losses += other.losses;
+    }
+}
+
+impl fmt::Display for Tally {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "+{} ={} -{}", self.wins, self.draws, self.losses)
+    }
+}
+
+/// Elo difference with the half width of its 95% confidence interval
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EloEstimate {
+    pub elo: f64,
+    pub margin: f64,
+}
+
+impl fmt::Display for EloEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:+.1} +/- {:.1}", self.elo, self.margin)
+    }
+}
+
+/// Elo difference scoring `score` on average, the inverse of `expected_score`
+pub fn elo_difference(score: f64) -> f64 {
+    match score {
+        score if score <= 0.0 => f64::NEG_INFINITY,
+        score if score >= 1.0 => f64::INFINITY,
+        score => 400.0 * (score / (1.0 - score)).log10(),
+    }
+}
+
+/// Expected score against an opponent rated `elo` points lower
+pub fn expected_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+/// Outcome of a sequential probability ratio test so far
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SprtResult {
+    /// The first engine is no better than `elo0`
+    AcceptH0,
+    /// The first engine is at least `elo1` better
+    AcceptH1,
+    Continue,
+}
+
+/// Sequential probability ratio test of an Elo difference of `elo0` against one of `elo1`,
+/// wrongly accepting H1 with a probability of `alpha` and H0 of `beta`
+///
+/// The log-likelihood ratio uses the normal approximation of the trinomial distribution of the games.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl Sprt {
+    pub fn new(elo0: f64, elo1: f64, alpha: f64, beta: f64) -> Self {
+        Self { elo0, elo1, alpha, beta }
+    }
+
+    /// Log-likelihood ratios under which H0 and over which H1 are accepted
+    pub fn bounds(&self) -> (f64, f64) {
+        let lower = (self.beta / (1.0 - self.alpha)).ln();
+        let upper = ((1.0 - self.beta) / self.alpha).ln();
+
+        (lower, upper)
+    }
+
+    /// Log-likelihood ratio of H1 over H0 given the games of `tally`
+    pub fn llr(&self, tally: &Tally) -> f64 {
+        let variance = match tally.games() {
+            0 => return 0.0,
+            _ => tally.variance(),
+        };
+
+        // Only wins, only draws or only losses tell nothing of the spread yet, a win and a loss
+        // stand in for it so that a one-sided match still ends
+        let variance = match variance {
+            variance if variance > 0.0 => variance,
+            _ => Tally { wins: tally.wins + 1, draws: tally.draws, losses: tally.losses + 1 }.variance(),
+        };
+
+        let (score0, score1) = (expected_score(self.elo0), expected_score(self.elo1));
+
+        tally.games() as f64 * (score1 - score0) * (2.0 * tally.score() - score0 - score1) / (2.0 * variance)
+    }
+
+    pub fn test(&self, tally: &Tally) -> SprtResult {
+        let (lower, upper) = self.bounds();
+        let llr = self.llr(tally);
+
+        if llr >= upper {
+            SprtResult::AcceptH1
+        } else if llr <= lower {
+            SprtResult::AcceptH0
+        } else {
+            SprtResult::Continue
+        }
+    }
+}
+
+#[cfg(test)]
+mod elo_tests {
+    use crate::elo::{elo_difference, expected_score, GameOutcome, Sprt, SprtResult, Tally};
+
+    #[test]
+    fn it_expects_even_scores_between_equals() {
+        assert_eq!(expected_score(0.0), 0.5);
+        assert!(expected_score(100.0) > 0.6);
+        assert!((expected_score(100.0) + expected_score(-100.0) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn it_scores_draws_half() {
+        let tally = Tally { wins: 3, draws: 2, losses: 5 };
+
+        assert_eq!(tally.games(), 10);
+        assert_eq!(tally.score(), 0.4);
+        assert_eq!(tally.to_string(), "+3 =2 -5");
+    }
+
+    #[test]
+    fn it_estimates_the_elo_difference() {
+        assert!((elo_difference(expected_score(150.0)) - 150.0).abs() < 1e-9);
+        assert_eq!(Tally::default().elo(), None);
+
+        let even = Tally { wins: 40, draws: 20, losses: 40 }.elo().unwrap();
+
+        assert_eq!(even.elo, 0.0);
+        assert!(even.margin > 0.0);
+
+        let ahead = Tally { wins: 400, draws: 200, losses: 400 }.elo().unwrap();
+        let stronger = Tally { wins: 60, draws: 20, losses: 20 }.elo().unwrap();
+
+        // More games narrow the interval, which holds the difference when it is significant
+        assert!(ahead.margin < even.margin);
+        assert!(stronger.elo - stronger.margin > 0.0);
+    }
+
+    #[test]
+    fn it_reads_results_files() {
+        let tally = Tally::read("# A against B\nW\nd\n\nL\nW\n").unwrap();
+
+        assert_eq!(tally, Tally { wins: 2, draws: 1, losses: 1 });
+        assert_eq!("l".parse(), Ok(GameOutcome::Loss));
+        assert!(Tally::read("W\n1-0\n").is_err());
+    }
+
+    #[test]
+    fn it_stops_once_the_evidence_is_strong_enough() {
+        let sprt = Sprt::new(0.0, 10.0, 0.05, 0.05);
+
+        assert_eq!(sprt.test(&Tally::default()), SprtResult::Continue);
+        assert_eq!(sprt.test(&Tally { wins: 10, draws: 0, losses: 8 }), SprtResult::Continue);
+        assert_eq!(sprt.test(&Tally { wins: 900, draws: 200, losses: 600 }), SprtResult::AcceptH1);
+        assert_eq!(sprt.test(&Tally { wins: 600, draws: 200, losses: 900 }), SprtResult::AcceptH0);
+    }
+
+    #[test]
+    fn it_decides_matches_won_or_lost_every_game() {
+        let sprt = Sprt::new(0.0, 10.0, 0.05, 0.05);
+
+        assert_eq!(sprt.test(&Tally { wins: 2, draws: 0, losses: 0 }), SprtResult::Continue);
+        assert_eq!(sprt.test(&Tally { wins: 100, draws: 0, losses: 0 }), SprtResult::AcceptH1);
+        assert_eq!(sprt.test(&Tally { wins: 0, draws: 0, losses: 100 }), SprtResult::AcceptH0);
+        assert_eq!(sprt.test(&Tally { wins: 0, draws: 200, losses: 0 }), SprtResult::AcceptH0);
+    }
+}
@@ -0,0 +1,259 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crate::error::GomokuError;
+use crate::goban::{Position, Stone, GOBAN_SIZE};
+use crate::gomoku::{Gomoku, PositionSetup, Rule, SearchEvent, SearchLimits, SearchReport};
+
+/// Identification of the engine and of the games it can play
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct About {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub author: &'static str,
+    pub rules: &'static [Rule],
+    pub board_sizes: &'static [usize],
+}
+
+/// Written the way the Gomocup `ABOUT` command expects it
+impl fmt::Display for About {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "name=\"{}\", version=\"{}\", author=\"{}\"", self.name, self.version, self.author)
+    }
+}
+
+pub fn about() -> About {
+    About {
+        name: env!("CARGO_PKG_NAME"),
+        version: env!("CARGO_PKG_VERSION"),
+        author: env!("CARGO_PKG_AUTHORS"),
+        rules: &[Rule::Freestyle],
+        board_sizes: &[GOBAN_SIZE],
+    }
+}
+
+/// Openings, middlegames and tactical positions searched by `gomoku bench` and compared by
+/// `gomoku-regress`, as moves played from black
+pub const BENCH_POSITIONS: [&str; 8] = [
+    "J10",
+    "J10 K11 K10",
+    "J10 K11 K9 L10 J12 J11",
+    "J10 K11 K10 J11 L11 M12 L9 L10 K8",
+    "J10 K11 J11 J12 K12 L13 I9 H8 I11 L11",
+    "J10 K10 J11 J9 K11 L12 I11 H11 I10 K12 I12 I13",
+    "G7 H8 H7 I8 J8 I7 I6 J9 K10 H6 G9 H9 H10 J7 K7 G8",
+    "J10 J11 K10 K11 L10 H10 M11 L12 N12 K9 M9 M10 L8 N10 O10 K8 J7",
+];
+
+/// Chooses the moves of one side of a game, the search or a baseline bot
+pub trait Engine {
+    /// Move for the side to move of `gomoku`, which holds the game and the tables of this side
+    fn choose_move(&mut self, gomoku: &mut Gomoku) -> Result<Position, GomokuError>;
+}
+
+/// The full search within `limits`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Searcher {
+    pub limits: SearchLimits,
+}
+
+impl Engine for Searcher {
+    fn choose_move(&mut self, gomoku: &mut Gomoku) -> Result<Position, GomokuError> {
+        gomoku.suggest_move(self.limits).map(|report| report.best_move)
+    }
+}
+
+pub enum Command {
+    /// Replaces the position, see `Gomoku::set_position`
+    Position(PositionSetup, Stone),
+    /// Searches the position within the limits, answered by `Event::BestMove`
+    Go(SearchLimits),
+    /// Ends the searches sent so far early, their best moves so far are still reported
+    Stop,
+    Quit,
+}
+
+#[derive(Debug)]
+pub enum Event {
+    /// Diagnostics of the running search
+    Info(String),
+    /// Progress of the running search
+    Search(SearchEvent),
+    BestMove(SearchReport),
+    Error(GomokuError),
+}
+
+/// Engine running on a background thread, driven by commands and answering with events
+///
+/// Commands are handled in order, except `Stop` which reaches the running search at once.
+/// Dropping the handle quits the engine.
+pub struct EngineHandle {
+    commands: Sender<(Command, Arc<AtomicBool>)>,
+    events: Receiver<Event>,
+    /// Stop flags of the searches sent since the last `Stop`, one for every `Go`
+    stops: Mutex<Vec<Arc<AtomicBool>>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl EngineHandle {
+    pub fn spawn(mut gomoku: Gomoku) -> Self {
+        let (commands, command_receiver) = mpsc::channel();
+        let (event_sender, events) = mpsc::channel();
+
+        let info_sender = event_sender.clone();
+
+        gomoku.set_logger(move |message| {
+            info_sender.send(Event::Info(message.to_string())).ok();
+        });
+
+        let search_sender = event_sender.clone();
+
+        gomoku.set_search_observer(move |event| {
+            search_sender.send(Event::Search(event.clone())).ok();
+        });
+
+        let worker = thread::spawn(move || Self::run(gomoku, command_receiver, event_sender));
+
+        Self {
+            commands,
+            events,
+            stops: Mutex::new(Vec::new()),
+            worker: Some(worker),
+        }
+    }
+
+    pub fn send(&self, command: Command) {
+        let mut stops = self.stops.lock().unwrap();
+
+        match command {
+            Command::Stop => {
+                for stop in stops.drain(..) {
+                    stop.store(true, Ordering::Relaxed);
+                }
+            }
+            command => {
+                // Each search gets its own flag, so stopping one never leaks into the next
+                let stop = Arc::new(AtomicBool::new(false));
+
+                if let Command::Go(_) = command {
+                    // Flags only held here belong to searches that are over
+                    stops.retain(|stop| Arc::strong_count(stop) > 1);
+                    stops.push(stop.clone());
+                }
+
+                self.commands.send((command, stop)).ok();
+            }
+        }
+    }
+
+    pub fn events(&self) -> &Receiver<Event> {
+        &self.events
+    }
+
+    fn run(mut gomoku: Gomoku, commands: Receiver<(Command, Arc<AtomicBool>)>, events: Sender<Event>) {
+        for (command, stop) in commands {
+            let event = match command {
+                Command::Position(setup, side_to_move) => match gomoku.set_position(setup, side_to_move) {
+                    Ok(_) => continue,
+                    Err(error) => Event::Error(error),
+                },
+                Command::Go(limits) => {
+                    gomoku.set_stop_flag(stop);
+
+                    match gomoku.suggest_move(limits) {
+                        Ok(report) => Event::BestMove(report),
+                        Err(error) => Event::Error(error),
+                    }
+                }
+                Command::Stop => continue,
+                Command::Quit => break,
+            };
+
+            if events.send(event).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+impl Drop for EngineHandle {
+    fn drop(&mut self) {
+        self.send(Command::Stop);
+        self.send(Command::Quit);
+
+        if let Some(worker) = self.worker.take() {
+            worker.join().ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod engine_tests {
+    use crate::engine::{about, Command, EngineHandle, Event};
+    use crate::error::GomokuError;
+    use crate::goban::Stone;
+    use crate::gomoku::{Gomoku, SearchLimits};
+
+    fn best_move_or_error(engine: &EngineHandle) -> Event {
+        engine
+            .events()
+            .iter()
+            .find(|event| !matches!(event, Event::Info(_) | Event::Search(_)))
+            .unwrap()
+    }
+
+    #[test]
+    fn it_introduces_itself_for_gomocup() {
+        assert_eq!(about().to_string(), format!("name=\"gomoku\", version=\"{}\", author=\"angauber\"", env!("CARGO_PKG_VERSION")));
+    }
+
+    #[test]
+    fn it_answers_commands_in_order() {
+        let engine = EngineHandle::spawn(Gomoku::with_hash_size(1));
+
+        engine.send(Command::Go(SearchLimits::new(3)));
+        assert!(matches!(best_move_or_error(&engine), Event::Error(GomokuError::InvalidDepth(3))));
+
+        engine.send(Command::Position("j10".parse().unwrap(), Stone::White));
+        engine.send(Command::Go(SearchLimits::new(2)));
+
+        match best_move_or_error(&engine) {
+            Event::BestMove(report) => assert_ne!((report.best_move.row, report.best_move.col), (9, 9)),
+            event => panic!("unexpected event {:?}", event),
+        }
+    }
+
+    #[test]
+    fn it_stops_the_running_search() {
+        let engine = EngineHandle::spawn(Gomoku::with_hash_size(1));
+
+        engine.send(Command::Position("j10 k11 h9".parse().unwrap(), Stone::White));
+        engine.send(Command::Go(SearchLimits::new(8)));
+        engine.send(Command::Stop);
+
+        assert!(matches!(best_move_or_error(&engine), Event::BestMove(_)));
+    }
+
+    #[test]
+    fn it_does_not_carry_a_stop_over_to_the_next_search() {
+        let engine = EngineHandle::spawn(Gomoku::with_hash_size(1));
+
+        engine.send(Command::Position("j10 k11 h9".parse().unwrap(), Stone::White));
+        engine.send(Command::Go(SearchLimits::new(10)));
+        engine.send(Command::Stop);
+        engine.send(Command::Go(SearchLimits::new(2)));
+
+        match best_move_or_error(&engine) {
+            Event::BestMove(report) => assert!(report.depth < 10),
+            event => panic!("unexpected event {:?}", event),
+        }
+
+        match best_move_or_error(&engine) {
+            Event::BestMove(report) => assert_eq!(report.depth, 2),
+            event => panic!("unexpected event {:?}", event),
+        }
+    }
+}
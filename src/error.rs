@@ -0,0 +1,61 @@
+use std::fmt;
+
+use crate::goban::{ParseError, Position};
+
+/// Why a move cannot be played
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidMoveReason {
+    OutOfBounds,
+    Occupied,
+}
+
+impl fmt::Display for InvalidMoveReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InvalidMoveReason::OutOfBounds => write!(f, "outside of the board"),
+            InvalidMoveReason::Occupied => write!(f, "already occupied"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GomokuError {
+    InvalidMove { reason: InvalidMoveReason, position: Position },
+    OutOfTurn,
+    GameOver,
+    InvalidDepth(usize),
+    NoMoveAvailable,
+    InvalidSetup(&'static str),
+    AlreadySeeded,
+    ParseError(ParseError),
+}
+
+impl fmt::Display for GomokuError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GomokuError::InvalidMove { reason, position } => write!(f, "Invalid move {}: {}", position, reason),
+            GomokuError::OutOfTurn => write!(f, "Not this player's turn"),
+            GomokuError::GameOver => write!(f, "The game is over"),
+            GomokuError::InvalidDepth(depth) => write!(f, "Invalid search depth {}, it must be even and at least 2", depth),
+            GomokuError::NoMoveAvailable => write!(f, "No move available"),
+            GomokuError::InvalidSetup(reason) => write!(f, "Invalid position: {}", reason),
+            GomokuError::AlreadySeeded => write!(f, "Zobrist keys are already initialized, the seed cannot change"),
+            GomokuError::ParseError(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for GomokuError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GomokuError::ParseError(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for GomokuError {
+    fn from(error: ParseError) -> Self {
+        GomokuError::ParseError(error)
+    }
+}
@@ -0,0 +1,118 @@
+use std::mem::size_of;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::evaluator::Eval;
+use crate::zobrist_hashing::ZobristHash;
+
+pub const DEFAULT_SIZE_MB: usize = 8;
+
+const WON_SCORE: i64 = i64::MAX;
+const LOST_SCORE: i64 = i64::MIN;
+
+// The occupied bit lives in the key so an empty slot never verifies
+const OCCUPIED_KEY: u64 = 1;
+
+#[derive(Default)]
+struct Slot {
+    key: AtomicU64,
+    score: AtomicU64,
+}
+
+/// Memo of static evaluations of the side to move, apart from the search table
+///
+/// Evaluations are cheap to recompute and far more numerous than search nodes,
+/// so the newest one always replaces the previous occupant of its slot.
+/// Like the transposition table, slots are lockless and verified on the upper half of the hash.
+pub struct EvalCache {
+    slots: Vec<Slot>,
+    mask: usize,
+}
+
+impl EvalCache {
+    pub fn with_size_mb(size_mb: usize) -> Self {
+        let budget = size_mb * 1024 * 1024 / size_of::<Slot>();
+        let capacity = match budget {
+            0 | 1 => 1,
+            n if n.is_power_of_two() => n,
+            n => n.next_power_of_two() >> 1,
+        };
+
+        Self {
+            slots: (0..capacity).map(|_| Slot::default()).collect(),
+            mask: capacity - 1,
+        }
+    }
+
+    pub fn clear(&self) {
+        for slot in self.slots.iter() {
+            slot.key.store(0, Ordering::Relaxed);
+            slot.score.store(0, Ordering::Relaxed);
+        }
+    }
+
+    pub fn probe(&self, hash: ZobristHash) -> Option<Eval> {
+        let slot = &self.slots[hash as usize & self.mask];
+
+        let score = slot.score.load(Ordering::Relaxed);
+        let key = slot.key.load(Ordering::Relaxed) ^ score;
+
+        if key != Self::key(hash) {
+            return None;
+        }
+
+        Some(match score as i64 {
+            WON_SCORE => Eval::Won,
+            LOST_SCORE => Eval::Lost,
+            n => Eval::Score(n as isize),
+        })
+    }
+
+    pub fn store(&self, hash: ZobristHash, eval: Eval) {
+        let slot = &self.slots[hash as usize & self.mask];
+
+        let score = match eval {
+            Eval::Won => WON_SCORE,
+            Eval::Lost => LOST_SCORE,
+            Eval::Score(n) => (n as i64).clamp(LOST_SCORE + 1, WON_SCORE - 1),
+        } as u64;
+
+        slot.key.store(Self::key(hash) ^ score, Ordering::Relaxed);
+        slot.score.store(score, Ordering::Relaxed);
+    }
+
+    fn key(hash: ZobristHash) -> u64 {
+        (hash >> 64) as u64 | OCCUPIED_KEY
+    }
+}
+
+impl Default for EvalCache {
+    fn default() -> Self {
+        Self::with_size_mb(DEFAULT_SIZE_MB)
+    }
+}
+
+#[cfg(test)]
+mod eval_cache_tests {
+    use crate::eval_cache::EvalCache;
+    use crate::evaluator::Eval;
+
+    #[test]
+    fn it_caches_evaluations_until_replaced() {
+        let cache = EvalCache::with_size_mb(0);
+        let hash = 3 << 64 | 7;
+
+        assert_eq!(cache.probe(0), None);
+        assert_eq!(cache.probe(hash), None);
+
+        for eval in [Eval::Won, Eval::Lost, Eval::Score(-20000), Eval::Score(0)] {
+            cache.store(hash, eval);
+
+            assert_eq!(cache.probe(hash), Some(eval));
+            assert_eq!(cache.probe(hash ^ 1 << 100), None);
+        }
+
+        cache.store(5 << 64, Eval::Won);
+
+        assert_eq!(cache.probe(hash), None);
+    }
+}
@@ -1,12 +1,54 @@
 use crate::goban::Bitboard;
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+// Score normalized to about three quarters, the value of a four
+const NORMALIZATION_SCALE: f64 = 50_000.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename_all = "lowercase"))]
 pub enum Eval {
     Won,
     Lost,
     Score(isize),
 }
 
+impl Eval {
+    /// The same evaluation seen from the other player
+    pub fn opposite(&self) -> Eval {
+        match self {
+            Eval::Won => Eval::Lost,
+            Eval::Lost => Eval::Won,
+            Eval::Score(n) => Eval::Score(-n),
+        }
+    }
+
+    /// The evaluation squashed between -1 for a loss and 1 for a win, for eval bars
+    pub fn normalized(&self) -> f64 {
+        match self {
+            Eval::Won => 1.0,
+            Eval::Lost => -1.0,
+            Eval::Score(n) => (*n as f64 / NORMALIZATION_SCALE).tanh(),
+        }
+    }
+}
+
 pub trait Evaluator {
     fn evaluate(&mut self, player: &Bitboard, opponent: &Bitboard) -> Eval;
 }
+
+#[cfg(test)]
+mod eval_tests {
+    use crate::evaluator::Eval;
+
+    #[test]
+    fn it_normalizes_between_loss_and_win() {
+        assert_eq!(Eval::Won.normalized(), 1.0);
+        assert_eq!(Eval::Lost.normalized(), -1.0);
+        assert_eq!(Eval::Score(0).normalized(), 0.0);
+
+        let four = Eval::Score(50_000).normalized();
+
+        assert!(four > 0.5 && four < 1.0);
+        assert_eq!(Eval::Score(-50_000).normalized(), -four);
+        assert!(Eval::Score(isize::MAX).normalized() <= 1.0);
+    }
+}
@@ -0,0 +1,247 @@
+use std::ffi::{c_char, c_int};
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Duration;
+
+use crate::error::{GomokuError, InvalidMoveReason};
+use crate::goban::{Player, Position, Stone, GOBAN_SIZE};
+use crate::gomoku::{GameState, Gomoku, SearchLimits};
+
+// The search deepens until it runs out of time, this only bounds it on trivial positions
+const MAX_DEPTH: usize = 20;
+
+pub const GOMOKU_OK: c_int = 0;
+pub const GOMOKU_NULL_POINTER: c_int = 1;
+pub const GOMOKU_OUT_OF_BOUNDS: c_int = 2;
+pub const GOMOKU_OCCUPIED: c_int = 3;
+pub const GOMOKU_GAME_OVER: c_int = 4;
+pub const GOMOKU_NO_MOVE: c_int = 5;
+pub const GOMOKU_INVALID_ARGUMENT: c_int = 6;
+pub const GOMOKU_PANIC: c_int = 7;
+
+pub const GOMOKU_EMPTY: c_int = 0;
+pub const GOMOKU_BLACK: c_int = 1;
+pub const GOMOKU_WHITE: c_int = 2;
+
+pub const GOMOKU_IN_PROGRESS: c_int = 0;
+pub const GOMOKU_BLACK_WON: c_int = 1;
+pub const GOMOKU_WHITE_WON: c_int = 2;
+pub const GOMOKU_DRAW: c_int = 3;
+
+/// Engine handle given to C, `include/gomoku.h` declares the API
+pub struct GomokuEngine {
+    gomoku: Gomoku,
+}
+
+fn status(error: GomokuError) -> c_int {
+    match error {
+        GomokuError::InvalidMove { reason: InvalidMoveReason::OutOfBounds, .. } => GOMOKU_OUT_OF_BOUNDS,
+        GomokuError::InvalidMove { reason: InvalidMoveReason::Occupied, .. } => GOMOKU_OCCUPIED,
+        GomokuError::GameOver => GOMOKU_GAME_OVER,
+        GomokuError::NoMoveAvailable => GOMOKU_NO_MOVE,
+        _ => GOMOKU_INVALID_ARGUMENT,
+    }
+}
+
+/// Runs `call` on the engine, a panic must not unwind into C
+unsafe fn with_engine<F>(engine: *mut GomokuEngine, call: F) -> c_int
+where
+    F: FnOnce(&mut Gomoku) -> Result<(), GomokuError>,
+{
+    let engine = match engine.as_mut() {
+        Some(engine) => engine,
+        None => return GOMOKU_NULL_POINTER,
+    };
+
+    match panic::catch_unwind(AssertUnwindSafe(|| call(&mut engine.gomoku))) {
+        Ok(Ok(())) => GOMOKU_OK,
+        Ok(Err(error)) => status(error),
+        Err(_) => GOMOKU_PANIC,
+    }
+}
+
+/// Creates an engine with a transposition table of `hash_size_mb` megabytes, at least one
+///
+/// The engine is released with `gomoku_free`.
+#[no_mangle]
+pub extern "C" fn gomoku_new(hash_size_mb: usize) -> *mut GomokuEngine {
+    let gomoku = Gomoku::with_hash_size(hash_size_mb.max(1));
+
+    Box::into_raw(Box::new(GomokuEngine { gomoku }))
+}
+
+/// Releases an engine, null is ignored
+///
+/// # Safety
+///
+/// `engine` comes from `gomoku_new` and is not used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn gomoku_free(engine: *mut GomokuEngine) {
+    if !engine.is_null() {
+        drop(Box::from_raw(engine));
+    }
+}
+
+/// Starts a new game, the caches are kept
+///
+/// # Safety
+///
+/// `engine` comes from `gomoku_new` and is not freed.
+#[no_mangle]
+pub unsafe extern "C" fn gomoku_reset(engine: *mut GomokuEngine) -> c_int {
+    with_engine(engine, |gomoku| {
+        gomoku.reset();
+        Ok(())
+    })
+}
+
+/// Plays the side to move's stone, rows are counted from the top and columns from the left
+///
+/// # Safety
+///
+/// `engine` comes from `gomoku_new` and is not freed.
+#[no_mangle]
+pub unsafe extern "C" fn gomoku_play(engine: *mut GomokuEngine, row: u32, col: u32) -> c_int {
+    with_engine(engine, |gomoku| {
+        let player = gomoku.board().side_to_move().into();
+
+        gomoku.play(Position::new(row as usize, col as usize), player).map(|_| ())
+    })
+}
+
+/// Searches the side to move's best move without playing it, for up to `timeout_ms`
+///
+/// A zero timeout searches to the engine's default depth instead.
+///
+/// # Safety
+///
+/// `engine` comes from `gomoku_new` and is not freed, `row` and `col` are writable.
+#[no_mangle]
+pub unsafe extern "C" fn gomoku_best_move(engine: *mut GomokuEngine, timeout_ms: u32, row: *mut u32, col: *mut u32) -> c_int {
+    if row.is_null() || col.is_null() {
+        return GOMOKU_NULL_POINTER;
+    }
+
+    with_engine(engine, |gomoku| {
+        let limits = match timeout_ms {
+            0 => SearchLimits::new(gomoku.search_depth()),
+            timeout => SearchLimits::new(MAX_DEPTH).with_movetime(Duration::from_millis(timeout as u64)),
+        };
+        let report = gomoku.suggest_move(limits)?;
+
+        *row = report.best_move.row as u32;
+        *col = report.best_move.col as u32;
+
+        Ok(())
+    })
+}
+
+/// Writes `GOMOKU_EMPTY`, `GOMOKU_BLACK` or `GOMOKU_WHITE` for the intersection
+///
+/// # Safety
+///
+/// `engine` comes from `gomoku_new` and is not freed, `stone` is writable.
+#[no_mangle]
+pub unsafe extern "C" fn gomoku_stone_at(engine: *mut GomokuEngine, row: u32, col: u32, stone: *mut c_int) -> c_int {
+    if stone.is_null() {
+        return GOMOKU_NULL_POINTER;
+    }
+
+    with_engine(engine, |gomoku| {
+        let position = Position::new(row as usize, col as usize);
+
+        if position.row >= GOBAN_SIZE || position.col >= GOBAN_SIZE {
+            return Err(GomokuError::InvalidMove { reason: InvalidMoveReason::OutOfBounds, position });
+        }
+
+        *stone = match gomoku.board().get(position.row, position.col) {
+            Some(Stone::Black) => GOMOKU_BLACK,
+            Some(Stone::White) => GOMOKU_WHITE,
+            None => GOMOKU_EMPTY,
+        };
+
+        Ok(())
+    })
+}
+
+/// Writes `GOMOKU_IN_PROGRESS`, `GOMOKU_BLACK_WON`, `GOMOKU_WHITE_WON` or `GOMOKU_DRAW`
+///
+/// # Safety
+///
+/// `engine` comes from `gomoku_new` and is not freed, `state` is writable.
+#[no_mangle]
+pub unsafe extern "C" fn gomoku_game_state(engine: *mut GomokuEngine, state: *mut c_int) -> c_int {
+    if state.is_null() {
+        return GOMOKU_NULL_POINTER;
+    }
+
+    with_engine(engine, |gomoku| {
+        *state = match gomoku.game_state() {
+            GameState::InProgress => GOMOKU_IN_PROGRESS,
+            GameState::Won(Player::Opponent) => GOMOKU_BLACK_WON,
+            GameState::Won(Player::Computer) => GOMOKU_WHITE_WON,
+            GameState::Draw => GOMOKU_DRAW,
+        };
+
+        Ok(())
+    })
+}
+
+/// Describes a status code, the string is static and must not be freed
+#[no_mangle]
+pub extern "C" fn gomoku_status_message(status: c_int) -> *const c_char {
+    let message: &'static [u8] = match status {
+        GOMOKU_OK => b"ok\0",
+        GOMOKU_NULL_POINTER => b"null pointer\0",
+        GOMOKU_OUT_OF_BOUNDS => b"outside of the board\0",
+        GOMOKU_OCCUPIED => b"already occupied\0",
+        GOMOKU_GAME_OVER => b"the game is over\0",
+        GOMOKU_NO_MOVE => b"no move available\0",
+        GOMOKU_INVALID_ARGUMENT => b"invalid argument\0",
+        GOMOKU_PANIC => b"internal error\0",
+        _ => b"unknown status\0",
+    };
+
+    message.as_ptr() as *const c_char
+}
+
+#[cfg(test)]
+mod ffi_tests {
+    use std::ffi::CStr;
+    use std::ptr;
+
+    use crate::ffi::*;
+
+    #[test]
+    fn it_plays_a_game_through_the_c_api() {
+        unsafe {
+            let engine = gomoku_new(1);
+            let (mut row, mut col, mut stone, mut state) = (0, 0, GOMOKU_EMPTY, GOMOKU_DRAW);
+
+            assert_eq!(gomoku_play(engine, 9, 9), GOMOKU_OK);
+            assert_eq!(gomoku_play(engine, 9, 9), GOMOKU_OCCUPIED);
+            assert_eq!(gomoku_play(engine, 19, 0), GOMOKU_OUT_OF_BOUNDS);
+
+            assert_eq!(gomoku_best_move(engine, 200, &mut row, &mut col), GOMOKU_OK);
+            assert_eq!(gomoku_play(engine, row, col), GOMOKU_OK);
+
+            assert_eq!(gomoku_stone_at(engine, row, col, &mut stone), GOMOKU_OK);
+            assert_eq!(stone, GOMOKU_WHITE);
+            assert_eq!(gomoku_game_state(engine, &mut state), GOMOKU_OK);
+            assert_eq!(state, GOMOKU_IN_PROGRESS);
+
+            gomoku_free(engine);
+        }
+    }
+
+    #[test]
+    fn it_reports_null_pointers() {
+        unsafe {
+            let mut state = 0;
+
+            assert_eq!(gomoku_game_state(ptr::null_mut(), &mut state), GOMOKU_NULL_POINTER);
+            assert_eq!(CStr::from_ptr(gomoku_status_message(GOMOKU_NULL_POINTER)).to_str(), Ok("null pointer"));
+
+            gomoku_free(ptr::null_mut());
+        }
+    }
+}
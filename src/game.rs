@@ -0,0 +1,498 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::ClockState;
+use crate::error::GomokuError;
+use crate::goban::{Move, Player, Position, Stone, GOBAN_SIZE};
+use crate::gomoku::{CandidateMove, GameState, Gomoku, PositionSetup, Rule, SearchLimits};
+use crate::sgf::SgfGame;
+use crate::threat_evaluator::LineThreat;
+
+/// How a recorded game ended
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum GameResult {
+    BlackWon,
+    WhiteWon,
+    Draw,
+}
+
+impl GameResult {
+    /// `None` while the game is in progress
+    pub fn from_state(state: GameState) -> Option<Self> {
+        match state {
+            GameState::InProgress => None,
+            GameState::Won(Player::Opponent) => Some(GameResult::BlackWon),
+            GameState::Won(Player::Computer) => Some(GameResult::WhiteWon),
+            GameState::Draw => Some(GameResult::Draw),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GameMove {
+    pub stone: Stone,
+    #[serde(rename = "move")]
+    pub position: Position,
+    /// Time the side took to play the move
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_ms: Option<u64>,
+}
+
+/// A move without its time
+impl From<Move> for GameMove {
+    fn from(played: Move) -> Self {
+        Self {
+            stone: played.stone,
+            position: played.position,
+            time_ms: None,
+        }
+    }
+}
+
+/// Record of a game, saved as JSON so an interrupted game can be resumed
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    pub rule: Rule,
+    pub moves: Vec<GameMove>,
+    pub result: Option<GameResult>,
+    /// Seed of the engine's random choices, to reproduce its moves
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// Time left on both sides when the game was saved, for timed games
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clock: Option<ClockState>,
+}
+
+/// The engine's view of a position of a game, exported for viewers and review tools
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlyAnalysis {
+    /// Moves played before the position
+    pub ply: usize,
+    pub side_to_move: Stone,
+    /// Move the game continued with, `None` after the last one
+    pub played: Option<Position>,
+    /// Best moves first, empty once the game is over
+    pub candidates: Vec<CandidateMove>,
+    pub threats: Vec<LineThreat>,
+}
+
+/// Score a move may lose to the best one before a review flags it, about a three
+pub const BLUNDER_THRESHOLD: isize = 20_000;
+
+/// A move of a game next to the engine's best move in the position before it
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveReview {
+    /// Moves played before this one
+    pub ply: usize,
+    pub stone: Stone,
+    pub played: Position,
+    /// Score of the played move from its side's point of view
+    pub score: isize,
+    /// Engine's choice in the position, the played move when nothing scores better
+    pub best: CandidateMove,
+    /// Score of the engine's second choice, `None` when the move was forced
+    pub runner_up: Option<isize>,
+    /// Score lost to the best move, zero when the played move is as good
+    pub score_drop: isize,
+}
+
+/// Mark of a reviewed move, as in game records
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Annotation {
+    /// `!`, the only move keeping the score, every other losing more than the blunder threshold
+    Good,
+    /// `?`, losing more than half the blunder threshold
+    Mistake,
+    /// `??`
+    Blunder,
+}
+
+impl fmt::Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Annotation::Good => write!(f, "!"),
+            Annotation::Mistake => write!(f, "?"),
+            Annotation::Blunder => write!(f, "??"),
+        }
+    }
+}
+
+impl MoveReview {
+    /// Whether the move lost more than `threshold` to the best one
+    pub fn is_blunder(&self, threshold: isize) -> bool {
+        self.score_drop > threshold
+    }
+
+    /// Mark of the move for a blunder threshold of `threshold`, `None` for an unremarkable one
+    pub fn annotation(&self, threshold: isize) -> Option<Annotation> {
+        let only_move = self.played == self.best.position
+            && self.runner_up.is_some_and(|runner_up| self.best.score.saturating_sub(runner_up) > threshold);
+
+        match self.score_drop {
+            _ if self.is_blunder(threshold) => Some(Annotation::Blunder),
+            score_drop if score_drop > threshold / 2 => Some(Annotation::Mistake),
+            _ if only_move => Some(Annotation::Good),
+            _ => None,
+        }
+    }
+
+    /// Score after the move from black's point of view
+    pub fn black_score(&self) -> isize {
+        match self.stone {
+            Stone::Black => self.score,
+            Stone::White => negate(self.score),
+        }
+    }
+}
+
+/// Score from the other side's point of view, proven wins becoming proven losses
+fn negate(score: isize) -> isize {
+    match score {
+        isize::MAX => isize::MIN,
+        isize::MIN => isize::MAX,
+        score => -score,
+    }
+}
+
+impl Game {
+    /// The game's moves so far, without their times
+    pub fn from_gomoku(gomoku: &Gomoku) -> Self {
+        Self {
+            rule: Rule::Freestyle,
+            moves: gomoku.history().iter().copied().map(GameMove::from).collect(),
+            result: GameResult::from_state(gomoku.game_state()),
+            seed: Some(gomoku.seed()),
+            clock: None,
+        }
+    }
+
+    /// Side whose turn it is once every move is played, black starting
+    pub fn side_to_move(&self) -> Stone {
+        self.moves
+            .last()
+            .map_or(Stone::Black, |played| played.stone.opponent())
+    }
+
+    /// Replaces the position of `gomoku` with the recorded moves
+    pub fn replay(&self, gomoku: &mut Gomoku) -> Result<GameState, GomokuError> {
+        let alternating = self.moves.windows(2).all(|pair| pair[0].stone != pair[1].stone);
+
+        if !alternating {
+            return Err(GomokuError::InvalidSetup("Moves must alternate between sides"));
+        }
+
+        let moves = self.moves.iter().map(|played| played.position).collect();
+
+        gomoku.set_position(PositionSetup::Moves(moves), self.side_to_move())
+    }
+
+    /// Analyzes every position of the game, from the empty board to the last move
+    pub fn analyze(&self, gomoku: &mut Gomoku, limits: SearchLimits, count: usize) -> Result<Vec<PlyAnalysis>, GomokuError> {
+        (0..=self.moves.len())
+            .map(|ply| {
+                let position = Game {
+                    moves: self.moves[..ply].to_vec(),
+                    ..self.clone()
+                };
+                let candidates = match position.replay(gomoku)? {
+                    GameState::InProgress => gomoku.analyze(limits, count)?,
+                    _ => Vec::new(),
+                };
+
+                Ok(PlyAnalysis {
+                    ply,
+                    side_to_move: position.side_to_move(),
+                    played: self.moves.get(ply).map(|played| played.position),
+                    candidates,
+                    threats: gomoku.board().threats(),
+                })
+            })
+            .collect()
+    }
+
+    /// Scores every move of the game against the engine's best move under the same `limits`
+    ///
+    /// The played move is scored by the same root search as the best one, so both come from the
+    /// same depth.
+    pub fn review(&self, gomoku: &mut Gomoku, limits: SearchLimits) -> Result<Vec<MoveReview>, GomokuError> {
+        let mut reviews = Vec::new();
+
+        for (ply, played) in self.moves.iter().enumerate() {
+            let position = Game {
+                moves: self.moves[..ply].to_vec(),
+                ..self.clone()
+            };
+
+            if position.replay(gomoku)? != GameState::InProgress {
+                break;
+            }
+
+            let (ranked, score) = gomoku.analyze_move(limits, 2, played.position)?;
+            let mut ranked = ranked.into_iter();
+            let best = ranked.next().ok_or(GomokuError::NoMoveAvailable)?;
+            let runner_up = ranked.next().map(|candidate| candidate.score);
+
+            reviews.push(MoveReview {
+                ply,
+                stone: played.stone,
+                played: played.position,
+                score,
+                score_drop: best.score.saturating_sub(score).max(0),
+                best,
+                runner_up,
+            });
+        }
+
+        Ok(reviews)
+    }
+
+    /// Moves numbered by turn, black's first: `1. J10 K11 2. J11`
+    pub fn move_list(&self) -> String {
+        let turns: Vec<String> = self
+            .moves
+            .chunks(2)
+            .enumerate()
+            .map(|(turn, moves)| {
+                let moves: Vec<String> = moves.iter().map(|played| played.position.to_string()).collect();
+
+                format!("{}. {}", turn + 1, moves.join(" "))
+            })
+            .collect();
+
+        turns.join(" ")
+    }
+
+    /// Average time `stone` took per move, over the moves whose time is known
+    pub fn average_time_ms(&self, stone: Stone) -> Option<u64> {
+        let times: Vec<u64> = self
+            .moves
+            .iter()
+            .filter(|played| played.stone == stone)
+            .filter_map(|played| played.time_ms)
+            .collect();
+
+        match times.len() {
+            0 => None,
+            count => Some(times.iter().sum::<u64>() / count as u64),
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+
+        serde_json::to_writer_pretty(writer, self).map_err(io::Error::from)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Game> {
+        let reader = BufReader::new(File::open(path)?);
+
+        serde_json::from_reader(reader).map_err(io::Error::from)
+    }
+}
+
+impl From<&Game> for SgfGame {
+    /// The game on a full board, with its result
+    fn from(game: &Game) -> Self {
+        let mut properties = vec![
+            ("GM".to_string(), "4".to_string()),
+            ("FF".to_string(), "4".to_string()),
+            ("SZ".to_string(), GOBAN_SIZE.to_string()),
+        ];
+        let result = game.result.map(|result| match result {
+            GameResult::BlackWon => (GameState::Won(Player::Opponent), "B+"),
+            GameResult::WhiteWon => (GameState::Won(Player::Computer), "W+"),
+            GameResult::Draw => (GameState::Draw, "0"),
+        });
+
+        if let Some((_, value)) = result {
+            properties.push(("RE".to_string(), value.to_string()));
+        }
+
+        Self {
+            properties,
+            moves: game.moves.iter().map(|played| Move::new(played.stone, played.position)).collect(),
+            move_properties: vec![Vec::new(); game.moves.len()],
+            result: result.map(|(state, _)| state),
+        }
+    }
+}
+
+impl From<SgfGame> for Game {
+    fn from(game: SgfGame) -> Self {
+        Self {
+            rule: Rule::Freestyle,
+            moves: game.moves.into_iter().map(GameMove::from).collect(),
+            result: game.result.and_then(GameResult::from_state),
+            seed: None,
+            clock: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod game_tests {
+    use std::env;
+    use std::time::Duration;
+
+    use crate::clock::{Clock, TimeControl};
+    use crate::game::{Annotation, Game, GameMove, GameResult, BLUNDER_THRESHOLD};
+    use crate::goban::{Player, Position, Stone};
+    use crate::gomoku::{GameState, Gomoku, Rule, SearchLimits};
+    use crate::sgf::{read_sgf, SgfGame};
+
+    #[test]
+    fn it_saves_and_resumes_a_game() {
+        let mut gomoku = Gomoku::with_hash_size(1);
+
+        gomoku.play(Position::new(9, 9), Player::Opponent).unwrap();
+        gomoku.play(Position::new(8, 8), Player::Computer).unwrap();
+        gomoku.play(Position::new(9, 10), Player::Opponent).unwrap();
+
+        let mut game = Game::from_gomoku(&gomoku);
+        let path = env::temp_dir().join(format!("gomoku-game-{}.json", std::process::id()));
+
+        game.moves[0].time_ms = Some(1200);
+        game.clock = Some(Clock::new(TimeControl::new(Duration::from_secs(300))).state());
+        game.save(&path).unwrap();
+
+        let loaded = Game::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, game);
+        assert_eq!(loaded.result, None);
+        assert_eq!(loaded.side_to_move(), Stone::White);
+
+        // SGF keeps the moves without their times
+        let sgf = read_sgf(&SgfGame::from(&game).to_string()).unwrap();
+
+        assert_eq!(sgf.moves, gomoku.history());
+
+        let mut resumed = Gomoku::with_hash_size(1);
+
+        assert_eq!(loaded.replay(&mut resumed), Ok(GameState::InProgress));
+        assert_eq!(resumed.history(), gomoku.history());
+        assert_eq!(resumed.board().side_to_move(), Stone::White);
+    }
+
+    #[test]
+    fn it_lists_the_moves_and_their_average_time() {
+        let mut gomoku = Gomoku::with_hash_size(1);
+
+        gomoku.play(Position::new(9, 9), Player::Opponent).unwrap();
+        gomoku.play(Position::new(8, 10), Player::Computer).unwrap();
+        gomoku.play(Position::new(9, 10), Player::Opponent).unwrap();
+
+        let mut game = Game::from_gomoku(&gomoku);
+
+        game.moves[0].time_ms = Some(1000);
+        game.moves[1].time_ms = Some(300);
+        game.moves[2].time_ms = Some(2000);
+
+        assert_eq!(game.move_list(), "1. J10 K11 2. K10");
+        assert_eq!(game.average_time_ms(Stone::Black), Some(1500));
+        assert_eq!(game.average_time_ms(Stone::White), Some(300));
+        assert_eq!(Game::from_gomoku(&gomoku).average_time_ms(Stone::Black), None);
+    }
+
+    #[test]
+    fn it_flags_the_moves_losing_to_a_better_one() {
+        let game = Game {
+            rule: Rule::Freestyle,
+            moves: "J10 J12 K10 K12 L10 L12 M10 M12 A19 N12"
+                .split_whitespace()
+                .zip([Stone::Black, Stone::White].iter().cycle())
+                .map(|(position, &stone)| GameMove {
+                    stone,
+                    position: Position::from_coordinates(position).unwrap(),
+                    time_ms: None,
+                })
+                .collect(),
+            result: Some(GameResult::WhiteWon),
+            seed: None,
+            clock: None,
+        };
+        let reviews = game.review(&mut Gomoku::with_hash_size(1), SearchLimits::new(2)).unwrap();
+
+        assert_eq!(reviews.len(), 10);
+        // Black played away from its open four instead of the five, letting white win
+        assert_eq!((reviews[8].stone, reviews[8].score, reviews[8].best.score), (Stone::Black, isize::MIN, isize::MAX));
+        assert!(["I10", "N10"].contains(&reviews[8].best.position.to_string().as_str()));
+        assert!(reviews[8].is_blunder(BLUNDER_THRESHOLD));
+        assert_eq!(reviews[8].annotation(BLUNDER_THRESHOLD), Some(Annotation::Blunder));
+        assert_eq!(reviews[8].black_score(), isize::MIN);
+        // Nothing beats the five
+        assert_eq!((reviews[9].score, reviews[9].score_drop), (isize::MAX, 0));
+    }
+
+    #[test]
+    fn it_does_not_flag_a_move_as_good_as_the_best() {
+        let mut gomoku = Gomoku::with_hash_size(1);
+
+        for (ply, position) in "J10 J9 K10 H10 K9 L11".split_whitespace().enumerate() {
+            let player = if ply % 2 == 0 { Player::Opponent } else { Player::Computer };
+
+            gomoku.play(Position::from_coordinates(position).unwrap(), player).unwrap();
+        }
+
+        let ranked = gomoku.analyze(SearchLimits::new(2), 2).unwrap();
+
+        // Black plays the engine's second choice, scored as the first
+        assert_eq!(ranked[0].score, ranked[1].score);
+
+        gomoku.play(ranked[1].position, Player::Opponent).unwrap();
+
+        let reviews = Game::from_gomoku(&gomoku).review(&mut gomoku, SearchLimits::new(2)).unwrap();
+
+        assert_eq!(reviews[6].played, ranked[1].position);
+        assert_eq!((reviews[6].score, reviews[6].score_drop), (reviews[6].best.score, 0));
+        assert_eq!(reviews[6].annotation(BLUNDER_THRESHOLD), None);
+    }
+
+    #[test]
+    fn it_reads_moves_in_letter_notation() {
+        let game: Game = serde_json::from_str(
+            r#"{"rule": "freestyle", "moves": [
+                {"stone": "black", "move": "J10"},
+                {"stone": "black", "move": "K10"}
+            ], "result": "blackWon"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(game.moves[0].position, Position::new(9, 9));
+        assert_eq!(game.result, Some(GameResult::BlackWon));
+        assert!(game.replay(&mut Gomoku::with_hash_size(1)).is_err());
+    }
+
+    #[test]
+    fn it_analyzes_every_position() {
+        let mut gomoku = Gomoku::with_hash_size(1);
+
+        gomoku.play(Position::new(9, 9), Player::Opponent).unwrap();
+        gomoku.play(Position::new(8, 8), Player::Computer).unwrap();
+
+        let game = Game::from_gomoku(&gomoku);
+        let analysis = game.analyze(&mut gomoku, SearchLimits::new(2), 2).unwrap();
+
+        assert_eq!(analysis.len(), 3);
+        assert_eq!(analysis[0].candidates[0].position, Position::new(9, 9));
+        assert_eq!(analysis[1].played, Some(Position::new(8, 8)));
+        assert_eq!(analysis[1].side_to_move, Stone::White);
+        assert_eq!(analysis[2].played, None);
+        assert!(analysis.iter().all(|ply| ply.threats.is_empty()));
+
+        let json = serde_json::to_value(&analysis[1]).unwrap();
+
+        assert_eq!(json["played"], "I11");
+        assert_eq!(json["sideToMove"], "white");
+        assert!(json["candidates"][0]["pv"].is_array());
+    }
+}
@@ -1,14 +1,14 @@
-use std::{fmt, isize};
+use std::fmt;
 use std::cmp::{Eq, PartialEq};
 use std::hash::Hash;
-use std::rc::Rc;
 
 use bitvec::prelude::*;
 use strum::IntoEnumIterator;
 use strum_macros::{EnumIter};
 
 use crate::evaluator::{Eval, Evaluator};
-use crate::zobrist_hashing::{ZobristHasher, ZobristHash};
+use crate::threat_evaluator::{LineThreat, LineThreats, ThreatEvaluator};
+use crate::zobrist_hashing::{hasher, INITIAL_BOARD_HASH, ZobristHash};
 
 pub const GOBAN_SIZE: usize = 19;
 pub const GOBAN_TOTAL_SIZE: usize = GOBAN_SIZE * GOBAN_SIZE;
@@ -23,18 +23,49 @@ pub enum Player {
     Opponent,
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
 pub enum Stone {
     Black,
     White
 }
 
+impl Stone {
+    pub fn opponent(self) -> Stone {
+        match self {
+            Stone::Black => Stone::White,
+            Stone::White => Stone::Black,
+        }
+    }
+}
+
+/// Black is the opponent's stone and white the computer's
+impl From<Stone> for Player {
+    fn from(stone: Stone) -> Self {
+        match stone {
+            Stone::Black => Player::Opponent,
+            Stone::White => Player::Computer,
+        }
+    }
+}
+
+impl From<Player> for Stone {
+    fn from(player: Player) -> Self {
+        match player {
+            Player::Opponent => Stone::Black,
+            Player::Computer => Stone::White,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Goban {
     white_stones: Bitboard,
     black_stones: Bitboard,
-    zobrist_hasher: Rc<ZobristHasher>,
     zobrist_hash: ZobristHash,
+    side_to_move: Stone,
+    /// Threat score of every line, updated with every stone
+    lines: LineThreats,
 }
 
 #[derive(EnumIter, Copy, Clone, Debug)]
@@ -49,7 +80,53 @@ pub enum Direction {
     NorthWest = -((GOBAN_SIZE + 1) as isize) - 1,
 }
 
-#[derive(Clone, PartialEq, Eq, Hash)]
+/// How coordinates are written: column letter (or number) followed by the row number
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoordinateStyle {
+    /// Columns `A` to `S`, `I` included
+    Letters,
+    /// Columns `A` to `T`, skipping `I` like most gomoku GUIs
+    LettersSkipI,
+    /// Columns `1` to `19`, written `col,row`
+    Numeric,
+}
+
+impl CoordinateStyle {
+    pub fn column_label(self, col: usize) -> String {
+        match self {
+            CoordinateStyle::Letters => ((b'A' + col as u8) as char).to_string(),
+            CoordinateStyle::LettersSkipI => {
+                let offset = if col >= 8 { col + 1 } else { col };
+
+                ((b'A' + offset as u8) as char).to_string()
+            }
+            CoordinateStyle::Numeric => (col + 1).to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    Empty,
+    InvalidFormat(String),
+    InvalidColumn(String),
+    InvalidRow(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "Empty position"),
+            ParseError::InvalidFormat(position) => write!(f, "Invalid position `{}`", position),
+            ParseError::InvalidColumn(col) => write!(f, "invalid col value `{}`", col),
+            ParseError::InvalidRow(row) => write!(f, "invalid row value `{}`", row),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Position {
     pub row: usize,
     pub col: usize,
@@ -63,6 +140,42 @@ impl fmt::Debug for Position {
     }
 }
 
+/// Letter columns, `display` writes the other styles
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_notation(CoordinateStyle::Letters))
+    }
+}
+
+/// A position written in a coordinate style, see `Position::display`
+pub struct Notation {
+    position: Position,
+    style: CoordinateStyle,
+}
+
+impl fmt::Display for Notation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.position.to_notation(self.style))
+    }
+}
+
+/// Serialized as its notation with letter columns
+#[cfg(feature = "serde")]
+impl serde::Serialize for Position {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_notation(CoordinateStyle::Letters))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Position {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let notation = String::deserialize(deserializer)?;
+
+        Position::from_coordinates_with(&notation, CoordinateStyle::Letters).map_err(serde::de::Error::custom)
+    }
+}
+
 impl Position {
     pub fn new(row: usize, col: usize) -> Position {
         Position { row, col }
@@ -72,27 +185,117 @@ impl Position {
         (self.row * (GOBAN_SIZE + 1)) + self.col
     }
 
-    pub fn from_coordinates(position: &str) -> Result<Position, String> {
-        if position.len() < 2 {
-            return Err(format!("Invalid position `{}`", position));
+    /// Inverse of `index`, `None` for the padding column and out of board indexes
+    pub fn from_index(index: usize) -> Option<Position> {
+        let row = index / (GOBAN_SIZE + 1);
+        let col = index - (row * (GOBAN_SIZE + 1));
+
+        if col == GOBAN_SIZE || index >= BIT_SIZE {
+            return None;
+        }
+
+        Some(Position::new(row, col))
+    }
+
+    /// Parses `h8`, `8h` or `8,8` style coordinates, reading letter columns from `A` to `S`
+    pub fn from_coordinates(position: &str) -> Result<Position, ParseError> {
+        Self::from_coordinates_with(position, CoordinateStyle::Letters)
+    }
+
+    /// Parses coordinates, reading column letters according to `style`
+    ///
+    /// Numeric `col,row` (or `col row`) input is always accepted, and letter
+    /// coordinates can be given in either order (`h8` or `8h`).
+    pub fn from_coordinates_with(position: &str, style: CoordinateStyle) -> Result<Position, ParseError> {
+        let position = position.trim();
+
+        if position.is_empty() {
+            return Err(ParseError::Empty);
         }
 
-        let (col_str, row_str) = position.split_at(1);
+        if let Some((col_str, row_str)) = position.split_once(|c: char| c == ',' || c.is_whitespace()) {
+            let col_str = col_str.trim();
+            let row_str = row_str.trim();
+
+            if !col_str.chars().chain(row_str.chars()).any(|c| c.is_ascii_alphabetic()) {
+                let col = col_str
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|col| (1..=GOBAN_SIZE).contains(col))
+                    .ok_or_else(|| ParseError::InvalidColumn(col_str.to_string()))?;
+
+                return Ok(Position::new(Self::parse_row(row_str)?, col - 1));
+            }
+        }
+
+        let position: String = position.chars().filter(|c| !c.is_whitespace()).collect();
+        let position = position.as_str();
+
+        let first = position.chars().next().unwrap();
+        let last = position.chars().last().unwrap();
 
-        let ascii_offset: isize = 97;
-        let col: isize = col_str.trim().chars().next().unwrap().to_lowercase().next().unwrap() as isize - ascii_offset;
-        let row: isize = 19 - row_str.trim().parse::<isize>().unwrap_or(-1);
+        let (col_char, row_str) = if first.is_ascii_alphabetic() {
+            (first, &position[1..])
+        } else if last.is_ascii_alphabetic() {
+            (last, &position[..position.len() - 1])
+        } else {
+            return Err(ParseError::InvalidFormat(position.to_string()));
+        };
 
-        if !(0..19).contains(&col) {
-            return Err(format!("invalid col value `{}`", col_str));
+        let col = Self::parse_col(col_char, style)?;
+        let row = Self::parse_row(row_str.trim())?;
+
+        Ok(Position::new(row, col))
+    }
+
+    fn parse_col(col_char: char, style: CoordinateStyle) -> Result<usize, ParseError> {
+        let letter = col_char.to_ascii_lowercase();
+        let invalid = || ParseError::InvalidColumn(col_char.to_string());
+
+        if !letter.is_ascii_lowercase() {
+            return Err(invalid());
         }
 
-        if !(0..19).contains(&row) {
-            return Err(format!("invalid row value `{}`", row_str));
+        let offset = (letter as u8 - b'a') as usize;
+
+        let col = match style {
+            CoordinateStyle::LettersSkipI => match letter {
+                'i' => return Err(invalid()),
+                l if l > 'i' => offset - 1,
+                _ => offset,
+            },
+            CoordinateStyle::Letters | CoordinateStyle::Numeric => offset,
+        };
+
+        if col >= GOBAN_SIZE {
+            return Err(invalid());
         }
 
+        Ok(col)
+    }
 
-        Ok(Position::new(row as usize, col as usize))
+    fn parse_row(row_str: &str) -> Result<usize, ParseError> {
+        row_str
+            .parse::<usize>()
+            .ok()
+            .filter(|row| (1..=GOBAN_SIZE).contains(row))
+            .map(|row| GOBAN_SIZE - row)
+            .ok_or_else(|| ParseError::InvalidRow(row_str.to_string()))
+    }
+
+    /// Writes the position in `style`, as in `format!("{}", position.display(style))`
+    pub fn display(self, style: CoordinateStyle) -> Notation {
+        Notation { position: self, style }
+    }
+
+    pub fn to_notation(&self, style: CoordinateStyle) -> String {
+        let col = style.column_label(self.col);
+        let row = GOBAN_SIZE - self.row;
+
+        match style {
+            CoordinateStyle::Numeric => format!("{},{}", col, row),
+            _ => format!("{}{}", col, row),
+        }
     }
 
     pub fn to_coordinates(&self) -> (char, usize) {
@@ -105,7 +308,45 @@ impl Position {
     }
 }
 
-#[derive(Debug)]
+/// One of the eight rotations and reflections of the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Symmetry(u8);
+
+impl Symmetry {
+    pub const IDENTITY: Symmetry = Symmetry(0);
+
+    pub fn all() -> impl Iterator<Item = Symmetry> {
+        (0..8).map(Symmetry)
+    }
+
+    pub fn apply(self, position: &Position) -> Position {
+        let last = GOBAN_SIZE - 1;
+        let (row, col) = (position.row, position.col);
+
+        let (row, col) = match self.0 {
+            0 => (row, col),
+            1 => (col, last - row),
+            2 => (last - row, last - col),
+            3 => (last - col, row),
+            4 => (row, last - col),
+            5 => (last - row, col),
+            6 => (col, row),
+            _ => (last - col, last - row),
+        };
+
+        Position::new(row, col)
+    }
+
+    pub fn inverse(self) -> Symmetry {
+        match self.0 {
+            1 => Symmetry(3),
+            3 => Symmetry(1),
+            n => Symmetry(n),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Move {
     pub stone: Stone,
     pub position: Position,
@@ -129,13 +370,25 @@ impl PartialEq<Self> for Goban {
 impl Eq for Goban {}
 
 impl fmt::Debug for Goban {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.display(CoordinateStyle::Letters))
+    }
+}
+
+/// The board drawn with its column labels in a coordinate style, see `Goban::display`
+pub struct BoardDisplay<'a> {
+    goban: &'a Goban,
+    style: CoordinateStyle,
+}
+
+impl fmt::Display for BoardDisplay<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         for row in 0..GOBAN_SIZE {
             for col in 0..GOBAN_SIZE {
                 write!(
                     f,
                     "{}",
-                    match self.get(row, col) {
+                    match self.goban.get(row, col) {
                         None => ". ",
                         Some(Stone::Black) => "X ",
                         Some(Stone::White) => "O ",
@@ -145,32 +398,87 @@ impl fmt::Debug for Goban {
             writeln!(f, "{}", 19 - row)?;
         }
 
-        writeln!(f, "A B C D E F G H I J K L M N O P Q R S")
+        // Numeric coordinates would not fit under the columns
+        let style = match self.style {
+            CoordinateStyle::Numeric => CoordinateStyle::Letters,
+            style => style,
+        };
+        let labels: Vec<String> = (0..GOBAN_SIZE).map(|col| style.column_label(col)).collect();
+
+        writeln!(f, "{}", labels.join(" "))
+    }
+}
+
+impl Default for Goban {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl Goban {
-    pub fn new(hasher: Rc<ZobristHasher>) -> Goban {
+    pub fn new() -> Goban {
         Goban {
             black_stones: bitarr![Msb0, u8; 0; BIT_SIZE],
             white_stones: bitarr![Msb0, u8; 0; BIT_SIZE],
-            zobrist_hasher: hasher,
             zobrist_hash: 0,
+            side_to_move: Stone::Black,
+            lines: LineThreats::new(),
         }
     }
 
     pub fn apply_move(&mut self, move_to_play: Move) {
-        let position = move_to_play.position.index();
+        self.clear(&move_to_play.position);
 
-        self.black_stones.set(position, false);
-        self.white_stones.set(position, false);
+        let position = move_to_play.position.index();
 
         match move_to_play.stone {
             Stone::Black => self.black_stones.set(position, true),
             Stone::White => self.white_stones.set(position, true),
         }
 
-        self.zobrist_hash = self.zobrist_hasher.update_hash(self.zobrist_hash, move_to_play);
+        self.lines.update(&self.black_stones, &self.white_stones, position);
+
+        self.zobrist_hash = hasher().update_hash(self.zobrist_hash, move_to_play);
+        self.set_side_to_move(move_to_play.stone.opponent());
+    }
+
+    /// Takes back `played`, the last move applied to an intersection that was empty before it,
+    /// giving the turn back to its stone
+    pub fn undo_move(&mut self, played: Move) {
+        self.clear(&played.position);
+        self.set_side_to_move(played.stone);
+    }
+
+    pub fn side_to_move(&self) -> Stone {
+        self.side_to_move
+    }
+
+    /// Changes whose turn it is, which is part of the hash. Playing a move passes the turn
+    pub fn set_side_to_move(&mut self, stone: Stone) {
+        if stone != self.side_to_move {
+            self.side_to_move = stone;
+            self.zobrist_hash = hasher().toggle_side(self.zobrist_hash);
+        }
+    }
+
+    /// Removes the stone at `position`, if any, and returns it. The side to move is unchanged
+    pub fn clear(&mut self, position: &Position) -> Option<Stone> {
+        let stone = self.get(position.row, position.col)?;
+        let index = position.index();
+
+        self.black_stones.set(index, false);
+        self.white_stones.set(index, false);
+        self.lines.update(&self.black_stones, &self.white_stones, index);
+
+        // xoring the same key again removes the stone from the hash
+        self.zobrist_hash = hasher().update_hash(self.zobrist_hash, Move::new(stone, *position));
+
+        Some(stone)
+    }
+
+    /// Draws the board with its columns labelled in `style`, as `Debug` does with letters
+    pub fn display(&self, style: CoordinateStyle) -> BoardDisplay<'_> {
+        BoardDisplay { goban: self, style }
     }
 
     pub fn get(&self, row: usize, col: usize) -> Option<Stone> {
@@ -187,6 +495,87 @@ impl Goban {
         None
     }
 
+    /// Bitboard of the stones of `stone`, indexed by `Position::index`
+    pub fn stones(&self, stone: Stone) -> &Bitboard {
+        match stone {
+            Stone::Black => &self.black_stones,
+            Stone::White => &self.white_stones,
+        }
+    }
+
+    pub fn count(&self, stone: Stone) -> usize {
+        match stone {
+            Stone::Black => self.black_stones.count_ones(),
+            Stone::White => self.white_stones.count_ones(),
+        }
+    }
+
+    /// Whether every intersection holds a stone
+    pub fn is_full(&self) -> bool {
+        self.count(Stone::Black) + self.count(Stone::White) == GOBAN_SIZE * GOBAN_SIZE
+    }
+
+    /// Whether `stone` has at least five in a row on the board
+    pub fn has_five(&self, stone: Stone) -> bool {
+        let stones = self.stones(stone);
+
+        [Direction::East, Direction::South, Direction::SouthEast, Direction::SouthWest]
+            .iter()
+            .any(|&axis| {
+                let mut line = *stones;
+
+                for step in 1..WIN_MINIMUM_LINE_SIZE {
+                    let mut shifted = *stones;
+                    shifted.shift_right(axis as usize * step);
+                    line &= shifted;
+                }
+
+                line.any()
+            })
+    }
+
+    /// Stones of a line of five or more, from its top or left end, `None` while nobody won
+    pub fn winning_line(&self) -> Option<Vec<Position>> {
+        let size = GOBAN_SIZE as isize;
+        let stone_at = |row: isize, col: isize| match (0..size).contains(&row) && (0..size).contains(&col) {
+            true => self.get(row as usize, col as usize),
+            false => None,
+        };
+
+        for row in 0..size {
+            for col in 0..size {
+                let stone = match stone_at(row, col) {
+                    Some(stone) => stone,
+                    None => continue,
+                };
+
+                for (row_step, col_step) in [(0, 1), (1, 0), (1, 1), (1, -1)] {
+                    // Only lines starting here, the longer ones are found from their end
+                    if stone_at(row - row_step, col - col_step) == Some(stone) {
+                        continue;
+                    }
+
+                    let line: Vec<Position> = (0..)
+                        .map(|step| (row + row_step * step, col + col_step * step))
+                        .take_while(|&(row, col)| stone_at(row, col) == Some(stone))
+                        .map(|(row, col)| Position::new(row as usize, col as usize))
+                        .collect();
+
+                    if line.len() >= WIN_MINIMUM_LINE_SIZE {
+                        return Some(line);
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Threats of both sides, for frontends and analyses annotating the board
+    pub fn threats(&self) -> Vec<LineThreat> {
+        ThreatEvaluator::threats(self)
+    }
+
     pub fn evaluate(&self, evaluator: &mut dyn Evaluator, player: Player) -> Eval {
         match player {
             Player::Computer => evaluator.evaluate(&self.white_stones, &self.black_stones),
@@ -194,9 +583,24 @@ impl Goban {
         }
     }
 
+    /// Threats of `player` less the other side's, summed from the line scores the board keeps up
+    /// to date instead of scanning it
+    pub fn evaluate_lines(&self, player: Player) -> Eval {
+        self.lines.evaluate(player.into())
+    }
+
     pub fn get_limited_moves(&self, steps: usize) -> Vec<Position>
     {
-        let mut positions: Vec<Position> = Vec::new();
+        let mut positions = Vec::new();
+
+        self.extend_limited_moves(steps, &mut positions);
+
+        positions
+    }
+
+    /// Appends the moves of `get_limited_moves` to `positions`, so searches can reuse one vector
+    pub fn extend_limited_moves(&self, steps: usize, positions: &mut Vec<Position>)
+    {
         let mut played_set = self.black_stones | self.white_stones;
         let playable_set = !played_set;
         let mut limited_set = played_set;
@@ -205,23 +609,38 @@ impl Goban {
             for axis in Direction::iter() {
                 limited_set |= Self::dilate(&played_set, axis);
             }
+
+            // Spilling into the padding column, the next step would wrap onto the opposite edge
+            for row in 0..GOBAN_SIZE {
+                limited_set.set(row * (GOBAN_SIZE + 1) + GOBAN_SIZE, false);
+            }
+
             played_set = limited_set;
         }
 
         limited_set &= playable_set;
 
         for index in limited_set.iter_ones() {
-            let row = index / (GOBAN_SIZE + 1);
-            let col = index - (row * (GOBAN_SIZE + 1));
-
-            if col == GOBAN_SIZE || index >= BIT_SIZE {
-                continue;
+            if let Some(position) = Position::from_index(index) {
+                positions.push(position);
             }
-
-            positions.push(Position::new(row, col));
         }
+    }
 
-        positions
+    /// Lists the squares whose content differs from `other`, as `(position, ours, theirs)`
+    pub fn diff(&self, other: &Goban) -> Vec<(Position, Option<Stone>, Option<Stone>)> {
+        let changed = (self.black_stones ^ other.black_stones) | (self.white_stones ^ other.white_stones);
+
+        changed
+            .iter_ones()
+            .filter_map(Position::from_index)
+            .map(|position| {
+                let ours = self.get(position.row, position.col);
+                let theirs = other.get(position.row, position.col);
+
+                (position, ours, theirs)
+            })
+            .collect()
     }
 
     pub fn get_hash(&self) -> ZobristHash
@@ -229,6 +648,30 @@ impl Goban {
         self.zobrist_hash
     }
 
+    /// Smallest hash among the eight symmetries of the board, and the symmetry giving it
+    ///
+    /// Hashes are rebuilt from every stone, meant for sparse positions like openings.
+    pub fn get_canonical_hash(&self) -> (ZobristHash, Symmetry)
+    {
+        let stones = self.black_stones | self.white_stones;
+
+        Symmetry::all()
+            .map(|symmetry| {
+                let hash = stones.iter_ones().filter_map(Position::from_index).fold(INITIAL_BOARD_HASH, |hash, position| {
+                    let stone = self.get(position.row, position.col).unwrap();
+
+                    hasher().update_hash(hash, Move::new(stone, symmetry.apply(&position)))
+                });
+
+                match self.side_to_move {
+                    Stone::Black => (hash, symmetry),
+                    Stone::White => (hasher().toggle_side(hash), symmetry),
+                }
+            })
+            .min_by_key(|(hash, _)| *hash)
+            .unwrap()
+    }
+
     fn dilate(bitboard: &Bitboard, axis: Direction) -> Bitboard {
         let mut rhs = *bitboard;
 
@@ -241,3 +684,183 @@ impl Goban {
         *bitboard | rhs
     }
 }
+
+#[cfg(test)]
+mod goban_tests {
+    use crate::goban::{Goban, Move, Position, Stone, Symmetry};
+
+    #[test]
+    fn it_detects_fives_in_every_direction() {
+        for (row_step, col_step, start_col) in [(0, 1, 14), (1, 0, 0), (1, 1, 0), (1, -1, 4)] {
+            let mut board = Goban::new();
+            let line: Vec<Position> = (0..5)
+                .map(|i| Position::new((row_step * i) as usize, (start_col + col_step * i) as usize))
+                .collect();
+
+            for position in &line {
+                board.apply_move(Move::new(Stone::White, *position));
+            }
+
+            assert!(board.has_five(Stone::White));
+            assert!(!board.has_five(Stone::Black));
+            assert_eq!(board.winning_line(), Some(line));
+
+            board.clear(&Position::new(0, start_col as usize));
+            assert!(!board.has_five(Stone::White));
+            assert_eq!(board.winning_line(), None);
+        }
+
+        let mut board = Goban::new();
+
+        // Rows must not wrap around the board edge
+        for col in 16..19 {
+            board.apply_move(Move::new(Stone::Black, Position::new(0, col)));
+        }
+        for col in 0..2 {
+            board.apply_move(Move::new(Stone::Black, Position::new(1, col)));
+        }
+
+        assert!(!board.has_five(Stone::Black));
+        assert_eq!(board.winning_line(), None);
+        assert_eq!(board.count(Stone::Black), 5);
+    }
+
+    #[test]
+    fn it_hashes_symmetric_positions_alike() {
+        let moves = [(Stone::Black, Position::new(9, 9)), (Stone::White, Position::new(8, 10)), (Stone::Black, Position::new(3, 1))];
+        let mut board = Goban::new();
+
+        for (stone, position) in moves.iter() {
+            board.apply_move(Move::new(*stone, *position));
+        }
+
+        let (canonical, to_canonical) = board.get_canonical_hash();
+
+        for symmetry in Symmetry::all() {
+            let mut transformed = Goban::new();
+
+            for (stone, position) in moves.iter() {
+                transformed.apply_move(Move::new(*stone, symmetry.apply(position)));
+            }
+
+            assert_eq!(transformed.get_canonical_hash().0, canonical);
+            assert_eq!(symmetry.inverse().apply(&symmetry.apply(&Position::new(2, 5))), Position::new(2, 5));
+        }
+
+        let mut canonical_board = Goban::new();
+
+        for (stone, position) in moves.iter() {
+            canonical_board.apply_move(Move::new(*stone, to_canonical.apply(position)));
+        }
+
+        assert_eq!(canonical_board.get_hash(), canonical);
+    }
+
+    #[test]
+    fn it_lists_changed_squares() {
+        let mut before = Goban::new();
+        before.apply_move(Move::new(Stone::Black, Position::new(9, 9)));
+        before.apply_move(Move::new(Stone::White, Position::new(9, 10)));
+
+        let mut after = before.clone();
+        after.apply_move(Move::new(Stone::Black, Position::new(10, 10)));
+        after.apply_move(Move::new(Stone::Black, Position::new(9, 10)));
+
+        assert!(before.diff(&before.clone()).is_empty());
+        assert_eq!(
+            before.diff(&after),
+            vec![
+                (Position::new(9, 10), Some(Stone::White), Some(Stone::Black)),
+                (Position::new(10, 10), None, Some(Stone::Black)),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_takes_back_moves() {
+        let mut board = Goban::new();
+        board.apply_move(Move::new(Stone::Black, Position::new(9, 9)));
+
+        let before = board.clone();
+        let played = [Move::new(Stone::White, Position::new(9, 10)), Move::new(Stone::Black, Position::new(10, 10))];
+
+        for played in played.iter() {
+            board.apply_move(*played);
+        }
+        for played in played.iter().rev() {
+            board.undo_move(*played);
+        }
+
+        assert!(board == before);
+        assert_eq!(board.get_hash(), before.get_hash());
+        assert_eq!(board.side_to_move(), Stone::White);
+    }
+}
+
+#[cfg(test)]
+mod position_tests {
+    use crate::goban::{CoordinateStyle, Goban, ParseError, Position};
+
+    #[test]
+    fn it_parses_letter_coordinates_in_both_orders() {
+        assert_eq!(Position::from_coordinates("a19"), Ok(Position::new(0, 0)));
+        assert_eq!(Position::from_coordinates("J10"), Ok(Position::new(9, 9)));
+        assert_eq!(Position::from_coordinates("10j"), Ok(Position::new(9, 9)));
+        assert_eq!(Position::from_coordinates("s 1"), Ok(Position::new(18, 18)));
+    }
+
+    #[test]
+    fn it_parses_numeric_coordinates() {
+        assert_eq!(Position::from_coordinates("10,10"), Ok(Position::new(9, 9)));
+        assert_eq!(Position::from_coordinates("1 19"), Ok(Position::new(0, 0)));
+        assert_eq!(Position::from_coordinates("19, 1"), Ok(Position::new(18, 18)));
+    }
+
+    #[test]
+    fn it_parses_coordinates_skipping_i() {
+        let style = CoordinateStyle::LettersSkipI;
+
+        assert_eq!(Position::from_coordinates_with("h10", style), Ok(Position::new(9, 7)));
+        assert_eq!(Position::from_coordinates_with("j10", style), Ok(Position::new(9, 8)));
+        assert_eq!(Position::from_coordinates_with("t1", style), Ok(Position::new(18, 18)));
+        assert_eq!(
+            Position::from_coordinates_with("i10", style),
+            Err(ParseError::InvalidColumn("i".to_string()))
+        );
+    }
+
+    #[test]
+    fn it_formats_coordinates_in_every_style() {
+        let position = Position::new(9, 10);
+
+        assert_eq!(position.to_notation(CoordinateStyle::Letters), "K10");
+        assert_eq!(position.to_notation(CoordinateStyle::LettersSkipI), "L10");
+        assert_eq!(position.to_notation(CoordinateStyle::Numeric), "11,10");
+
+        for style in [CoordinateStyle::Letters, CoordinateStyle::LettersSkipI, CoordinateStyle::Numeric] {
+            assert_eq!(Position::from_coordinates_with(&position.to_notation(style), style), Ok(position));
+            assert_eq!(position.display(style).to_string(), position.to_notation(style));
+        }
+
+        assert_eq!(position.to_string(), "K10");
+    }
+
+    #[test]
+    fn it_labels_the_board_in_the_style_asked() {
+        let goban = Goban::new();
+        let skipping_i = goban.display(CoordinateStyle::LettersSkipI).to_string();
+
+        assert!(skipping_i.ends_with("H J K L M N O P Q R S T\n"));
+        assert!(format!("{:?}", goban).ends_with("H I J K L M N O P Q R S\n"));
+        assert_eq!(goban.display(CoordinateStyle::Numeric).to_string(), format!("{:?}", goban));
+    }
+
+    #[test]
+    fn it_rejects_invalid_coordinates() {
+        assert_eq!(Position::from_coordinates(""), Err(ParseError::Empty));
+        assert_eq!(Position::from_coordinates("t1"), Err(ParseError::InvalidColumn("t".to_string())));
+        assert_eq!(Position::from_coordinates("a20"), Err(ParseError::InvalidRow("20".to_string())));
+        assert_eq!(Position::from_coordinates("0,5"), Err(ParseError::InvalidColumn("0".to_string())));
+        assert_eq!(Position::from_coordinates("42"), Err(ParseError::InvalidFormat("42".to_string())));
+    }
+}
@@ -1,284 +1,2020 @@
-use std::cmp::{max, min, Ordering};
-use std::collections::{BinaryHeap, HashMap};
-use std::isize;
-use std::rc::Rc;
+use std::cmp::{max, min, Reverse};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::mem;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::book::OpeningBook;
+use crate::error::{GomokuError, InvalidMoveReason};
+use crate::eval_cache::{self, EvalCache};
 use crate::evaluator::Eval;
-use crate::goban::{Goban, GOBAN_SIZE, Move, Player, Position, Stone};
-use crate::threat_evaluator::ThreatEvaluator;
-use crate::transposition_table::TranspositionTable;
-use crate::zobrist_hashing::ZobristHasher;
+use crate::goban::{Goban, GOBAN_SIZE, Move, ParseError, Player, Position, Stone, Symmetry};
+use crate::search_tree::{Cutoff, SearchTree};
+use crate::transposition_table::{Bound, DEFAULT_SIZE_MB, ReplacementPolicy, TranspositionTable};
+use crate::zobrist_hashing::{self, hasher, ZobristHash};
 
 const BRANCHING_FACTOR_THRESHOLD: usize = 10;
 
+// Candidate moves lie within this many intersections of a stone
+const CANDIDATE_RADIUS: usize = 2;
+
+pub const DEFAULT_SEARCH_DEPTH: usize = 4;
+
+// Up to this many stones, positions share table entries with their rotations and reflections
+const CANONICAL_HASH_PLIES: usize = 6;
+
+/// Winning condition of a game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
+pub enum Rule {
+    /// Five or more stones in a row win
+    Freestyle,
+}
+
+/// What the caches keep from one game to the next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheRetention {
+    /// Start from empty caches, as a freshly created engine
+    Clear,
+    /// Keep everything, search entries of the previous game being the first evicted
+    Age,
+    /// Keep everything as is
+    Retain,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GameState {
     InProgress,
     Won(Player),
+    /// The board is full and nobody completed a five
+    Draw,
 }
 
-pub struct Gomoku {
-    goban: Goban,
-    evaluator: ThreatEvaluator,
-    transposition_table: TranspositionTable,
-    visited_nodes: usize,
-    evaluated_nodes: usize,
-    evaluated_nodes_hit: usize,
-    evaluated_nodes_miss: usize,
+/// Receives the engine diagnostics, nothing is reported when none is set
+pub type Logger = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Progress of a running search
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchEvent {
+    /// A root move beat the best move of the running iteration
+    NewBestMove { depth: usize, position: Position, score: isize },
+    /// An iteration searched every root move
+    Iteration(SearchInfo),
+}
+
+/// State of the search once an iteration completed, scores from the side to move's point of view
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchInfo {
+    pub depth: usize,
+    pub best_move: Position,
+    pub score: isize,
+    pub pv: Vec<Position>,
+    /// Moves of the side to move until the five the PV ends with, negative when the opponent makes it
+    pub mate: Option<isize>,
+    pub nodes: usize,
+    /// Performance of the search so far
+    pub metrics: SearchMetrics,
+}
+
+/// Nodes and time of a completed iteration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename_all = "camelCase"))]
+pub struct IterationMetrics {
+    pub depth: usize,
+    /// Nodes visited by this iteration alone
+    pub nodes: usize,
+    pub time_ms: u64,
 }
 
-#[derive(Eq, PartialEq)]
-pub struct NodeScore {
-    node: Goban,
-    position: Position,
-    score: isize,
+/// Performance of a search, to compare builds and tune the limits
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename_all = "camelCase"))]
+pub struct SearchMetrics {
+    pub time_ms: u64,
+    pub nps: usize,
+    /// Growth of the nodes per ply between the last two iterations, `None` before the second one
+    pub branching_factor: Option<f64>,
+    /// Permille of the transposition table filled by this search
+    pub hashfull: usize,
+    /// Lookups of the transposition table, and those finding an entry deep enough to use
+    pub tt_probes: usize,
+    pub tt_hits: usize,
+    pub iterations: Vec<IterationMetrics>,
 }
 
-impl Ord for NodeScore {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.score.cmp(&other.score)
+impl SearchMetrics {
+    /// Share of the transposition table lookups finding a usable entry, from 0 to 1
+    pub fn tt_hit_rate(&self) -> f64 {
+        self.tt_hits as f64 / self.tt_probes.max(1) as f64
     }
 }
 
-impl PartialOrd for NodeScore {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+impl fmt::Display for SearchMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ms, {} nps", self.time_ms, self.nps)?;
+
+        if let Some(branching_factor) = self.branching_factor {
+            write!(f, ", branching factor {:.2}", branching_factor)?;
+        }
+
+        write!(f, ", hash {}‰ full, {:.1}% hits", self.hashfull, 100.0 * self.tt_hit_rate())?;
+
+        for iteration in &self.iterations {
+            write!(f, ", depth {} {} nodes {} ms", iteration.depth, iteration.nodes, iteration.time_ms)?;
+        }
+
+        Ok(())
     }
 }
 
-impl NodeScore {
-    pub fn new(node: Goban, position: Position, score: isize) -> Self {
+/// Receives the progress of every search
+pub type SearchObserver = Arc<dyn Fn(&SearchEvent) + Send + Sync>;
+
+/// When a search ends, `depth` being the only mandatory limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchLimits {
+    /// Deepest iteration, in plies
+    pub depth: usize,
+    /// Nodes visited after which the search stops
+    pub nodes: Option<usize>,
+    /// Time after which the search stops
+    pub movetime: Option<Duration>,
+    /// Moves of the side to move within which to look for a win, capping the depth at twice as many
+    /// plies. The search still returns its best move when it finds none, a proven win scoring
+    /// `isize::MAX`.
+    pub mate_in: Option<usize>,
+}
+
+impl SearchLimits {
+    pub fn new(depth: usize) -> Self {
         Self {
-            node,
-            position,
-            score,
+            depth,
+            nodes: None,
+            movetime: None,
+            mate_in: None,
         }
     }
+
+    pub fn with_nodes(mut self, nodes: usize) -> Self {
+        self.nodes = Some(nodes);
+        self
+    }
+
+    pub fn with_movetime(mut self, movetime: Duration) -> Self {
+        self.movetime = Some(movetime);
+        self
+    }
+
+    pub fn with_mate_in(mut self, moves: usize) -> Self {
+        self.mate_in = Some(moves);
+        self
+    }
+}
+
+/// Outcome of a search, with the statistics gathered while running it
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename_all = "camelCase"))]
+pub struct SearchReport {
+    pub best_move: Position,
+    pub score: isize,
+    /// Expected continuation, starting with the best move
+    pub pv: Vec<Position>,
+    /// Last depth searched, complete unless the limits cut its first iteration short
+    pub depth: usize,
+    pub static_eval: Eval,
+    pub visited_nodes: usize,
+    pub evaluated_nodes: usize,
+    pub eval_cache_hits: usize,
+    pub eval_cache_misses: usize,
+    pub metrics: SearchMetrics,
+}
+
+/// Root moves with their score from the side to move's point of view, best first
+type RankedMoves = Vec<(Position, isize)>;
+
+/// A root move with its score from the side to move's point of view and its principal variation
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CandidateMove {
+    #[cfg_attr(feature = "serde", serde(rename = "move"))]
+    pub position: Position,
+    pub score: isize,
+    pub pv: Vec<Position>,
+}
+
+/// A position handed to `Gomoku::set_position`
+#[derive(Debug, Clone, PartialEq)]
+pub enum PositionSetup {
+    /// Stones as they lie, without any history
+    Board(Box<Goban>),
+    /// Moves played in order by alternating sides
+    Moves(Vec<Position>),
+}
+
+impl FromStr for PositionSetup {
+    type Err = ParseError;
+
+    /// Parses a whitespace separated move list such as `j10 k11 h9`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split_whitespace()
+            .map(Position::from_coordinates)
+            .collect::<Result<Vec<_>, _>>()
+            .map(PositionSetup::Moves)
+    }
+}
+
+/// Clones share the transposition table and the evaluation cache with the original
+///
+/// Both are lockless and keyed by position alone, so a fork exploring another line only
+/// warms them for the live game. Board, history and statistics are copied.
+#[derive(Clone)]
+pub struct Gomoku {
+    goban: Goban,
+    history: Vec<Move>,
+    logger: Option<Logger>,
+    observer: Option<SearchObserver>,
+    transposition_table: Arc<TranspositionTable>,
+    eval_cache: Arc<EvalCache>,
+    /// Book played from before searching, with the randomness among its moves
+    book: Option<(Arc<OpeningBook>, f64)>,
+    /// Records the tree of every search when set
+    tree: Option<SearchTree>,
+    /// Seed of every random choice, see `rng`
+    seed: u64,
+    search_depth: usize,
+    stop: Option<Arc<AtomicBool>>,
+    node_limit: Option<usize>,
+    deadline: Option<Instant>,
+    visited_nodes: usize,
+    evaluated_nodes: usize,
+    evaluated_nodes_hit: usize,
+    evaluated_nodes_miss: usize,
+    tt_probes: usize,
+    tt_hits: usize,
+    /// Iterations completed by the running search
+    iterations: Vec<IterationMetrics>,
+    /// When the running search started
+    started: Instant,
+    /// Candidate moves of the node being expanded, kept to reuse its allocation
+    candidates: Vec<Position>,
+    /// Children vectors of searched nodes, reused by the next nodes expanded
+    spare_children: Vec<Vec<(Position, isize)>>,
 }
 
 impl Default for Gomoku {
     fn default() -> Self {
-        let hasher = Rc::new(ZobristHasher::initialize());
+        GomokuBuilder::new().create()
+    }
+}
 
+/// Engine configuration, `build` checks it and creates the engine
+#[derive(Debug, Clone)]
+pub struct GomokuBuilder {
+    hash_size_mb: usize,
+    replacement_policy: ReplacementPolicy,
+    eval_cache_size_mb: usize,
+    search_depth: usize,
+    seed: Option<u64>,
+    zobrist_seed: Option<u64>,
+}
+
+impl Default for GomokuBuilder {
+    fn default() -> Self {
         Self {
-            goban: Goban::new(hasher),
-            evaluator: ThreatEvaluator::new(),
-            transposition_table: TranspositionTable::new(),
+            hash_size_mb: DEFAULT_SIZE_MB,
+            replacement_policy: ReplacementPolicy::TwoTier,
+            eval_cache_size_mb: eval_cache::DEFAULT_SIZE_MB,
+            search_depth: DEFAULT_SEARCH_DEPTH,
+            seed: None,
+            zobrist_seed: None,
+        }
+    }
+}
+
+impl GomokuBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Megabytes used by the transposition table
+    pub fn hash_size_mb(mut self, size_mb: usize) -> Self {
+        self.hash_size_mb = size_mb;
+        self
+    }
+
+    pub fn replacement_policy(mut self, policy: ReplacementPolicy) -> Self {
+        self.replacement_policy = policy;
+        self
+    }
+
+    /// Megabytes used by the static evaluation cache
+    pub fn eval_cache_size_mb(mut self, size_mb: usize) -> Self {
+        self.eval_cache_size_mb = size_mb;
+        self
+    }
+
+    /// Depth searched when the caller does not pick one
+    pub fn search_depth(mut self, depth: usize) -> Self {
+        self.search_depth = depth;
+        self
+    }
+
+    /// Seed of the engine's random choices, drawn at random when not given
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Seed of the zobrist keys, shared by every engine of the process, so building fails with
+    /// `AlreadySeeded` once a board was hashed
+    pub fn zobrist_seed(mut self, seed: u64) -> Self {
+        self.zobrist_seed = Some(seed);
+        self
+    }
+
+    pub fn build(self) -> Result<Gomoku, GomokuError> {
+        Gomoku::check_depth(self.search_depth)?;
+
+        if let Some(seed) = self.zobrist_seed {
+            zobrist_hashing::seed(seed).map_err(|_| GomokuError::AlreadySeeded)?;
+        }
+
+        Ok(self.create())
+    }
+
+    fn create(self) -> Gomoku {
+        Gomoku {
+            goban: Goban::new(),
+            history: Vec::new(),
+            logger: None,
+            observer: None,
+            transposition_table: Arc::new(TranspositionTable::with_size_mb(self.hash_size_mb, self.replacement_policy)),
+            eval_cache: Arc::new(EvalCache::with_size_mb(self.eval_cache_size_mb)),
+            book: None,
+            tree: None,
+            seed: self.seed.unwrap_or_else(rand::random),
+            search_depth: self.search_depth,
+            stop: None,
+            node_limit: None,
+            deadline: None,
             visited_nodes: 0,
             evaluated_nodes: 0,
             evaluated_nodes_hit: 0,
             evaluated_nodes_miss: 0,
+            tt_probes: 0,
+            tt_hits: 0,
+            iterations: Vec::new(),
+            started: Instant::now(),
+            candidates: Vec::new(),
+            spare_children: Vec::new(),
         }
     }
 }
 
 impl Gomoku {
-    pub fn print_board(&self) {
-        println!("{:?}", self.goban);
+    /// Creates an engine whose transposition table uses at most `size_mb` megabytes
+    pub fn with_hash_size(size_mb: usize) -> Self {
+        GomokuBuilder::new().hash_size_mb(size_mb).create()
+    }
+
+    /// Depth searched when the caller does not pick one
+    pub fn search_depth(&self) -> usize {
+        self.search_depth
     }
 
-    pub fn play(&mut self, position: Position, player: Player) -> Result<GameState, &str> {
-        if position.row >= GOBAN_SIZE
-            || position.col >= GOBAN_SIZE
-            || self.goban.get(position.row, position.col) != None
-        {
-            return Err("Invalid move");
+    /// Empties the board for a new game, keeping the caches according to `retention`
+    pub fn new_game(&mut self, retention: CacheRetention) {
+        self.goban = Goban::new();
+        self.history.clear();
+
+        match retention {
+            CacheRetention::Clear => {
+                self.transposition_table.clear();
+                self.eval_cache.clear();
+            }
+            CacheRetention::Age => self.transposition_table.new_search(),
+            CacheRetention::Retain => {}
         }
+    }
 
-        let stone = match player {
-            Player::Opponent => Stone::Black,
-            Player::Computer => Stone::White,
-        };
+    /// Starts a new game from the empty board, the caches stay warm
+    pub fn reset(&mut self) {
+        self.new_game(CacheRetention::Age);
+    }
 
-        self.goban.apply_move(Move::new(stone, position));
+    /// Starts editing the position, the current game is untouched until `PositionEditor::finish`
+    pub fn edit_position(&mut self) -> PositionEditor<'_> {
+        let goban = self.goban.clone();
 
-        Ok(self.game_state())
+        PositionEditor { gomoku: self, goban }
     }
 
-    pub fn play_computer_move(&mut self, depth: usize) -> GameState {
-        if depth < 2 {
-            panic!("depth search cannot be less than 2")
-        }
+    /// Saves the transposition table so a later session can resume with it
+    pub fn save_transposition_table<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
 
-        if depth % 2 != 0 {
-            panic!("depth search cannot be odd")
-        }
+        self.transposition_table.save(&mut writer, hasher().fingerprint())
+    }
 
-        let computer_eval = self.eval_current(Player::Computer);
+    /// Loads a table saved with `save_transposition_table` into the current one
+    pub fn load_transposition_table<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        let mut reader = BufReader::new(File::open(path)?);
 
-        println!("computer score: {:?}", computer_eval);
+        self.transposition_table.load(&mut reader, hasher().fingerprint())
+    }
 
-        let mut moves = HashMap::new();
+    /// Seed of the engine's random choices, to reproduce them with `GomokuBuilder::seed`
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
 
-        self.visited_nodes = 0;
-        self.evaluated_nodes = 0;
-        self.evaluated_nodes_hit = 0;
-        self.evaluated_nodes_miss = 0;
+    /// Random numbers for the choices in the current position, such as among book moves
+    ///
+    /// They only depend on the seed and the position, so replaying a game to a position with the same
+    /// seed makes the same choices whatever happened before.
+    pub fn rng(&self) -> StdRng {
+        let hash = self.goban.get_hash();
 
-        for child in self.get_child_nodes(&self.goban.clone(), Player::Computer) {
-            let score = self.minimax(&child.node, depth - 1, isize::MIN, isize::MAX, false);
+        StdRng::seed_from_u64(self.seed ^ hash as u64 ^ (hash >> 64) as u64)
+    }
 
-            moves.insert(child.position, score);
-        }
+    /// Sends the engine diagnostics to `logger` instead of discarding them
+    pub fn set_logger<F: Fn(&str) + Send + Sync + 'static>(&mut self, logger: F) {
+        self.logger = Some(Arc::new(logger));
+    }
 
-        // println!("{:?}", moves);
+    /// Plays the moves of `book` without searching while the game is in it
+    ///
+    /// `randomness` goes from 0, always the book move of the highest weight, to 1, each as often as its weight.
+    pub fn set_book(&mut self, book: Arc<OpeningBook>, randomness: f64) {
+        self.book = Some((book, randomness));
+    }
 
-        if let Some(move_to_play) = Self::get_best_move(moves) {
-            println!("move to play: {:?}", move_to_play);
+    /// Searches every position again
+    pub fn clear_book(&mut self) {
+        self.book = None;
+    }
 
-            let state = match self.play(move_to_play, Player::Computer) {
-                Ok(state) => state,
-                Err(_) => panic!("Invalid move found"),
-            };
+    pub fn book(&self) -> Option<&OpeningBook> {
+        self.book.as_ref().map(|(book, _)| book.as_ref())
+    }
 
-            println!("visited {} nodes", self.visited_nodes);
-            println!("evaluated {} nodes (cache hit {}, cache miss: {})", self.evaluated_nodes, self.evaluated_nodes_hit, self.evaluated_nodes_miss);
+    /// Counts the game played so far in the book, `winner` being `None` for a draw, so the engine
+    /// stops walking into the openings it lost
+    ///
+    /// Engines sharing the book get their own copy.
+    pub fn learn_book(&mut self, winner: Option<Stone>) {
+        let moves: Vec<Position> = self.history.iter().map(|played| played.position).collect();
 
-            state
-        } else {
-            panic!("No move found");
+        if let Some((book, _)) = &mut self.book {
+            Arc::make_mut(book).learn(&moves, winner);
         }
     }
 
-    fn get_best_move(moves: HashMap<Position, isize>) -> Option<Position> {
-        moves
-            .into_iter()
-            .max_by(|a, b| a.1.cmp(&b.1))
-            .map(|(k, _v)| k)
+    /// Records the tree explored by the last iteration of every search, up to `limit` nodes,
+    /// to see why the engine rejected a move
+    pub fn record_search_tree(&mut self, limit: usize) {
+        self.tree = Some(SearchTree::new(limit));
     }
 
-    fn get_child_nodes(&mut self, node: &Goban, player: Player) -> Vec<NodeScore> {
-        let mut child_nodes: BinaryHeap<NodeScore> = BinaryHeap::new();
-
-        for position in node.get_limited_moves(2) {
-            let mut child = node.clone();
-
-            let stone = match player {
-                Player::Computer => Stone::White,
-                Player::Opponent => Stone::Black,
-            };
+    pub fn stop_recording_search_tree(&mut self) {
+        self.tree = None;
+    }
 
-            child.apply_move(Move::new(stone, position.clone()));
+    /// Tree of the last search, `None` unless recording
+    pub fn search_tree(&self) -> Option<&SearchTree> {
+        self.tree.as_ref()
+    }
 
-            // We should use a custom evaluation function for this
-            // With this solution we will miss winning / losing nodes
-            // one idea: include only move that create threat or block some
-            let eval = self.eval(&child, player);
+    fn record<F: FnOnce(&mut SearchTree)>(&mut self, f: F) {
+        if let Some(tree) = &mut self.tree {
+            f(tree);
+        }
+    }
 
-            let score_eval = match eval {
-                Eval::Won => isize::MAX,
-                Eval::Lost => isize::MIN,
-                Eval::Score(n) => n,
-            };
+    /// Sends the progress of every search to `observer`
+    pub fn set_search_observer<F: Fn(&SearchEvent) + Send + Sync + 'static>(&mut self, observer: F) {
+        self.observer = Some(Arc::new(observer));
+    }
 
-            child_nodes.push(NodeScore::new(child, position, score_eval));
+    fn notify<F: FnOnce() -> SearchEvent>(&self, event: F) {
+        if let Some(observer) = &self.observer {
+            observer(&event());
         }
+    }
 
-        child_nodes
-            .into_iter_sorted()
-            .take(BRANCHING_FACTOR_THRESHOLD)
-            .collect()
+    /// Searches end early once `flag` is raised, keeping what they found so far
+    pub fn set_stop_flag(&mut self, flag: Arc<AtomicBool>) {
+        self.stop = Some(flag);
     }
 
-    pub fn minimax(
-        &mut self,
-        node: &Goban,
-        depth: usize,
-        mut alpha: isize,
-        mut beta: isize,
-        maximizing: bool,
-    ) -> isize {
-        let side = match maximizing {
-            true => Player::Computer,
-            false => Player::Opponent,
-        };
+    fn stopped(&self) -> bool {
+        self.stop.as_ref().is_some_and(|flag| flag.load(AtomicOrdering::Relaxed))
+            || self.node_limit.is_some_and(|limit| self.visited_nodes >= limit)
+            || self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
 
-        self.visited_nodes += 1;
+    fn log<F: FnOnce() -> String>(&self, message: F) {
+        if let Some(logger) = &self.logger {
+            logger(&message());
+        }
+    }
 
-        match self.eval(node, side) {
-            Eval::Won => return if maximizing { isize::MAX } else { isize::MIN },
-            Eval::Lost => return if maximizing { isize::MIN } else { isize::MAX },
-            Eval::Score(n) if depth == 0 => return n as isize * if maximizing { 1 } else { -1 },
-            _ => {}
-        };
+    /// Replaces the game with `setup`, leaving `side_to_move` to play
+    ///
+    /// A move list is played by alternating sides, starting with whichever side makes
+    /// its last move hand the turn to `side_to_move`.
+    pub fn set_position(&mut self, setup: PositionSetup, side_to_move: Stone) -> Result<GameState, GomokuError> {
+        let (mut goban, history) = match setup {
+            PositionSetup::Board(goban) => {
+                if goban.has_five(Stone::Black) && goban.has_five(Stone::White) {
+                    return Err(GomokuError::InvalidSetup("Both sides cannot have a five"));
+                }
 
-        let mut best;
+                (*goban, Vec::new())
+            }
+            PositionSetup::Moves(moves) => {
+                let mut goban = Goban::new();
+                let mut history = Vec::with_capacity(moves.len());
+                let mut stone = match moves.len() % 2 {
+                    0 => side_to_move,
+                    _ => side_to_move.opponent(),
+                };
 
-        if maximizing {
-            best = isize::MIN;
+                for position in moves {
+                    if goban.has_five(Stone::Black) || goban.has_five(Stone::White) {
+                        return Err(GomokuError::GameOver);
+                    }
 
-            for child in self.get_child_nodes(node, Player::Computer) {
-                best = max(
-                    best,
-                    self.minimax(&child.node, depth - 1, alpha, beta, false),
-                );
+                    Self::check_move(&goban, position)?;
 
-                if best >= beta {
-                    break;
+                    goban.apply_move(Move::new(stone, position));
+                    history.push(Move::new(stone, position));
+                    stone = stone.opponent();
                 }
 
-                alpha = max(alpha, best);
+                (goban, history)
             }
+        };
+
+        goban.set_side_to_move(side_to_move);
+
+        self.goban = goban;
+        self.history = history;
+
+        Ok(self.game_state())
+    }
+
+    /// Current position, for frontends rendering the board themselves
+    pub fn board(&self) -> &Goban {
+        &self.goban
+    }
+
+    /// Moves played since the game started or its position was set
+    pub fn history(&self) -> &[Move] {
+        &self.history
+    }
+
+    /// Most recent move of the history
+    pub fn last_move(&self) -> Option<Move> {
+        self.history.last().copied()
+    }
+
+    pub fn game_state(&self) -> GameState {
+        if self.goban.has_five(Stone::Black) {
+            GameState::Won(Player::Opponent)
+        } else if self.goban.has_five(Stone::White) {
+            GameState::Won(Player::Computer)
+        } else if self.goban.is_full() {
+            GameState::Draw
         } else {
-            best = isize::MAX;
+            GameState::InProgress
+        }
+    }
 
-            for child in self.get_child_nodes(node, Player::Opponent) {
-                best = min(
-                    best,
-                    self.minimax(&child.node, depth - 1, alpha, beta, true),
-                );
+    pub fn play(&mut self, position: Position, player: Player) -> Result<GameState, GomokuError> {
+        self.check_turn(player)?;
 
-                if best <= alpha {
-                    break;
-                }
+        Self::check_move(&self.goban, position)?;
 
-                beta = min(beta, best);
-            }
+        let played = Move::new(Self::stone_of(player), position);
+
+        self.goban.apply_move(played);
+        self.history.push(played);
+
+        Ok(self.game_state())
+    }
+
+    /// Every move the side to move may play, none once the game is over
+    ///
+    /// Unlike the candidates the search considers, this covers the whole board.
+    pub fn legal_moves(&self) -> Vec<Position> {
+        if self.check_turn(Self::player_of(self.goban.side_to_move())).is_err() {
+            return Vec::new();
         }
 
-        best
+        (0..GOBAN_SIZE)
+            .flat_map(|row| (0..GOBAN_SIZE).map(move |col| Position::new(row, col)))
+            .filter(|position| Self::check_move(&self.goban, *position).is_ok())
+            .collect()
     }
 
-    fn game_state(&mut self) -> GameState {
-        let eval = self.eval_current(Player::Computer);
+    /// Whether the side to move may play `position`
+    pub fn is_legal(&self, position: Position) -> bool {
+        self.check_turn(Self::player_of(self.goban.side_to_move())).is_ok()
+            && Self::check_move(&self.goban, position).is_ok()
+    }
 
-        match eval {
-            Eval::Won => GameState::Won(Player::Computer),
-            Eval::Lost => GameState::Won(Player::Opponent),
-            Eval::Score(_) => GameState::InProgress
+    /// Moves the search considers for the side to move, around the stones, empty once the game is over
+    pub fn candidates(&self) -> Vec<Position> {
+        match self.game_state() {
+            GameState::InProgress => Self::candidate_moves(&self.goban),
+            _ => Vec::new(),
         }
     }
 
-    fn eval(&mut self, goban: &Goban, player: Player) -> Eval
-    {
-        // self.transposition_table
-        //     .entry((goban.get_hash(), player))
-        //     .or_insert_with(|| goban.evaluate(&mut self.evaluator, player))
-        //     .to_owned()
+    /// Counts the positions `depth` plies below this one through every candidate move the search
+    /// generates, before its ordering truncates them, to check changes to the move generation
+    /// against known counts
+    ///
+    /// Positions won by five are not expanded, and on an empty board the centre is the only
+    /// candidate as in the search.
+    pub fn perft(&self, depth: usize) -> u64 {
+        match depth {
+            0 => 1,
+            _ => self.perft_divide(depth).iter().map(|(_, nodes)| nodes).sum(),
+        }
+    }
 
-        self.evaluated_nodes += 1;
+    /// Count of `perft` below each candidate move of the position
+    pub fn perft_divide(&self, depth: usize) -> Vec<(Position, u64)> {
+        if depth == 0 || self.game_state() != GameState::InProgress {
+            return Vec::new();
+        }
 
-        let key = (goban.get_hash(), player);
-        let entry = self.transposition_table.get(&key);
+        let mut node = self.goban.clone();
 
-        match entry {
-            Some(eval) => {
-                self.evaluated_nodes_hit += 1;
-                eval.to_owned()
-            },
-            None => {
-                self.evaluated_nodes_miss += 1;
+        Self::candidate_moves(&self.goban)
+            .into_iter()
+            .map(|position| (position, Self::perft_node(&mut node, position, depth - 1)))
+            .collect()
+    }
 
-                let eval = goban.evaluate(&mut self.evaluator, player);
+    fn candidate_moves(goban: &Goban) -> Vec<Position> {
+        match goban.count(Stone::Black) + goban.count(Stone::White) {
+            0 => vec![Position::new(GOBAN_SIZE / 2, GOBAN_SIZE / 2)],
+            _ => goban.get_limited_moves(CANDIDATE_RADIUS),
+        }
+    }
 
-                self.transposition_table.insert(key, eval.clone());
+    fn perft_node(node: &mut Goban, position: Position, depth: usize) -> u64 {
+        let stone = node.side_to_move();
+        let played = Move::new(stone, position);
 
-                eval
-            }
+        if depth == 0 {
+            return 1;
         }
+
+        node.apply_move(played);
+
+        let nodes = match node.has_five(stone) || node.is_full() {
+            true => 0,
+            false => Self::candidate_moves(node)
+                .into_iter()
+                .map(|position| Self::perft_node(node, position, depth - 1))
+                .sum(),
+        };
+
+        node.undo_move(played);
+
+        nodes
     }
 
-    fn eval_current(&mut self, player: Player) -> Eval
-    {
-        self.eval(&self.goban.clone(), player)
+    fn check_move(goban: &Goban, position: Position) -> Result<(), GomokuError> {
+        if position.row >= GOBAN_SIZE || position.col >= GOBAN_SIZE {
+            return Err(GomokuError::InvalidMove { reason: InvalidMoveReason::OutOfBounds, position });
+        }
+
+        if goban.get(position.row, position.col).is_some() {
+            return Err(GomokuError::InvalidMove { reason: InvalidMoveReason::Occupied, position });
+        }
+
+        Ok(())
+    }
+
+    pub fn play_computer_move(&mut self, limits: SearchLimits) -> Result<(GameState, SearchReport), GomokuError> {
+        self.check_turn(Player::Computer)?;
+
+        let report = self.suggest_move(limits)?;
+
+        self.log(|| format!("move to play: {}", report.best_move));
+
+        let state = self.play(report.best_move, Player::Computer)?;
+
+        Ok((state, report))
+    }
+
+    /// Searches the best move for the side to move without playing it
+    ///
+    /// Scores in the report are from the side to move's point of view. A book move is played
+    /// without searching, its report of depth 0 scored by the static evaluation.
+    pub fn suggest_move(&mut self, limits: SearchLimits) -> Result<SearchReport, GomokuError> {
+        if let Some(report) = self.book_move() {
+            return Ok(report);
+        }
+
+        let (static_eval, moves, depth) = self.search_root(&limits, None)?;
+        let (best_move, score) = moves.first().copied().ok_or(GomokuError::NoMoveAvailable)?;
+
+        let report = SearchReport {
+            best_move,
+            score,
+            pv: self.principal_variation(best_move, depth),
+            depth,
+            static_eval,
+            visited_nodes: self.visited_nodes,
+            evaluated_nodes: self.evaluated_nodes,
+            eval_cache_hits: self.evaluated_nodes_hit,
+            eval_cache_misses: self.evaluated_nodes_miss,
+            metrics: self.metrics(),
+        };
+
+        self.log(|| format!("visited {} nodes, {}", report.visited_nodes, report.metrics));
+        self.log(|| format!("evaluated {} nodes (cache hit {}, cache miss: {})", report.evaluated_nodes, report.eval_cache_hits, report.eval_cache_misses));
+
+        Ok(report)
+    }
+
+    fn book_move(&mut self) -> Option<SearchReport> {
+        if self.game_state() != GameState::InProgress {
+            return None;
+        }
+
+        let (book, randomness) = self.book.as_ref()?;
+        let played = book.choose(&self.goban, *randomness, &mut self.rng())?;
+        let static_eval = self.evaluate_position();
+
+        self.log(|| format!("book move {} played in {} games, scoring {:.2}", played.position, played.games, played.win_rate()));
+
+        Some(SearchReport {
+            best_move: played.position,
+            score: Self::eval_to_score(&static_eval, Player::Computer),
+            pv: vec![played.position],
+            depth: 0,
+            static_eval,
+            visited_nodes: 0,
+            evaluated_nodes: 0,
+            eval_cache_hits: 0,
+            eval_cache_misses: 0,
+            metrics: SearchMetrics::default(),
+        })
+    }
+
+    /// Ranks the `n` best moves for the side to move, each with its expected continuation
+    pub fn analyze(&mut self, limits: SearchLimits, n: usize) -> Result<Vec<CandidateMove>, GomokuError> {
+        let (_, moves, depth) = self.search_root(&limits, None)?;
+
+        Ok(self.candidate_moves_of(moves, depth, n))
+    }
+
+    /// Ranks the `n` best moves as `analyze` does, along with the score of `position` from the same
+    /// search, so it compares with theirs even when `position` is not a move the engine considers
+    pub fn analyze_move(
+        &mut self,
+        limits: SearchLimits,
+        n: usize,
+        position: Position,
+    ) -> Result<(Vec<CandidateMove>, isize), GomokuError> {
+        let (_, moves, depth) = self.search_root(&limits, Some(position))?;
+        let score = moves
+            .iter()
+            .find(|&&(candidate, _)| candidate == position)
+            .map(|&(_, score)| score)
+            .ok_or(GomokuError::NoMoveAvailable)?;
+
+        Ok((self.candidate_moves_of(moves, depth, n), score))
+    }
+
+    fn candidate_moves_of(&self, moves: RankedMoves, depth: usize, n: usize) -> Vec<CandidateMove> {
+        moves
+            .into_iter()
+            .take(n)
+            .map(|(position, score)| CandidateMove {
+                position,
+                score,
+                pv: self.principal_variation(position, depth),
+            })
+            .collect()
+    }
+
+    /// Scores every root move from the side to move's point of view, best first
+    ///
+    /// Iterates over even depths up to the limit, the last completed depth is returned with its
+    /// scores. When the first iteration is cut short, the moves it scored are returned instead,
+    /// and without any, the moves ranked by their static evaluation at depth 0. `first` opens the
+    /// first iteration, among the candidates or not, so it is scored whenever any move is.
+    fn search_root(&mut self, limits: &SearchLimits, first: Option<Position>) -> Result<(Eval, RankedMoves, usize), GomokuError> {
+        let max_depth = match limits.mate_in {
+            Some(moves) => min(limits.depth, 2 * moves),
+            None => limits.depth,
+        };
+
+        Self::check_depth(max_depth)?;
+
+        let side = Self::player_of(self.goban.side_to_move());
+
+        self.check_turn(side)?;
+
+        let _search = trace_span!(INFO, "search", ?side, max_depth);
+        let static_eval = self.eval_current(side);
+
+        self.log(|| format!("{:?} score: {:?}", side, static_eval));
+
+        self.transposition_table.new_search();
+
+        self.visited_nodes = 0;
+        self.evaluated_nodes = 0;
+        self.evaluated_nodes_hit = 0;
+        self.evaluated_nodes_miss = 0;
+        self.tt_probes = 0;
+        self.tt_hits = 0;
+        self.iterations.clear();
+        self.started = Instant::now();
+        self.node_limit = limits.nodes;
+        self.deadline = limits.movetime.map(|movetime| self.started + movetime);
+
+        self.record(SearchTree::start);
+
+        // The whole search plays and takes back its moves on this board
+        let mut node = self.goban.clone();
+        let mut children = self.get_child_nodes(&mut node, side);
+        let mut result: Option<(RankedMoves, usize)> = None;
+
+        // Candidates surround the stones, on an empty board the centre is the natural opening
+        if children.is_empty() && self.goban.count(Stone::Black) + self.goban.count(Stone::White) == 0 {
+            let centre = Position::new(GOBAN_SIZE / 2, GOBAN_SIZE / 2);
+            let moves = std::iter::once(centre).chain(first.filter(|&first| first != centre)).map(|position| (position, 0)).collect::<Vec<_>>();
+
+            return Ok((static_eval, moves, 0));
+        }
+
+        if let Some(first) = first {
+            Self::check_move(&self.goban, first)?;
+            children.retain(|&(position, _)| position != first);
+            children.insert(0, (first, 0));
+        }
+
+        for depth in (2..=max_depth).step_by(2) {
+            let _iteration = trace_span!(DEBUG, "iteration", depth);
+            let mut moves = Vec::new();
+            let (iteration_nodes, iteration_start) = (self.visited_nodes, self.started.elapsed());
+
+            self.record(|tree| tree.start_iteration(depth));
+
+            for &(position, _) in &children {
+                let _root_move = trace_span!(DEBUG, "root_move", position = %position);
+
+                self.record(|tree| tree.enter(position, depth - 1, isize::MIN, isize::MAX));
+
+                let played = Move::new(Self::stone_of(side), position);
+
+                node.apply_move(played);
+
+                let score = self.minimax(&mut node, depth - 1, isize::MIN, isize::MAX, side == Player::Opponent);
+
+                node.undo_move(played);
+
+                self.record(|tree| tree.exit(score));
+
+                // An interrupted subtree has no meaningful score
+                if self.stopped() {
+                    break;
+                }
+
+                let score = Self::orient_score(score, side);
+
+                if moves.iter().all(|&(_, best)| score > best) {
+                    self.notify(|| SearchEvent::NewBestMove { depth, position, score });
+                }
+
+                trace_event!(DEBUG, score, nodes = self.visited_nodes, "root move searched");
+
+                moves.push((position, score));
+            }
+
+            let moves_searched = moves.len();
+            let complete = moves_searched == children.len();
+
+            // Stable, so equal scores keep the move ordering
+            moves.sort_by_key(|&(_, score)| Reverse(score));
+
+            if let Some(&(_, best)) = moves.first() {
+                self.record(|tree| tree.finish_iteration(Self::orient_score(best, side)));
+            }
+
+            if complete || (result.is_none() && !moves.is_empty()) {
+                result = Some((moves, depth));
+            }
+
+            if !complete {
+                trace_event!(DEBUG, searched = moves_searched, "iteration interrupted");
+                self.record(|tree| tree.cut_root(Cutoff::Stopped));
+                break;
+            }
+
+            self.iterations.push(IterationMetrics {
+                depth,
+                nodes: self.visited_nodes - iteration_nodes,
+                time_ms: (self.started.elapsed() - iteration_start).as_millis() as u64,
+            });
+
+            let moves = result.as_ref().map(|(moves, _)| moves.as_slice()).unwrap_or_default();
+
+            self.log(|| match moves.first() {
+                Some((position, score)) => format!("depth {}: {} scored {}", depth, position, score),
+                None => format!("depth {}: no move", depth),
+            });
+
+            if let Some(&(best_move, score)) = moves.first() {
+                self.notify(|| {
+                    let pv = self.principal_variation(best_move, depth);
+
+                    SearchEvent::Iteration(SearchInfo {
+                        depth,
+                        best_move,
+                        score,
+                        mate: self.mate_distance(&pv),
+                        pv,
+                        nodes: self.visited_nodes,
+                        metrics: self.metrics(),
+                    })
+                });
+            }
+
+            // Nothing beats a forced win
+            if moves.first().is_some_and(|&(_, score)| score == isize::MAX) {
+                break;
+            }
+
+            // The next iteration searches the best moves first
+            children.sort_by_key(|&(child, _)| moves.iter().position(|&(position, _)| position == child));
+        }
+
+        let (moves, depth) = result.unwrap_or((children, 0));
+
+        Ok((static_eval, moves, depth))
+    }
+
+    /// Performance of the running or last search
+    fn metrics(&self) -> SearchMetrics {
+        let elapsed = self.started.elapsed();
+        // Iterations go two plies deeper each
+        let branching_factor = match self.iterations.as_slice() {
+            [.., previous, last] if previous.nodes > 0 => Some((last.nodes as f64 / previous.nodes as f64).sqrt()),
+            _ => None,
+        };
+
+        SearchMetrics {
+            time_ms: elapsed.as_millis() as u64,
+            nps: (self.visited_nodes as f64 / elapsed.as_secs_f64().max(1e-6)) as usize,
+            branching_factor,
+            hashfull: self.transposition_table.hashfull(),
+            tt_probes: self.tt_probes,
+            tt_hits: self.tt_hits,
+            iterations: self.iterations.clone(),
+        }
+    }
+
+    /// Follows the table's best moves from the current position after `first`
+    fn principal_variation(&self, first: Position, length: usize) -> Vec<Position> {
+        let mut node = self.goban.clone();
+        let mut pv = Vec::new();
+        let mut next = Some(first);
+
+        while let Some(position) = next.filter(|_| pv.len() < length) {
+            if node.get(position.row, position.col).is_some() {
+                break;
+            }
+
+            node.apply_move(Move::new(node.side_to_move(), position));
+            pv.push(position);
+
+            if node.has_five(Stone::Black) || node.has_five(Stone::White) {
+                break;
+            }
+
+            let (hash, symmetry) = Self::table_key(&node);
+
+            next = self.transposition_table.probe_move(hash).map(|m| symmetry.inverse().apply(&m));
+        }
+
+        pv
+    }
+
+    /// Moves of the side to move until `pv` makes a five, negative when the opponent's, `None` when it
+    /// makes none
+    fn mate_distance(&self, pv: &[Position]) -> Option<isize> {
+        let side = self.goban.side_to_move();
+        let mut node = self.goban.clone();
+
+        for &position in pv {
+            node.apply_move(Move::new(node.side_to_move(), position));
+        }
+
+        // The side to move plays the odd plies of the PV
+        let moves = pv.len().div_ceil(2) as isize;
+
+        match (node.has_five(side), node.has_five(side.opponent())) {
+            (true, _) => Some(moves),
+            (_, true) => Some(-moves),
+            _ => None,
+        }
+    }
+
+    fn check_depth(depth: usize) -> Result<(), GomokuError> {
+        match depth >= 2 && depth.is_multiple_of(2) {
+            true => Ok(()),
+            false => Err(GomokuError::InvalidDepth(depth)),
+        }
+    }
+
+    fn check_turn(&self, player: Player) -> Result<(), GomokuError> {
+        if self.goban.has_five(Stone::Black) || self.goban.has_five(Stone::White) || self.goban.is_full() {
+            return Err(GomokuError::GameOver);
+        }
+
+        if Self::stone_of(player) != self.goban.side_to_move() {
+            return Err(GomokuError::OutOfTurn);
+        }
+
+        Ok(())
+    }
+
+    /// Best candidate moves of `player` with the evaluation of the position they lead to, each
+    /// played on `node` and taken back, best first
+    fn get_child_nodes(&mut self, node: &mut Goban, player: Player) -> Vec<(Position, isize)> {
+        let mut candidates = mem::take(&mut self.candidates);
+        let mut child_nodes = self.spare_children.pop().unwrap_or_default();
+        let opening = node.count(Stone::Black) + node.count(Stone::White) < CANONICAL_HASH_PLIES;
+
+        candidates.clear();
+        node.extend_limited_moves(CANDIDATE_RADIUS, &mut candidates);
+
+        for &position in &candidates {
+            let played = Move::new(Self::stone_of(player), position);
+
+            node.apply_move(played);
+
+            // The child's slot loads while it gets evaluated, opening nodes use another key
+            if !opening {
+                self.transposition_table.prefetch(node.get_hash());
+            }
+
+            // We should use a custom evaluation function for this
+            // With this solution we will miss winning / losing nodes
+            // one idea: include only move that create threat or block some
+            let eval = self.eval(node, player);
+
+            node.undo_move(played);
+
+            let score_eval = match eval {
+                Eval::Won => isize::MAX,
+                Eval::Lost => isize::MIN,
+                Eval::Score(n) => n,
+            };
+
+            child_nodes.push((position, score_eval));
+        }
+
+        self.candidates = candidates;
+
+        // Ties keep the board order, so searches are reproducible
+        let order = |&(position, score): &(Position, isize)| (Reverse(score), position.index());
+
+        if child_nodes.len() > BRANCHING_FACTOR_THRESHOLD {
+            child_nodes.select_nth_unstable_by_key(BRANCHING_FACTOR_THRESHOLD, order);
+
+            if let Some(tree) = &mut self.tree {
+                let pruned = &mut child_nodes[BRANCHING_FACTOR_THRESHOLD..];
+
+                pruned.sort_unstable_by_key(order);
+
+                for &(position, score) in pruned.iter() {
+                    tree.prune(position, Self::orient_score(score, player));
+                }
+            }
+
+            child_nodes.truncate(BRANCHING_FACTOR_THRESHOLD);
+        }
+
+        child_nodes.sort_unstable_by_key(order);
+
+        child_nodes
+    }
+
+    /// Score of `node` searched `depth` plies deep, its moves are played on it and taken back
+    pub fn minimax(
+        &mut self,
+        node: &mut Goban,
+        depth: usize,
+        mut alpha: isize,
+        mut beta: isize,
+        maximizing: bool,
+    ) -> isize {
+        let side = match maximizing {
+            true => Player::Computer,
+            false => Player::Opponent,
+        };
+
+        if self.stopped() {
+            self.record(|tree| tree.cut(Cutoff::Stopped));
+            return 0;
+        }
+
+        self.visited_nodes += 1;
+
+        match self.eval(node, side) {
+            Eval::Won => {
+                self.record(|tree| tree.cut(Cutoff::Terminal));
+                return if maximizing { isize::MAX } else { isize::MIN };
+            }
+            Eval::Lost => {
+                self.record(|tree| tree.cut(Cutoff::Terminal));
+                return if maximizing { isize::MIN } else { isize::MAX };
+            }
+            Eval::Score(n) if depth == 0 => {
+                self.record(|tree| tree.cut(Cutoff::Horizon));
+                return n * if maximizing { 1 } else { -1 };
+            }
+            _ => {}
+        };
+
+        let (hash, symmetry) = Self::table_key(node);
+
+        self.tt_probes += 1;
+
+        if let Some(entry) = self.transposition_table.probe_search(hash, depth) {
+            let score = Self::eval_to_score(&entry.eval(), side);
+
+            self.tt_hits += 1;
+
+            trace_event!(TRACE, depth, entry_depth = entry.depth(), bound = ?entry.bound(), "transposition hit");
+
+            match Self::orient_bound(entry.bound(), side) {
+                Bound::Exact => {
+                    self.record(|tree| tree.cut(Cutoff::Transposition));
+                    return score;
+                }
+                Bound::Lower => alpha = max(alpha, score),
+                Bound::Upper => beta = min(beta, score),
+            }
+
+            if alpha >= beta {
+                trace_event!(TRACE, depth, score, "transposition cutoff");
+                self.record(|tree| tree.cut(Cutoff::Transposition));
+                return score;
+            }
+        }
+
+        let (window_alpha, window_beta) = (alpha, beta);
+        let mut best;
+        let mut best_move = None;
+
+        let mut children = self.get_child_nodes(node, side);
+
+        // Search the move that was best last time first, it is the most likely to cut off
+        if let Some(hash_move) = self.transposition_table.probe_move(hash).map(|m| symmetry.inverse().apply(&m)) {
+            if let Some(index) = children.iter().position(|&(position, _)| position == hash_move) {
+                children[..=index].rotate_right(1);
+            }
+        }
+
+        if maximizing {
+            best = isize::MIN;
+
+            for &(position, _) in &children {
+                self.record(|tree| tree.enter(position, depth - 1, alpha, beta));
+
+                let played = Move::new(Self::stone_of(side), position);
+
+                node.apply_move(played);
+
+                let score = self.minimax(node, depth - 1, alpha, beta, false);
+
+                node.undo_move(played);
+
+                self.record(|tree| tree.exit(score));
+
+                if best_move.is_none() || score > best {
+                    best = score;
+                    best_move = Some(position);
+                }
+
+                if best >= beta {
+                    self.record(|tree| tree.cut(Cutoff::Beta));
+                    break;
+                }
+
+                alpha = max(alpha, best);
+            }
+        } else {
+            best = isize::MAX;
+
+            for &(position, _) in &children {
+                self.record(|tree| tree.enter(position, depth - 1, alpha, beta));
+
+                let played = Move::new(Self::stone_of(side), position);
+
+                node.apply_move(played);
+
+                let score = self.minimax(node, depth - 1, alpha, beta, true);
+
+                node.undo_move(played);
+
+                self.record(|tree| tree.exit(score));
+
+                if best_move.is_none() || score < best {
+                    best = score;
+                    best_move = Some(position);
+                }
+
+                if best <= alpha {
+                    self.record(|tree| tree.cut(Cutoff::Alpha));
+                    break;
+                }
+
+                beta = min(beta, best);
+            }
+        }
+
+        children.clear();
+        self.spare_children.push(children);
+
+        if self.stopped() {
+            self.record(|tree| tree.cut(Cutoff::Stopped));
+            return best;
+        }
+
+        let bound = match best {
+            n if n <= window_alpha => Bound::Upper,
+            n if n >= window_beta => Bound::Lower,
+            _ => Bound::Exact,
+        };
+
+        trace_event!(TRACE, depth, score = best, bound = ?bound, "transposition store");
+
+        self.transposition_table.store(
+            hash,
+            depth,
+            Self::orient_bound(bound, side),
+            Self::score_to_eval(best, side),
+            best_move.map(|m| symmetry.apply(&m)),
+        );
+
+        best
+    }
+
+    /// Hash the node is stored under in the table, with the symmetry mapping the node onto it
+    fn table_key(node: &Goban) -> (ZobristHash, Symmetry) {
+        match node.count(Stone::Black) + node.count(Stone::White) {
+            n if n <= CANONICAL_HASH_PLIES => node.get_canonical_hash(),
+            _ => (node.get_hash(), Symmetry::IDENTITY),
+        }
+    }
+
+    fn stone_of(player: Player) -> Stone {
+        player.into()
+    }
+
+    fn player_of(stone: Stone) -> Player {
+        stone.into()
+    }
+
+    /// Search scores are from the computer's point of view, table entries from `side`'s
+    fn score_to_eval(score: isize, side: Player) -> Eval {
+        let eval = match score {
+            isize::MAX => Eval::Won,
+            isize::MIN => Eval::Lost,
+            n => Eval::Score(n),
+        };
+
+        match side {
+            Player::Computer => eval,
+            Player::Opponent => eval.opposite(),
+        }
+    }
+
+    fn eval_to_score(eval: &Eval, side: Player) -> isize {
+        let score = match eval {
+            Eval::Won => isize::MAX,
+            Eval::Lost => isize::MIN,
+            Eval::Score(n) => *n,
+        };
+
+        Self::orient_score(score, side)
+    }
+
+    /// Turns a score from the computer's point of view into `side`'s, or back
+    fn orient_score(score: isize, side: Player) -> isize {
+        match side {
+            Player::Computer => score,
+            Player::Opponent => match score {
+                isize::MAX => isize::MIN,
+                isize::MIN => isize::MAX,
+                n => -n,
+            },
+        }
+    }
+
+    /// Flips lower and upper bounds between the computer's and the opponent's point of view
+    fn orient_bound(bound: Bound, side: Player) -> Bound {
+        match (bound, side) {
+            (Bound::Lower, Player::Opponent) => Bound::Upper,
+            (Bound::Upper, Player::Opponent) => Bound::Lower,
+            (bound, _) => bound,
+        }
+    }
+
+    /// Static evaluation from `player`'s point of view
+    ///
+    /// The cache holds evaluations for the side to move only, the other side's
+    /// evaluation is taken as its opposite.
+    fn eval(&mut self, goban: &Goban, player: Player) -> Eval
+    {
+        self.evaluated_nodes += 1;
+
+        let hash = goban.get_hash();
+        let to_move = Self::player_of(goban.side_to_move());
+
+        let eval = match self.eval_cache.probe(hash) {
+            Some(eval) => {
+                self.evaluated_nodes_hit += 1;
+                eval
+            },
+            None => {
+                self.evaluated_nodes_miss += 1;
+
+                let eval = goban.evaluate_lines(to_move);
+
+                self.eval_cache.store(hash, eval);
+
+                eval
+            }
+        };
+
+        match player == to_move {
+            true => eval,
+            false => eval.opposite(),
+        }
+    }
+
+    fn eval_current(&mut self, player: Player) -> Eval
+    {
+        self.eval(&self.goban.clone(), player)
+    }
+
+    /// Static evaluation of the current position for the side to move
+    pub fn evaluate_position(&mut self) -> Eval {
+        self.evaluate_board(&self.goban.clone())
+    }
+
+    /// Static evaluation of `goban` for its side to move, the current game is untouched
+    pub fn evaluate_board(&mut self, goban: &Goban) -> Eval {
+        self.eval(goban, Self::player_of(goban.side_to_move()))
+    }
+}
+
+impl fmt::Display for Gomoku {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.goban)
+    }
+}
+
+/// Free placement of stones on a copy of the board, committed by `finish`
+pub struct PositionEditor<'a> {
+    gomoku: &'a mut Gomoku,
+    goban: Goban,
+}
+
+impl<'a> PositionEditor<'a> {
+    /// Puts a stone on an empty intersection, `remove` first to replace one
+    pub fn place(&mut self, position: Position, stone: Stone) -> Result<&mut Self, GomokuError> {
+        if position.row >= GOBAN_SIZE || position.col >= GOBAN_SIZE {
+            return Err(GomokuError::InvalidMove { reason: InvalidMoveReason::OutOfBounds, position });
+        }
+
+        if self.goban.get(position.row, position.col).is_some() {
+            return Err(GomokuError::InvalidMove { reason: InvalidMoveReason::Occupied, position });
+        }
+
+        self.goban.apply_move(Move::new(stone, position));
+
+        Ok(self)
+    }
+
+    pub fn remove(&mut self, position: &Position) -> Option<Stone> {
+        self.goban.clear(position)
+    }
+
+    pub fn clear(&mut self) -> &mut Self {
+        for row in 0..GOBAN_SIZE {
+            for col in 0..GOBAN_SIZE {
+                self.goban.clear(&Position::new(row, col));
+            }
+        }
+
+        self
+    }
+
+    /// Validates the edited position and makes it the current game position
+    ///
+    /// Black moves first, so it must have as many stones as White or exactly one more,
+    /// and only the side that moved last can have completed a five.
+    pub fn finish(mut self) -> Result<GameState, GomokuError> {
+        let black = self.goban.count(Stone::Black);
+        let white = self.goban.count(Stone::White);
+
+        if black != white && black != white + 1 {
+            return Err(GomokuError::InvalidSetup("Black must have as many stones as White or one more"));
+        }
+
+        let side_to_move = match black == white {
+            true => Stone::Black,
+            false => Stone::White,
+        };
+
+        if self.goban.has_five(side_to_move) {
+            return Err(GomokuError::InvalidSetup("The side to move cannot have a five"));
+        }
+
+        self.goban.set_side_to_move(side_to_move);
+
+        self.gomoku.goban = self.goban;
+        self.gomoku.history.clear();
+
+        Ok(self.gomoku.game_state())
+    }
+}
+
+#[cfg(test)]
+mod editor_tests {
+    use crate::error::{GomokuError, InvalidMoveReason};
+    use crate::goban::{Position, Stone};
+    use crate::gomoku::{GameState, Gomoku};
+
+    #[test]
+    fn it_validates_edited_positions() {
+        let mut gomoku = Gomoku::default();
+
+        let mut editor = gomoku.edit_position();
+        editor.place(Position::new(9, 9), Stone::White).unwrap();
+        assert!(editor.finish().is_err());
+        assert_eq!(gomoku.goban.count(Stone::White), 0);
+
+        let mut editor = gomoku.edit_position();
+        for col in 0..5 {
+            editor.place(Position::new(0, col), Stone::Black).unwrap();
+            editor.place(Position::new(1, col), Stone::White).unwrap();
+        }
+        assert!(editor.finish().is_err());
+
+        let mut editor = gomoku.edit_position();
+        for col in 0..5 {
+            editor.place(Position::new(0, col), Stone::Black).unwrap();
+            editor.place(Position::new(2, col), Stone::White).unwrap();
+        }
+        editor.remove(&Position::new(2, 0));
+        assert!(matches!(editor.finish(), Ok(GameState::Won(_))));
+
+        let mut editor = gomoku.edit_position();
+        editor.clear().place(Position::new(9, 9), Stone::Black).unwrap();
+        assert!(matches!(editor.finish(), Ok(GameState::InProgress)));
+        assert_eq!(gomoku.goban.count(Stone::Black), 1);
+    }
+
+    #[test]
+    fn it_rejects_a_five_by_the_side_to_move() {
+        let mut gomoku = Gomoku::default();
+
+        let mut editor = gomoku.edit_position();
+        for col in 0..5 {
+            editor.place(Position::new(0, col), Stone::Black).unwrap();
+            editor.place(Position::new(2, col), Stone::White).unwrap();
+        }
+        assert_eq!(editor.finish(), Err(GomokuError::InvalidSetup("The side to move cannot have a five")));
+
+        let mut editor = gomoku.edit_position();
+        for col in 0..5 {
+            editor.place(Position::new(0, col), Stone::White).unwrap();
+            editor.place(Position::new(2, col), Stone::Black).unwrap();
+        }
+        editor.place(Position::new(4, 0), Stone::Black).unwrap();
+        assert_eq!(editor.finish(), Err(GomokuError::InvalidSetup("The side to move cannot have a five")));
+        assert_eq!(gomoku.goban.count(Stone::Black), 0);
+    }
+
+    #[test]
+    fn it_does_not_place_on_an_occupied_intersection() {
+        let mut gomoku = Gomoku::default();
+
+        let mut editor = gomoku.edit_position();
+        editor.place(Position::new(9, 9), Stone::Black).unwrap();
+        assert_eq!(
+            editor.place(Position::new(9, 9), Stone::White).err(),
+            Some(GomokuError::InvalidMove { reason: InvalidMoveReason::Occupied, position: Position::new(9, 9) })
+        );
+
+        assert_eq!(editor.remove(&Position::new(9, 9)), Some(Stone::Black));
+        editor.place(Position::new(9, 9), Stone::White).unwrap();
+        editor.place(Position::new(0, 0), Stone::Black).unwrap();
+        assert_eq!(editor.finish(), Ok(GameState::InProgress));
+        assert_eq!(gomoku.goban.get(9, 9), Some(Stone::White));
+    }
+}
+
+#[cfg(test)]
+mod search_tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use crate::book::OpeningBook;
+    use crate::error::{GomokuError, InvalidMoveReason};
+    use crate::evaluator::Eval;
+    use crate::goban::{Goban, GOBAN_SIZE, Move, Player, Position, Stone};
+    use crate::gomoku::{GameState, Gomoku, GomokuBuilder, PositionSetup, SearchEvent, SearchInfo, SearchLimits};
+    use crate::search_tree::{Cutoff, TreeNode};
+    use crate::transposition_table::ReplacementPolicy;
+    use crate::zobrist_hashing::hasher;
+
+    #[test]
+    fn it_reports_the_played_move_to_the_caller() {
+        let mut gomoku = Gomoku::with_hash_size(1);
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let sink = messages.clone();
+
+        gomoku.set_logger(move |message| sink.lock().unwrap().push(message.to_string()));
+        gomoku.play(Position::new(9, 9), Player::Opponent).unwrap();
+
+        let (_, report) = gomoku.play_computer_move(SearchLimits::new(2)).unwrap();
+
+        assert_eq!(gomoku.goban.get(report.best_move.row, report.best_move.col), Some(Stone::White));
+        assert!(report.visited_nodes > 0);
+        assert!(messages.lock().unwrap().iter().any(|message| message.starts_with("move to play")));
+    }
+
+    #[test]
+    fn it_rejects_invalid_moves_and_depths() {
+        let mut gomoku = Gomoku::with_hash_size(1);
+
+        assert_eq!(gomoku.suggest_move(SearchLimits::new(3)).err(), Some(GomokuError::InvalidDepth(3)));
+        assert_eq!(gomoku.play_computer_move(SearchLimits::new(2)).err(), Some(GomokuError::OutOfTurn));
+        assert_eq!(gomoku.play(Position::new(0, 0), Player::Computer).err(), Some(GomokuError::OutOfTurn));
+        assert_eq!(
+            gomoku.play(Position::new(0, GOBAN_SIZE), Player::Opponent).err(),
+            Some(GomokuError::InvalidMove { reason: InvalidMoveReason::OutOfBounds, position: Position::new(0, GOBAN_SIZE) })
+        );
+
+        gomoku.play(Position::new(9, 9), Player::Opponent).unwrap();
+        assert_eq!(
+            gomoku.play(Position::new(9, 9), Player::Computer).err(),
+            Some(GomokuError::InvalidMove { reason: InvalidMoveReason::Occupied, position: Position::new(9, 9) })
+        );
+
+        let mut editor = gomoku.edit_position();
+        editor.clear();
+        for col in 0..5 {
+            editor.place(Position::new(0, col), Stone::Black).unwrap();
+            editor.place(Position::new(2, col), Stone::White).unwrap();
+        }
+        editor.remove(&Position::new(2, 0));
+        assert!(editor.finish().is_ok());
+
+        assert_eq!(gomoku.play_computer_move(SearchLimits::new(2)).err(), Some(GomokuError::GameOver));
+    }
+
+    #[test]
+    fn it_draws_on_a_full_board_without_five() {
+        let mut gomoku = Gomoku::with_hash_size(1);
+
+        let mut editor = gomoku.edit_position();
+        for row in 0..GOBAN_SIZE {
+            for col in 0..GOBAN_SIZE {
+                let stone = match (col + 2 * row) % 4 < 2 {
+                    true => Stone::Black,
+                    false => Stone::White,
+                };
+                editor.place(Position::new(row, col), stone).unwrap();
+            }
+        }
+
+        assert_eq!(editor.finish(), Ok(GameState::Draw));
+        assert_eq!(gomoku.play_computer_move(SearchLimits::new(2)).err(), Some(GomokuError::GameOver));
+    }
+
+    #[test]
+    fn it_suggests_a_move_without_playing_it() {
+        let mut gomoku = Gomoku::with_hash_size(1);
+
+        gomoku.play(Position::new(9, 9), Player::Opponent).unwrap();
+
+        let report = gomoku.suggest_move(SearchLimits::new(2)).unwrap();
+        assert_eq!(gomoku.goban.count(Stone::White), 0);
+        assert_eq!(report.pv.first(), Some(&report.best_move));
+
+        gomoku.play(report.best_move, Player::Computer).unwrap();
+
+        let report = gomoku.suggest_move(SearchLimits::new(2)).unwrap();
+        assert_eq!(gomoku.goban.count(Stone::Black), 1);
+        assert_eq!(gomoku.goban.get(report.best_move.row, report.best_move.col), None);
+    }
+
+    #[test]
+    fn it_opens_in_the_centre() {
+        let mut gomoku = Gomoku::with_hash_size(1);
+
+        let report = gomoku.suggest_move(SearchLimits::new(2)).unwrap();
+        assert_eq!(report.best_move, Position::new(GOBAN_SIZE / 2, GOBAN_SIZE / 2));
+    }
+
+    #[test]
+    fn it_ranks_candidate_moves() {
+        let mut gomoku = Gomoku::with_hash_size(1);
+
+        gomoku.play(Position::new(9, 9), Player::Opponent).unwrap();
+
+        let candidates = gomoku.analyze(SearchLimits::new(2), 3).unwrap();
+
+        assert_eq!(candidates.len(), 3);
+        assert!(candidates.windows(2).all(|pair| pair[0].score >= pair[1].score));
+        assert!(candidates.iter().all(|candidate| candidate.pv.first() == Some(&candidate.position)));
+        assert!(candidates[0].pv.len() > 1);
+        assert_eq!(gomoku.goban.count(Stone::White), 0);
+    }
+
+    #[test]
+    fn it_evaluates_positions_for_the_side_to_move() {
+        let mut gomoku = Gomoku::with_hash_size(1);
+
+        let mut editor = gomoku.edit_position();
+        for col in 0..4 {
+            editor.place(Position::new(0, col), Stone::Black).unwrap();
+            editor.place(Position::new(9, 2 * col), Stone::White).unwrap();
+        }
+        assert_eq!(editor.finish(), Ok(GameState::InProgress));
+
+        let mut board = gomoku.goban.clone();
+        board.set_side_to_move(Stone::White);
+
+        assert!(matches!(gomoku.evaluate_position(), Eval::Score(n) if n > 0));
+        assert!(matches!(gomoku.evaluate_board(&board), Eval::Score(n) if n < 0));
+        assert_eq!(gomoku.goban.side_to_move(), Stone::Black);
+    }
+
+    #[test]
+    fn it_sets_positions_from_moves_and_boards() {
+        let mut gomoku = Gomoku::with_hash_size(1);
+
+        let setup: PositionSetup = "j10 k11 h9".parse().unwrap();
+        assert_eq!(gomoku.set_position(setup, Stone::White), Ok(GameState::InProgress));
+        assert_eq!(gomoku.history().len(), 3);
+        assert_eq!(gomoku.history()[0], Move::new(Stone::Black, Position::new(9, 9)));
+        assert_eq!(gomoku.goban.side_to_move(), Stone::White);
+
+        let setup: PositionSetup = "j10 k11".parse().unwrap();
+        assert!(gomoku.set_position(setup, Stone::White).is_ok());
+        assert_eq!(gomoku.history()[0].stone, Stone::White);
+
+        let setup: PositionSetup = "j10 j10".parse().unwrap();
+        assert!(matches!(gomoku.set_position(setup, Stone::Black), Err(GomokuError::InvalidMove { .. })));
+        assert_eq!(gomoku.history().len(), 2);
+
+        let mut board = Goban::new();
+        board.apply_move(Move::new(Stone::White, Position::new(3, 3)));
+        assert!(gomoku.set_position(PositionSetup::Board(Box::new(board)), Stone::White).is_ok());
+        assert!(gomoku.history().is_empty());
+        assert_eq!(gomoku.play(Position::new(4, 4), Player::Computer), Ok(GameState::InProgress));
+    }
+
+    #[test]
+    fn it_resets_the_game_but_not_the_caches() {
+        let mut gomoku = Gomoku::with_hash_size(1);
+
+        gomoku.play(Position::new(9, 9), Player::Opponent).unwrap();
+        gomoku.play_computer_move(SearchLimits::new(2)).unwrap();
+        let (hash, _) = Gomoku::table_key(&gomoku.goban);
+        assert!(gomoku.transposition_table.probe_move(hash).is_some());
+
+        gomoku.reset();
+
+        assert_eq!(gomoku.goban, Goban::new());
+        assert!(gomoku.history().is_empty());
+        assert_eq!(gomoku.goban.side_to_move(), Stone::Black);
+        assert!(gomoku.transposition_table.probe_move(hash).is_some());
+    }
+
+    #[test]
+    fn it_forks_without_disturbing_the_game() {
+        let mut gomoku = Gomoku::with_hash_size(1);
+
+        gomoku.play(Position::new(9, 9), Player::Opponent).unwrap();
+
+        let mut fork = gomoku.clone();
+        fork.play_computer_move(SearchLimits::new(2)).unwrap();
+        let (hash, _) = Gomoku::table_key(&fork.goban);
+
+        assert_eq!(fork.history().len(), 2);
+        assert_eq!(gomoku.history().len(), 1);
+        assert_eq!(gomoku.board().side_to_move(), Stone::White);
+        assert_eq!(gomoku.board().get(9, 9), Some(Stone::Black));
+        assert!(gomoku.transposition_table.probe_move(hash).is_some());
+    }
+
+    #[test]
+    fn it_builds_configured_engines() {
+        let gomoku = GomokuBuilder::new()
+            .hash_size_mb(1)
+            .replacement_policy(ReplacementPolicy::AlwaysReplace)
+            .eval_cache_size_mb(1)
+            .search_depth(6)
+            .build()
+            .unwrap();
+
+        assert_eq!(gomoku.search_depth(), 6);
+        assert_eq!(GomokuBuilder::new().search_depth(5).build().err(), Some(GomokuError::InvalidDepth(5)));
+
+        // Choice seeds belong to each engine, unlike the zobrist keys
+        for _ in 0..2 {
+            assert_eq!(GomokuBuilder::new().hash_size_mb(1).eval_cache_size_mb(1).seed(7).build().unwrap().seed(), 7);
+        }
+
+        hasher();
+        assert_eq!(
+            GomokuBuilder::new().hash_size_mb(1).zobrist_seed(7).build().err(),
+            Some(GomokuError::AlreadySeeded)
+        );
+    }
+
+    #[test]
+    fn it_can_be_shared_between_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<Gomoku>();
+        assert_send_sync::<Goban>();
+        assert_send_sync::<GomokuError>();
+    }
+
+    #[test]
+    fn it_stops_when_the_flag_is_raised() {
+        let mut gomoku = Gomoku::with_hash_size(1);
+
+        gomoku.play(Position::new(9, 9), Player::Opponent).unwrap();
+        gomoku.set_stop_flag(Arc::new(AtomicBool::new(true)));
+
+        let report = gomoku.suggest_move(SearchLimits::new(4)).unwrap();
+        assert_eq!(report.depth, 0);
+        assert_eq!(gomoku.history().len(), 1);
+    }
+
+    #[test]
+    fn it_keeps_the_last_complete_iteration_within_the_limits() {
+        let mut gomoku = Gomoku::with_hash_size(1);
+
+        gomoku.play(Position::new(9, 9), Player::Opponent).unwrap();
+
+        let report = gomoku.suggest_move(SearchLimits::new(4).with_nodes(20)).unwrap();
+        assert_eq!(report.depth, 2);
+
+        let report = gomoku.suggest_move(SearchLimits::new(8).with_mate_in(1)).unwrap();
+        assert_eq!(report.depth, 2);
+        // No win in one move, the best move is returned all the same
+        assert_ne!(report.score, isize::MAX);
+
+        let report = gomoku.suggest_move(SearchLimits::new(8).with_movetime(Duration::ZERO)).unwrap();
+        assert_eq!(report.depth, 0);
+    }
+
+    #[test]
+    fn it_streams_search_progress() {
+        let mut gomoku = Gomoku::with_hash_size(1);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+
+        gomoku.set_search_observer(move |event| sink.lock().unwrap().push(event.clone()));
+        gomoku.play(Position::new(9, 9), Player::Opponent).unwrap();
+
+        let report = gomoku.suggest_move(SearchLimits::new(2)).unwrap();
+        let events = events.lock().unwrap();
+        let iterations: Vec<&SearchInfo> = events
+            .iter()
+            .filter_map(|event| match event {
+                SearchEvent::Iteration(info) => Some(info),
+                _ => None,
+            })
+            .collect();
+
+        assert!(matches!(events[0], SearchEvent::NewBestMove { depth: 2, .. }));
+        assert_eq!(iterations.len(), 1);
+        assert_eq!(iterations[0].depth, 2);
+        assert_eq!(iterations[0].best_move, report.best_move);
+        assert_eq!(iterations[0].pv.first(), Some(&report.best_move));
+    }
+
+    #[test]
+    fn it_reports_the_moves_to_a_proven_five() {
+        let mut gomoku = Gomoku::with_hash_size(1);
+        let mates = Arc::new(Mutex::new(Vec::new()));
+        let sink = mates.clone();
+
+        gomoku.set_search_observer(move |event| {
+            if let SearchEvent::Iteration(info) = event {
+                sink.lock().unwrap().push(info.mate);
+            }
+        });
+
+        // Black's open four on the tenth row, white to move
+        for (ply, position) in "J10 A1 K10 A3 L10 A5 M10".split_whitespace().enumerate() {
+            let player = if ply % 2 == 0 { Player::Opponent } else { Player::Computer };
+
+            gomoku.play(Position::from_coordinates(position).unwrap(), player).unwrap();
+        }
+
+        let report = gomoku.suggest_move(SearchLimits::new(2)).unwrap();
+
+        assert_eq!(report.score, isize::MIN);
+        assert_eq!(mates.lock().unwrap().last(), Some(&Some(-1)));
+
+        gomoku.play(report.best_move, Player::Computer).unwrap();
+        gomoku.suggest_move(SearchLimits::new(2)).unwrap();
+
+        assert_eq!(mates.lock().unwrap().last(), Some(&Some(1)));
+
+        // Nobody wins right after the opening move
+        gomoku.reset();
+        gomoku.play(Position::new(9, 9), Player::Opponent).unwrap();
+        gomoku.suggest_move(SearchLimits::new(2)).unwrap();
+
+        assert_eq!(mates.lock().unwrap().last(), Some(&None));
+    }
+
+    #[test]
+    fn it_measures_every_iteration() {
+        let mut gomoku = Gomoku::with_hash_size(1);
+
+        gomoku.play(Position::new(9, 9), Player::Opponent).unwrap();
+
+        let report = gomoku.suggest_move(SearchLimits::new(2)).unwrap();
+        let metrics = &report.metrics;
+
+        assert_eq!(metrics.iterations.len(), 1);
+        assert_eq!((metrics.iterations[0].depth, metrics.iterations[0].nodes), (2, report.visited_nodes));
+        // One iteration gives nothing to compare
+        assert_eq!(metrics.branching_factor, None);
+        assert!(metrics.tt_probes > 0 && metrics.tt_hits <= metrics.tt_probes);
+        assert!((0.0..=1.0).contains(&metrics.tt_hit_rate()));
+    }
+
+    #[test]
+    fn it_lists_legal_moves() {
+        let mut gomoku = Gomoku::with_hash_size(1);
+
+        assert_eq!(gomoku.legal_moves().len(), GOBAN_SIZE * GOBAN_SIZE);
+
+        gomoku.play(Position::new(9, 9), Player::Opponent).unwrap();
+
+        assert_eq!(gomoku.legal_moves().len(), GOBAN_SIZE * GOBAN_SIZE - 1);
+        assert!(!gomoku.is_legal(Position::new(9, 9)));
+        assert!(!gomoku.is_legal(Position::new(GOBAN_SIZE, 0)));
+        assert!(gomoku.is_legal(Position::new(0, 0)));
+
+        let mut editor = gomoku.edit_position();
+        editor.clear();
+        for col in 0..5 {
+            editor.place(Position::new(0, col), Stone::Black).unwrap();
+            editor.place(Position::new(2, col), Stone::White).unwrap();
+        }
+        editor.remove(&Position::new(2, 0));
+        assert!(editor.finish().is_ok());
+
+        assert!(gomoku.legal_moves().is_empty());
+        assert!(!gomoku.is_legal(Position::new(18, 18)));
+    }
+
+    #[test]
+    fn it_records_the_search_tree() {
+        let mut gomoku = Gomoku::with_hash_size(1);
+
+        gomoku.record_search_tree(100_000);
+        gomoku.play(Position::new(9, 9), Player::Opponent).unwrap();
+
+        let report = gomoku.suggest_move(SearchLimits::new(2)).unwrap();
+        let tree = gomoku.search_tree().unwrap();
+        let root_moves: Vec<&TreeNode> = tree.nodes().iter().filter(|node| node.parent == Some(0)).collect();
+        let (pruned, searched): (Vec<&TreeNode>, Vec<&TreeNode>) =
+            root_moves.iter().partition(|node| node.cutoff == Some(Cutoff::Pruned));
+
+        // White is to move, so the scores of the tree are the ones reported
+        assert_eq!(tree.nodes()[0].score, Some(report.score));
+        assert_eq!((searched.len(), pruned.len()), (10, 14));
+        assert!(searched
+            .iter()
+            .any(|node| node.position == Some(report.best_move) && node.score == Some(report.score)));
+        assert!(tree.nodes().iter().any(|node| node.cutoff == Some(Cutoff::Horizon)));
+        assert!(!tree.is_truncated());
+
+        gomoku.record_search_tree(5);
+        gomoku.suggest_move(SearchLimits::new(2)).unwrap();
+
+        let tree = gomoku.search_tree().unwrap();
+
+        assert_eq!(tree.nodes().len(), 5);
+        assert!(tree.is_truncated());
+
+        gomoku.stop_recording_search_tree();
+        assert!(gomoku.search_tree().is_none());
+    }
+
+    #[test]
+    fn it_counts_the_generated_candidates() {
+        let mut gomoku = Gomoku::with_hash_size(1);
+
+        assert_eq!(gomoku.perft(0), 1);
+        assert_eq!(gomoku.perft(1), 1);
+        assert_eq!(gomoku.perft(2), 24);
+        // The 24 replies each open the 5x5 square around them, less its overlap with the centre's
+        assert_eq!(gomoku.perft(3), 816);
+
+        let divide = gomoku.perft_divide(3);
+
+        assert_eq!(divide, vec![(Position::new(9, 9), 816)]);
+
+        // Candidates stop at the edges rather than wrapping to the other side of the board
+        gomoku.play(Position::new(0, 0), Player::Opponent).unwrap();
+        assert_eq!(gomoku.perft(1), 8);
+
+        let five = (0..5).flat_map(|col| [Position::new(9, col), Position::new(12, col)]).take(9).collect();
+
+        gomoku.set_position(PositionSetup::Moves(five), Stone::White).unwrap();
+        assert_eq!(gomoku.perft(1), 0);
+    }
+
+    #[test]
+    fn it_plays_book_moves_without_searching() {
+        let mut gomoku = Gomoku::with_hash_size(1);
+        let mut book = OpeningBook::new();
+
+        book.add_game(&[Position::new(9, 9), Position::new(8, 8)], Some(Stone::White), 2);
+        gomoku.set_book(Arc::new(book), 0.0);
+        gomoku.play(Position::new(9, 9), Player::Opponent).unwrap();
+
+        let report = gomoku.suggest_move(SearchLimits::new(4)).unwrap();
+
+        assert_eq!(report.best_move, Position::new(8, 8));
+        assert_eq!((report.depth, report.visited_nodes), (0, 0));
+
+        // Out of the book the engine searches again
+        gomoku.play(Position::new(8, 8), Player::Computer).unwrap();
+        gomoku.play(Position::new(9, 10), Player::Opponent).unwrap();
+
+        assert!(gomoku.suggest_move(SearchLimits::new(2)).unwrap().visited_nodes > 0);
+
+        gomoku.learn_book(Some(Stone::Black));
+
+        assert_eq!(gomoku.book().unwrap().moves(&Goban::new())[0].wins, 1);
+
+        gomoku.clear_book();
+        gomoku.set_position(PositionSetup::Moves(vec![Position::new(9, 9)]), Stone::White).unwrap();
+
+        assert!(gomoku.suggest_move(SearchLimits::new(2)).unwrap().depth > 0);
+    }
+
+    #[test]
+    fn it_repeats_its_random_choices_from_the_seed() {
+        let mut book = OpeningBook::new();
+
+        for col in 7..12 {
+            book.add_game(&[Position::new(9, 9), Position::new(8, col)], None, 2);
+        }
+
+        let book = Arc::new(book);
+        let book_move = |seed: u64| {
+            let mut gomoku = GomokuBuilder::new().hash_size_mb(1).eval_cache_size_mb(1).seed(seed).build().unwrap();
+
+            gomoku.set_book(book.clone(), 1.0);
+            gomoku.play(Position::new(9, 9), Player::Opponent).unwrap();
+            gomoku.suggest_move(SearchLimits::new(2)).unwrap().best_move
+        };
+        let chosen: Vec<Position> = (0..16).map(book_move).collect();
+
+        assert_eq!(chosen, (0..16).map(book_move).collect::<Vec<_>>());
+        assert!(chosen.iter().any(|&position| position != chosen[0]));
     }
 }
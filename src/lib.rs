@@ -1,8 +1,28 @@
-#![feature(binary_heap_into_iter_sorted)]
+#[macro_use]
+mod trace;
 
-mod evaluator;
+#[cfg(feature = "async")]
+pub mod async_search;
+pub mod clock;
+pub mod book;
+pub mod bots;
+pub mod elo;
+pub mod engine;
+pub mod error;
+mod eval_cache;
+pub mod evaluator;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "serde")]
+pub mod game;
 pub mod goban;
 pub mod gomoku;
-mod threat_evaluator;
+pub mod renju;
+pub mod search_tree;
+#[cfg(any(feature = "server", feature = "grpc"))]
+pub mod sessions;
+pub mod sgf;
+pub mod suite;
+pub mod threat_evaluator;
 pub mod zobrist_hashing;
-mod transposition_table;
+pub mod transposition_table;
@@ -0,0 +1,294 @@
+use std::fmt;
+
+use crate::goban::{Player, Position, GOBAN_SIZE};
+use crate::gomoku::{GameState, PositionSetup};
+
+/// Renju is played on 15x15, imported moves are centred on the larger board
+pub const RENJU_SIZE: usize = 15;
+
+const OFFSET: usize = (GOBAN_SIZE - RENJU_SIZE) / 2;
+
+const RENLIB_MAGIC: &[u8] = b"\xFFRenLib\xFF";
+const RENLIB_HEADER_SIZE: usize = 20;
+
+// Flags of a RenLib node, the other ones (marks, start) do not matter to the moves
+const DOWN: u8 = 0x80;
+const RIGHT: u8 = 0x40;
+const OLD_COMMENT: u8 = 0x20;
+const COMMENT: u8 = 0x08;
+const EXTENSION: u8 = 0x01;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenjuError {
+    InvalidHeader,
+    /// The data ends in the middle of a record
+    Truncated,
+    /// Extended RenLib records are not supported
+    Unsupported(usize),
+    InvalidMove(String),
+}
+
+impl fmt::Display for RenjuError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RenjuError::InvalidHeader => write!(f, "Not a RenLib library"),
+            RenjuError::Truncated => write!(f, "The library ends in the middle of a record"),
+            RenjuError::Unsupported(offset) => write!(f, "Unsupported extended record at byte {}", offset),
+            RenjuError::InvalidMove(notation) => write!(f, "Invalid Renju move `{}`", notation),
+        }
+    }
+}
+
+impl std::error::Error for RenjuError {}
+
+/// A game of a RIF database
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenjuGame {
+    /// Attributes of the game element, such as its `id`, `black` and `white` player ids
+    pub attributes: Vec<(String, String)>,
+    pub moves: Vec<Position>,
+    /// From the `bresult` attribute, Black being the opponent
+    pub result: Option<GameState>,
+}
+
+impl RenjuGame {
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn setup(&self) -> PositionSetup {
+        PositionSetup::Moves(self.moves.clone())
+    }
+}
+
+/// Position of a 15x15 intersection, the row counted from the top
+fn centred(row: usize, col: usize) -> Position {
+    Position::new(row + OFFSET, col + OFFSET)
+}
+
+/// Reads a Renju move such as `h8`, columns `a` to `o` and rows 1 to 15 from the bottom
+pub fn parse_move(notation: &str) -> Result<Position, RenjuError> {
+    let invalid = || RenjuError::InvalidMove(notation.to_string());
+    let mut chars = notation.chars();
+
+    let col = match chars.next().map(|c| c.to_ascii_lowercase()) {
+        Some(c @ 'a'..='o') => c as usize - 'a' as usize,
+        _ => return Err(invalid()),
+    };
+    let row = match chars.as_str().parse::<usize>() {
+        Ok(row @ 1..=RENJU_SIZE) => RENJU_SIZE - row,
+        _ => return Err(invalid()),
+    };
+
+    Ok(centred(row, col))
+}
+
+/// Every line of a RenLib library, from the root to each leaf
+///
+/// A node is two bytes, its intersection then its flags, stored in preorder: `DOWN`
+/// marks a node without children and `RIGHT` one followed by a sibling once its
+/// subtree ends. Comments are skipped.
+pub fn read_renlib(data: &[u8]) -> Result<Vec<Vec<Position>>, RenjuError> {
+    if data.len() < RENLIB_HEADER_SIZE || !data.starts_with(RENLIB_MAGIC) {
+        return Err(RenjuError::InvalidHeader);
+    }
+
+    let mut lines = Vec::new();
+    let mut line = Vec::new();
+    // Lengths the line goes back to when the pending siblings come
+    let mut branches = Vec::new();
+    let mut offset = RENLIB_HEADER_SIZE;
+
+    while offset < data.len() {
+        let record = data.get(offset..offset + 2).ok_or(RenjuError::Truncated)?;
+        let (intersection, flags) = (record[0] as usize, record[1]);
+
+        if flags & EXTENSION != 0 {
+            return Err(RenjuError::Unsupported(offset));
+        }
+
+        offset += 2;
+
+        // Comments are null terminated and padded to whole records
+        if flags & (COMMENT | OLD_COMMENT) != 0 {
+            loop {
+                let pair = data.get(offset..offset + 2).ok_or(RenjuError::Truncated)?;
+                offset += 2;
+
+                if pair[1] == 0 {
+                    break;
+                }
+            }
+        }
+
+        if flags & RIGHT != 0 {
+            branches.push(line.len());
+        }
+
+        // Zero is the root, which has no stone
+        if intersection != 0 {
+            let (row, col) = (intersection / 16, intersection % 16);
+
+            if col == 0 || row >= RENJU_SIZE {
+                return Err(RenjuError::InvalidMove(format!("{:#04x}", intersection)));
+            }
+
+            line.push(centred(row, col - 1));
+        }
+
+        if flags & DOWN != 0 {
+            if !line.is_empty() {
+                lines.push(line.clone());
+            }
+
+            match branches.pop() {
+                Some(length) => line.truncate(length),
+                None => break,
+            }
+        }
+    }
+
+    Ok(lines)
+}
+
+/// Games of a RIF database, the XML export of the Renju International Federation
+///
+/// Moves are read from the `move` element of each `game` element, such as
+/// `<game id="1" black="2" white="3" bresult="1"><move>h8 i9 j10</move></game>`.
+pub fn read_rif(text: &str) -> Result<Vec<RenjuGame>, RenjuError> {
+    let mut games = Vec::new();
+    let mut rest = text;
+
+    // `<games>` encloses the games, the element of a game is `<game>` or `<game ...>`
+    while let Some(start) = [rest.find("<game "), rest.find("<game>")].iter().flatten().min().copied() {
+        let element = &rest[start + "<game".len()..];
+        let tag_end = element.find('>').ok_or(RenjuError::Truncated)?;
+        let tag = &element[..tag_end];
+        let self_closing = tag.ends_with('/');
+
+        let body = match self_closing {
+            true => "",
+            false => {
+                let body = &element[tag_end + 1..];
+                &body[..body.find("</game>").ok_or(RenjuError::Truncated)?]
+            }
+        };
+
+        let moves = match (body.find("<move>"), body.find("</move>")) {
+            (Some(open), Some(close)) if open < close => &body[open + "<move>".len()..close],
+            _ => "",
+        };
+
+        let attributes = attributes(tag.trim_end_matches('/'));
+        let result = attributes
+            .iter()
+            .find(|(key, _)| key == "bresult")
+            .and_then(|(_, value)| match value.as_str() {
+                "1" => Some(GameState::Won(Player::Opponent)),
+                "0" => Some(GameState::Won(Player::Computer)),
+                "0.5" => Some(GameState::Draw),
+                _ => None,
+            });
+
+        games.push(RenjuGame {
+            attributes,
+            moves: moves.split_whitespace().map(parse_move).collect::<Result<_, _>>()?,
+            result,
+        });
+
+        rest = &element[tag_end + 1 + body.len()..];
+    }
+
+    Ok(games)
+}
+
+/// The `name="value"` pairs of a start tag
+fn attributes(tag: &str) -> Vec<(String, String)> {
+    let mut attributes = Vec::new();
+    let mut rest = tag;
+
+    while let Some(equals) = rest.find('=') {
+        let name = rest[..equals].trim().to_string();
+        let value = rest[equals + 1..].trim_start();
+
+        let quote = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => quote,
+            _ => break,
+        };
+
+        let value = &value[1..];
+        let end = match value.find(quote) {
+            Some(end) => end,
+            None => break,
+        };
+
+        attributes.push((name, value[..end].to_string()));
+        rest = &value[end + 1..];
+    }
+
+    attributes
+}
+
+#[cfg(test)]
+mod renju_tests {
+    use crate::goban::{Player, Position};
+    use crate::gomoku::GameState;
+    use crate::renju::{parse_move, read_renlib, read_rif, RenjuError, DOWN, RIGHT};
+
+    fn renlib(records: &[u8]) -> Vec<u8> {
+        let mut data = b"\xFFRenLib\xFF\x03\x00".to_vec();
+
+        data.resize(20, 0);
+        data.extend_from_slice(records);
+        data
+    }
+
+    #[test]
+    fn it_centres_renju_moves() {
+        assert_eq!(parse_move("h8"), Ok(Position::new(9, 9)));
+        assert_eq!(parse_move("a15"), Ok(Position::new(2, 2)));
+        assert_eq!(parse_move("p1"), Err(RenjuError::InvalidMove("p1".to_string())));
+        assert_eq!(parse_move("a16"), Err(RenjuError::InvalidMove("a16".to_string())));
+    }
+
+    #[test]
+    fn it_reads_every_line_of_a_library() {
+        // h8, then i9 (with a sibling) and j10, the sibling i7 and g9, i7 carrying a comment
+        let data = renlib(&[
+            0x78, 0x00,
+            0x69, RIGHT, 0x5A, DOWN,
+            0x89, 0x08, b'o', b'k', 0x00, 0x00, 0x67, DOWN,
+        ]);
+
+        assert_eq!(
+            read_renlib(&data),
+            Ok(vec![
+                vec![parse_move("h8").unwrap(), parse_move("i9").unwrap(), parse_move("j10").unwrap()],
+                vec![parse_move("h8").unwrap(), parse_move("i7").unwrap(), parse_move("g9").unwrap()],
+            ])
+        );
+        assert_eq!(read_renlib(b"RenLib"), Err(RenjuError::InvalidHeader));
+        assert_eq!(read_renlib(&renlib(&[0x78])), Err(RenjuError::Truncated));
+    }
+
+    #[test]
+    fn it_reads_rif_games() {
+        let rif = r#"<?xml version="1.0"?>
+            <database><games>
+                <game id="7" black="12" white="34" bresult="0.5"><move>h8 i9 j10</move></game>
+                <game id="8" bresult="1"/>
+            </games></database>"#;
+
+        let games = read_rif(rif).unwrap();
+
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].attribute("black"), Some("12"));
+        assert_eq!(games[0].moves, vec![Position::new(9, 9), Position::new(8, 10), Position::new(7, 11)]);
+        assert_eq!(games[0].result, Some(GameState::Draw));
+        assert!(games[1].moves.is_empty());
+        assert_eq!(games[1].result, Some(GameState::Won(Player::Opponent)));
+    }
+}
@@ -0,0 +1,310 @@
+use std::fmt::Write;
+
+use crate::goban::Position;
+
+/// Why a node of the search tree was not searched any further
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename_all = "lowercase"))]
+pub enum Cutoff {
+    /// A side has five, the game is over
+    Terminal,
+    /// The depth ran out, the score is the static evaluation
+    Horizon,
+    /// The transposition table gave the score, or a bound closing the window
+    Transposition,
+    /// A move scored at least beta, the opponent avoids this node
+    Beta,
+    /// A move scored at most alpha, the side to move avoids this node
+    Alpha,
+    /// Left out of the candidates the search keeps after ordering them by static evaluation
+    Pruned,
+    /// The search was stopped or ran out of nodes or time
+    Stopped,
+}
+
+impl Cutoff {
+    fn label(&self) -> &'static str {
+        match self {
+            Cutoff::Terminal => "five",
+            Cutoff::Horizon => "horizon",
+            Cutoff::Transposition => "transposition",
+            Cutoff::Beta => "beta cutoff",
+            Cutoff::Alpha => "alpha cutoff",
+            Cutoff::Pruned => "pruned",
+            Cutoff::Stopped => "stopped",
+        }
+    }
+}
+
+/// A position the search visited, or left out
+///
+/// Scores and bounds are from white's point of view, as the search computes them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TreeNode {
+    pub id: usize,
+    pub parent: Option<usize>,
+    /// Move leading to the node, `None` for the root
+    #[cfg_attr(feature = "serde", serde(rename = "move"))]
+    pub position: Option<Position>,
+    /// Plies left to search below the node
+    pub depth: usize,
+    pub alpha: isize,
+    pub beta: isize,
+    /// `None` when the search was interrupted before scoring the node
+    pub score: Option<isize>,
+    /// `None` when every candidate below the node was searched
+    pub cutoff: Option<Cutoff>,
+}
+
+/// Tree explored by the last iteration of a search, up to `limit` nodes
+///
+/// Nodes are stored parents first, the root being the first. Once the limit is reached the
+/// deeper nodes are left out and `is_truncated` tells so.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchTree {
+    limit: usize,
+    nodes: Vec<TreeNode>,
+    /// Nodes entered and not left yet, the innermost last
+    stack: Vec<usize>,
+    /// Nodes entered past the limit and not left yet
+    skipped: usize,
+    /// Nodes kept from one iteration to the next, the root and its pruned moves
+    kept: Option<usize>,
+    truncated: bool,
+}
+
+impl SearchTree {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit: limit.max(1),
+            nodes: Vec::new(),
+            stack: Vec::new(),
+            skipped: 0,
+            kept: None,
+            truncated: false,
+        }
+    }
+
+    pub fn nodes(&self) -> &[TreeNode] {
+        &self.nodes
+    }
+
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Starts the tree of a new search from its root
+    pub(crate) fn start(&mut self) {
+        self.nodes.clear();
+        self.stack.clear();
+        self.skipped = 0;
+        self.kept = None;
+        self.truncated = false;
+
+        let root = self.push(None, 0, isize::MIN, isize::MAX, None);
+
+        self.stack.push(root);
+    }
+
+    /// Drops the nodes of the previous iteration, keeping the root
+    pub(crate) fn start_iteration(&mut self, depth: usize) {
+        match self.kept {
+            Some(kept) => self.nodes.truncate(kept),
+            None => self.kept = Some(self.nodes.len()),
+        }
+
+        self.stack.truncate(1);
+        self.skipped = 0;
+        self.truncated = false;
+
+        if let Some(root) = self.nodes.first_mut() {
+            root.depth = depth;
+            root.score = None;
+            root.cutoff = None;
+        }
+    }
+
+    pub(crate) fn enter(&mut self, position: Position, depth: usize, alpha: isize, beta: isize) {
+        if self.skipped > 0 || self.nodes.len() >= self.limit {
+            self.skipped += 1;
+            self.truncated = true;
+            return;
+        }
+
+        let parent = self.stack.last().copied();
+        let id = self.push(Some(position), depth, alpha, beta, parent);
+
+        self.stack.push(id);
+    }
+
+    /// Leaves the innermost node with its score
+    pub(crate) fn exit(&mut self, score: isize) {
+        if self.skipped > 0 {
+            self.skipped -= 1;
+            return;
+        }
+
+        if let Some(id) = self.stack.pop() {
+            self.nodes[id].score = Some(score);
+        }
+    }
+
+    /// Records why the innermost node stops there
+    pub(crate) fn cut(&mut self, cutoff: Cutoff) {
+        if self.skipped > 0 {
+            return;
+        }
+
+        if let Some(&id) = self.stack.last() {
+            self.nodes[id].cutoff = Some(cutoff);
+        }
+    }
+
+    /// Records why the iteration stopped at the root
+    pub(crate) fn cut_root(&mut self, cutoff: Cutoff) {
+        if let Some(root) = self.nodes.first_mut() {
+            root.cutoff = Some(cutoff);
+        }
+    }
+
+    /// Records a candidate of the innermost node the search left out, with its static evaluation
+    pub(crate) fn prune(&mut self, position: Position, score: isize) {
+        if self.skipped > 0 || self.nodes.len() >= self.limit {
+            self.truncated = true;
+            return;
+        }
+
+        let parent = self.stack.last().copied();
+        let id = self.push(Some(position), 0, isize::MIN, isize::MAX, parent);
+
+        self.nodes[id].score = Some(score);
+        self.nodes[id].cutoff = Some(Cutoff::Pruned);
+    }
+
+    /// Scores the root once an iteration is over
+    pub(crate) fn finish_iteration(&mut self, score: isize) {
+        if let Some(root) = self.nodes.first_mut() {
+            root.score = Some(score);
+        }
+    }
+
+    fn push(&mut self, position: Option<Position>, depth: usize, alpha: isize, beta: isize, parent: Option<usize>) -> usize {
+        let id = self.nodes.len();
+
+        self.nodes.push(TreeNode {
+            id,
+            parent,
+            position,
+            depth,
+            alpha,
+            beta,
+            score: None,
+            cutoff: None,
+        });
+
+        id
+    }
+
+    /// Graphviz rendering of the tree, pruned moves dashed
+    pub fn to_dot(&self) -> String {
+        let bound = |value: isize| match value {
+            isize::MIN => "-inf".to_string(),
+            isize::MAX => "+inf".to_string(),
+            value => value.to_string(),
+        };
+        let mut dot = String::from("digraph search {\n    node [shape=box, fontname=monospace];\n");
+
+        for node in &self.nodes {
+            let name = node.position.map_or_else(|| "root".to_string(), |position| position.to_string());
+            let score = node.score.map_or_else(|| "?".to_string(), bound);
+            let mut label = match node.cutoff {
+                Some(Cutoff::Pruned) => format!("{}\\neval {}", name, score),
+                _ => format!(
+                    "{}\\ndepth {} [{}, {}]\\nscore {}",
+                    name,
+                    node.depth,
+                    bound(node.alpha),
+                    bound(node.beta),
+                    score
+                ),
+            };
+
+            if let Some(cutoff) = node.cutoff {
+                write!(label, "\\n{}", cutoff.label()).ok();
+            }
+
+            let style = match node.cutoff {
+                Some(Cutoff::Pruned) => ", style=dashed, color=gray",
+                _ => "",
+            };
+
+            writeln!(dot, "    n{} [label=\"{}\"{}];", node.id, label, style).ok();
+
+            if let Some(parent) = node.parent {
+                writeln!(dot, "    n{} -> n{};", parent, node.id).ok();
+            }
+        }
+
+        if self.truncated {
+            writeln!(dot, "    truncated [label=\"{} node limit reached\", shape=plaintext];", self.limit).ok();
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod search_tree_tests {
+    use crate::goban::Position;
+    use crate::search_tree::{Cutoff, SearchTree};
+
+    #[test]
+    fn it_links_nodes_to_their_parents() {
+        let mut tree = SearchTree::new(10);
+
+        tree.start();
+        tree.prune(Position::new(0, 0), -5);
+        tree.start_iteration(2);
+        tree.enter(Position::new(9, 9), 1, isize::MIN, isize::MAX);
+        tree.enter(Position::new(9, 10), 0, isize::MIN, isize::MAX);
+        tree.cut(Cutoff::Horizon);
+        tree.exit(12);
+        tree.cut(Cutoff::Alpha);
+        tree.exit(12);
+        tree.finish_iteration(12);
+
+        let nodes = tree.nodes();
+
+        assert_eq!(nodes.len(), 4);
+        assert_eq!(nodes[1].cutoff, Some(Cutoff::Pruned));
+        assert_eq!((nodes[2].parent, nodes[2].cutoff), (Some(0), Some(Cutoff::Alpha)));
+        assert_eq!((nodes[3].parent, nodes[3].score), (Some(2), Some(12)));
+        assert_eq!(nodes[0].score, Some(12));
+        assert!(tree.to_dot().contains("n2 -> n3;"));
+
+        // The next iteration keeps the root and its pruned moves only
+        tree.start_iteration(4);
+        assert_eq!(tree.nodes().len(), 2);
+        assert_eq!(tree.nodes()[0].depth, 4);
+    }
+
+    #[test]
+    fn it_stops_recording_at_the_limit() {
+        let mut tree = SearchTree::new(2);
+
+        tree.start();
+        tree.start_iteration(2);
+        tree.enter(Position::new(9, 9), 1, isize::MIN, isize::MAX);
+        tree.enter(Position::new(9, 10), 0, isize::MIN, isize::MAX);
+        tree.cut(Cutoff::Horizon);
+        tree.exit(3);
+        tree.exit(7);
+
+        assert_eq!(tree.nodes().len(), 2);
+        assert_eq!(tree.nodes()[1].score, Some(7));
+        assert_eq!(tree.nodes()[1].cutoff, None);
+        assert!(tree.is_truncated());
+    }
+}
@@ -0,0 +1,187 @@
+//! Games hosted by the servers, each searched from blocking threads under its own lock
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use tokio::sync::Mutex as GameLock;
+
+use crate::error::GomokuError;
+use crate::gomoku::{Gomoku, GomokuBuilder, SearchLimits};
+
+// Each game owns its caches, kept small so many games fit in memory
+const GAME_HASH_SIZE_MB: usize = 8;
+const GAME_EVAL_CACHE_SIZE_MB: usize = 8;
+
+/// Games hosted at once by default, bounding the memory of their caches
+pub const MAX_GAMES: usize = 64;
+
+/// Games untouched for this long are ended by default to make room for new ones
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+// Bounds of the limits clients ask for, so a request cannot hold a blocking thread for long
+const MAX_DEPTH: usize = 10;
+const MAX_MOVETIME: Duration = Duration::from_secs(60);
+
+/// A game's engine, locked without blocking the async workers while another request searches
+pub type Game = Arc<GameLock<Gomoku>>;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SessionError {
+    NotFound(u64),
+    /// The games in play already reach the limit
+    TooManyGames(usize),
+    Engine(GomokuError),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SessionError::NotFound(id) => write!(f, "No game {}", id),
+            SessionError::TooManyGames(limit) => write!(f, "{} games are already in play", limit),
+            SessionError::Engine(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl From<GomokuError> for SessionError {
+    fn from(error: GomokuError) -> Self {
+        SessionError::Engine(error)
+    }
+}
+
+struct Session {
+    game: Game,
+    last_used: Mutex<Instant>,
+}
+
+/// Games in play keyed by ID, clones sharing them
+#[derive(Clone)]
+pub struct Sessions {
+    games: Arc<RwLock<HashMap<u64, Session>>>,
+    next_id: Arc<AtomicU64>,
+    max_games: usize,
+    idle_timeout: Duration,
+}
+
+impl Default for Sessions {
+    fn default() -> Self {
+        Self::new(MAX_GAMES, IDLE_TIMEOUT)
+    }
+}
+
+impl Sessions {
+    pub fn new(max_games: usize, idle_timeout: Duration) -> Self {
+        Self {
+            games: Arc::default(),
+            next_id: Arc::default(),
+            max_games,
+            idle_timeout,
+        }
+    }
+
+    /// Starts a game, ending the idle ones first and failing when the limit is still reached
+    pub fn create(&self) -> Result<(u64, Game), SessionError> {
+        let mut games = self.games.write().unwrap();
+
+        // A game held elsewhere is being searched or streamed, it is not idle
+        games.retain(|_, session| {
+            Arc::strong_count(&session.game) > 1 || session.last_used.lock().unwrap().elapsed() < self.idle_timeout
+        });
+
+        if games.len() >= self.max_games {
+            return Err(SessionError::TooManyGames(self.max_games));
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let gomoku = GomokuBuilder::new()
+            .hash_size_mb(GAME_HASH_SIZE_MB)
+            .eval_cache_size_mb(GAME_EVAL_CACHE_SIZE_MB)
+            .build()?;
+        let game = Arc::new(GameLock::new(gomoku));
+
+        games.insert(id, Session { game: game.clone(), last_used: Mutex::new(Instant::now()) });
+
+        Ok((id, game))
+    }
+
+    /// The game `id`, which counts as used
+    pub fn get(&self, id: u64) -> Result<Game, SessionError> {
+        let games = self.games.read().unwrap();
+        let session = games.get(&id).ok_or(SessionError::NotFound(id))?;
+
+        *session.last_used.lock().unwrap() = Instant::now();
+
+        Ok(session.game.clone())
+    }
+
+    /// Ends the game `id`, searches running on it still finish
+    pub fn remove(&self, id: u64) -> Result<Game, SessionError> {
+        self.games.write().unwrap().remove(&id).map(|session| session.game).ok_or(SessionError::NotFound(id))
+    }
+}
+
+/// Runs the blocking engine work of one game off the async workers
+pub async fn with_game<T, E, F>(game: Game, work: F) -> Result<T, E>
+where
+    T: Send + 'static,
+    E: Send + 'static,
+    F: FnOnce(&mut Gomoku) -> Result<T, E> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || work(&mut game.blocking_lock()))
+        .await
+        .expect("engine task panicked")
+}
+
+/// `limits` held to the depth and time a client may ask for
+pub fn bounded(mut limits: SearchLimits) -> SearchLimits {
+    limits.depth = limits.depth.min(MAX_DEPTH);
+    limits.movetime = limits.movetime.map(|movetime| movetime.min(MAX_MOVETIME));
+
+    limits
+}
+
+#[cfg(test)]
+mod sessions_tests {
+    use std::time::Duration;
+
+    use crate::gomoku::SearchLimits;
+    use crate::sessions::{bounded, SessionError, Sessions};
+
+    #[test]
+    fn it_ends_idle_games_to_make_room() {
+        let sessions = Sessions::new(2, Duration::from_secs(60));
+        let (first, _) = sessions.create().unwrap();
+
+        sessions.create().unwrap();
+
+        assert_eq!(sessions.create().err(), Some(SessionError::TooManyGames(2)));
+
+        sessions.remove(first).unwrap();
+
+        assert_eq!(sessions.get(first).err(), Some(SessionError::NotFound(first)));
+        assert!(sessions.create().is_ok());
+
+        let idle = Sessions::new(1, Duration::ZERO);
+        let (id, game) = idle.create().unwrap();
+
+        // Held by a search, the game stays
+        assert!(idle.create().is_err());
+
+        drop(game);
+
+        assert!(idle.create().is_ok());
+        assert!(idle.get(id).is_err());
+    }
+
+    #[test]
+    fn it_bounds_the_limits_clients_ask_for() {
+        let limits = bounded(SearchLimits::new(40).with_movetime(Duration::from_secs(3600)));
+
+        assert_eq!(limits.depth, 10);
+        assert_eq!(limits.movetime, Some(Duration::from_secs(60)));
+        assert_eq!(bounded(SearchLimits::new(4)).depth, 4);
+    }
+}
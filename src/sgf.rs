@@ -0,0 +1,234 @@
+use std::fmt;
+
+use crate::goban::{Move, Player, Position, Stone, GOBAN_SIZE};
+use crate::gomoku::GameState;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SgfError {
+    /// The text ends inside a property value or before the game starts
+    Truncated,
+    /// `SZ` larger than the board
+    UnsupportedSize(String),
+    InvalidMove(String),
+}
+
+impl fmt::Display for SgfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SgfError::Truncated => write!(f, "The SGF game is truncated"),
+            SgfError::UnsupportedSize(size) => write!(f, "Unsupported board size `{}`", size),
+            SgfError::InvalidMove(value) => write!(f, "Invalid SGF move `{}`", value),
+        }
+    }
+}
+
+impl std::error::Error for SgfError {}
+
+/// Identifiers and values of the properties of a node
+pub type Properties = Vec<(String, String)>;
+
+/// Main line of an SGF game, such as `(;GM[4]SZ[15];B[hh];W[ii])`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SgfGame {
+    /// Properties of the root node, such as `PB`, `PW` and `RE`
+    pub properties: Properties,
+    pub moves: Vec<Move>,
+    /// Other properties of the node of each move, such as its comment `C`
+    pub move_properties: Vec<Properties>,
+    /// From the `RE` property, Black being the opponent
+    pub result: Option<GameState>,
+}
+
+impl SgfGame {
+    pub fn property(&self, name: &str) -> Option<&str> {
+        self.properties
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Reads the first game of an SGF collection, following the first variation of each node
+///
+/// Smaller boards are centred on the goban, their moves being `aa` at the top left.
+pub fn read_sgf(text: &str) -> Result<SgfGame, SgfError> {
+    let start = text.find("(;").ok_or(SgfError::Truncated)?;
+    let mut chars = text[start..].chars().peekable();
+    let mut nodes: Vec<Properties> = Vec::new();
+    let mut identifier = String::new();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ';' => nodes.push(Vec::new()),
+            // The first variation to close ends the main line
+            ')' => break,
+            'A'..='Z' => identifier.push(c),
+            '[' => {
+                let mut value = String::new();
+
+                loop {
+                    match chars.next().ok_or(SgfError::Truncated)? {
+                        '\\' => value.push(chars.next().ok_or(SgfError::Truncated)?),
+                        ']' => break,
+                        c => value.push(c),
+                    }
+                }
+
+                // Further values of a property repeat its identifier
+                let name = match identifier.is_empty() {
+                    true => nodes.last().and_then(|node| node.last()).map(|(name, _)| name.clone()),
+                    false => Some(std::mem::take(&mut identifier)),
+                };
+
+                if let (Some(node), Some(name)) = (nodes.last_mut(), name) {
+                    node.push((name, value));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let properties = match nodes.first() {
+        Some(root) => root.clone(),
+        None => return Err(SgfError::Truncated),
+    };
+
+    let size = match properties.iter().find(|(name, _)| name == "SZ") {
+        Some((_, value)) => match value.trim().parse::<usize>() {
+            Ok(size @ 1..=GOBAN_SIZE) => size,
+            _ => return Err(SgfError::UnsupportedSize(value.clone())),
+        },
+        None => GOBAN_SIZE,
+    };
+    let offset = (GOBAN_SIZE - size) / 2;
+
+    // Nodes of the moves, a node holding a single move
+    let move_nodes: Vec<(Stone, &String, &Properties)> = nodes
+        .iter()
+        .skip(1)
+        .filter_map(|node| {
+            node.iter().find_map(|(name, value)| match name.as_str() {
+                "B" => Some((Stone::Black, value, node)),
+                "W" => Some((Stone::White, value, node)),
+                _ => None,
+            })
+        })
+        .collect();
+    let move_properties = move_nodes
+        .iter()
+        .map(|(_, _, node)| node.iter().filter(|(name, _)| name != "B" && name != "W").cloned().collect())
+        .collect();
+    let moves = move_nodes
+        .iter()
+        .map(|&(stone, value, _)| {
+            let mut coordinates = value.bytes().map(|c| c.wrapping_sub(b'a') as usize);
+
+            match (coordinates.next(), coordinates.next(), coordinates.next()) {
+                (Some(col), Some(row), None) if col < size && row < size => {
+                    Ok(Move::new(stone, Position::new(row + offset, col + offset)))
+                }
+                // Gomoku has no pass
+                _ => Err(SgfError::InvalidMove(value.clone())),
+            }
+        })
+        .collect::<Result<_, _>>()?;
+
+    let result = properties
+        .iter()
+        .find(|(name, _)| name == "RE")
+        .and_then(|(_, value)| match value.as_str() {
+            result if result.starts_with("B+") => Some(GameState::Won(Player::Opponent)),
+            result if result.starts_with("W+") => Some(GameState::Won(Player::Computer)),
+            "0" | "Draw" => Some(GameState::Draw),
+            _ => None,
+        });
+
+    Ok(SgfGame {
+        properties,
+        moves,
+        move_properties,
+        result,
+    })
+}
+
+fn write_properties(f: &mut fmt::Formatter, properties: &[(String, String)]) -> fmt::Result {
+    for (name, value) in properties {
+        write!(f, "{}[{}]", name, value.replace('\\', "\\\\").replace(']', "\\]"))?;
+    }
+
+    Ok(())
+}
+
+/// Writes the game on a full board, `read_sgf` reading it back
+impl fmt::Display for SgfGame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(;")?;
+        write_properties(f, &self.properties)?;
+
+        for (index, played) in self.moves.iter().enumerate() {
+            let (col, row) = ((b'a' + played.position.col as u8) as char, (b'a' + played.position.row as u8) as char);
+            let color = match played.stone {
+                Stone::Black => 'B',
+                Stone::White => 'W',
+            };
+
+            write!(f, "\n;{}[{}{}]", color, col, row)?;
+            write_properties(f, self.move_properties.get(index).map_or(&[][..], Vec::as_slice))?;
+        }
+
+        writeln!(f, ")")
+    }
+}
+
+#[cfg(test)]
+mod sgf_tests {
+    use crate::goban::{Move, Player, Position, Stone};
+    use crate::gomoku::GameState;
+    use crate::sgf::{read_sgf, SgfError, SgfGame};
+
+    #[test]
+    fn it_reads_the_main_line() {
+        let game = read_sgf(
+            "(;GM[4]FF[4]SZ[15]PB[Black \\] player]RE[B+5]\n;B[hh];W[ii](;B[jj];W[gg])(;B[aa]))",
+        )
+        .unwrap();
+
+        assert_eq!(game.property("PB"), Some("Black ] player"));
+        assert_eq!(game.result, Some(GameState::Won(Player::Opponent)));
+        assert_eq!(
+            game.moves,
+            vec![
+                Move::new(Stone::Black, Position::new(9, 9)),
+                Move::new(Stone::White, Position::new(10, 10)),
+                Move::new(Stone::Black, Position::new(11, 11)),
+                Move::new(Stone::White, Position::new(8, 8)),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_writes_games_it_reads_back() {
+        let game = SgfGame {
+            properties: vec![("GM".to_string(), "4".to_string()), ("SZ".to_string(), "19".to_string())],
+            moves: vec![
+                Move::new(Stone::Black, Position::new(9, 9)),
+                Move::new(Stone::White, Position::new(0, 18)),
+            ],
+            move_properties: vec![vec![], vec![("C".to_string(), "corner [sic]".to_string())]],
+            result: None,
+        };
+        let text = game.to_string();
+
+        assert!(text.starts_with("(;GM[4]SZ[19]\n;B[jj]\n;W[sa]C[corner [sic\\]]"));
+        assert_eq!(read_sgf(&text), Ok(game));
+    }
+
+    #[test]
+    fn it_rejects_invalid_games() {
+        assert_eq!(read_sgf("GM[4]"), Err(SgfError::Truncated));
+        assert_eq!(read_sgf("(;SZ[15];B[hh"), Err(SgfError::Truncated));
+        assert_eq!(read_sgf("(;SZ[21])"), Err(SgfError::UnsupportedSize("21".to_string())));
+        assert_eq!(read_sgf("(;SZ[15];B[])"), Err(SgfError::InvalidMove(String::new())));
+        assert_eq!(read_sgf("(;SZ[15];B[pp])"), Err(SgfError::InvalidMove("pp".to_string())));
+    }
+}
@@ -0,0 +1,126 @@
+use std::fmt;
+
+use crate::goban::{ParseError, Position};
+
+/// Why a line of a test suite cannot be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SuiteError {
+    InvalidMove { line: usize, error: ParseError },
+    UnknownOperation { line: usize, operation: String },
+    /// The position has neither a `bm` nor an `am` operation
+    NoExpectedMove(usize),
+}
+
+impl fmt::Display for SuiteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SuiteError::InvalidMove { line, error } => write!(f, "Line {}: {}", line, error),
+            SuiteError::UnknownOperation { line, operation } => {
+                write!(f, "Line {}: unknown operation `{}`, expected bm, am or id", line, operation)
+            }
+            SuiteError::NoExpectedMove(line) => write!(f, "Line {}: the position has no bm or am operation", line),
+        }
+    }
+}
+
+impl std::error::Error for SuiteError {}
+
+/// A position of a tactical test suite with the moves that solve it
+///
+/// Written on one line as the moves played by alternating sides from black, then
+/// `;` separated operations as in EPD: `h8 h9 i8 j8 g8; bm f8 k8; id "open four";`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuitePosition {
+    pub id: Option<String>,
+    pub moves: Vec<Position>,
+    /// Any of these solves the position
+    pub best_moves: Vec<Position>,
+    /// None of these may be played
+    pub avoid_moves: Vec<Position>,
+}
+
+impl SuitePosition {
+    pub fn is_solved_by(&self, position: Position) -> bool {
+        (self.best_moves.is_empty() || self.best_moves.contains(&position)) && !self.avoid_moves.contains(&position)
+    }
+}
+
+fn parse_moves(moves: &str, line: usize) -> Result<Vec<Position>, SuiteError> {
+    moves
+        .split_whitespace()
+        .map(|position| Position::from_coordinates(position).map_err(|error| SuiteError::InvalidMove { line, error }))
+        .collect()
+}
+
+/// Reads a test suite, one position per line, skipping blank lines and `#` comments
+pub fn read_suite(text: &str) -> Result<Vec<SuitePosition>, SuiteError> {
+    let mut positions = Vec::new();
+
+    for (index, content) in text.lines().enumerate().map(|(index, content)| (index + 1, content.trim())) {
+        if content.is_empty() || content.starts_with('#') {
+            continue;
+        }
+
+        let mut fields = content.split(';');
+        let mut position = SuitePosition {
+            id: None,
+            moves: parse_moves(fields.next().unwrap_or_default(), index)?,
+            best_moves: Vec::new(),
+            avoid_moves: Vec::new(),
+        };
+
+        for field in fields.map(str::trim).filter(|field| !field.is_empty()) {
+            let (operation, operand) = field.split_once(char::is_whitespace).unwrap_or((field, ""));
+
+            match operation {
+                "bm" => position.best_moves.extend(parse_moves(operand, index)?),
+                "am" => position.avoid_moves.extend(parse_moves(operand, index)?),
+                "id" => position.id = Some(operand.trim().trim_matches('"').to_string()),
+                _ => {
+                    return Err(SuiteError::UnknownOperation {
+                        line: index,
+                        operation: operation.to_string(),
+                    })
+                }
+            }
+        }
+
+        if position.best_moves.is_empty() && position.avoid_moves.is_empty() {
+            return Err(SuiteError::NoExpectedMove(index));
+        }
+
+        positions.push(position);
+    }
+
+    Ok(positions)
+}
+
+#[cfg(test)]
+mod suite_tests {
+    use crate::goban::Position;
+    use crate::suite::{read_suite, SuiteError};
+
+    #[test]
+    fn it_reads_positions_with_their_expected_moves() {
+        let suite = read_suite("# Fours\nJ10 A1 K10 A2 L10 A3; bm H10 M10; id \"open three\";\n\nJ10 K10; am A1;\n").unwrap();
+
+        assert_eq!(suite.len(), 2);
+        assert_eq!(suite[0].id.as_deref(), Some("open three"));
+        assert_eq!(suite[0].moves.len(), 6);
+        assert!(suite[0].is_solved_by(Position::from_coordinates("M10").unwrap()));
+        assert!(!suite[0].is_solved_by(Position::from_coordinates("N10").unwrap()));
+        assert_eq!(suite[1].id, None);
+        assert!(suite[1].is_solved_by(Position::from_coordinates("J11").unwrap()));
+        assert!(!suite[1].is_solved_by(Position::from_coordinates("A1").unwrap()));
+    }
+
+    #[test]
+    fn it_rejects_invalid_lines() {
+        assert_eq!(read_suite("J10\nJ10 K10\n"), Err(SuiteError::NoExpectedMove(1)));
+        assert!(matches!(read_suite("J10; bm Z99"), Err(SuiteError::InvalidMove { line: 1, .. })));
+        assert!(matches!(
+            read_suite("\nJ10; dm 3"),
+            Err(SuiteError::UnknownOperation { line: 2, .. })
+        ));
+    }
+}
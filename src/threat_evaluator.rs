@@ -1,12 +1,14 @@
 use std::cmp::Ordering;
+use std::sync::OnceLock;
 use bitvec::prelude::*;
 
 use crate::evaluator::{Eval, Evaluator};
-use crate::goban::{BIT_SIZE, Bitboard, Direction, GOBAN_SIZE};
+use crate::goban::{BIT_SIZE, Bitboard, Direction, GOBAN_SIZE, Goban, Position, Stone};
 
 #[repr(isize)]
 #[derive(PartialEq, Debug, Copy, Clone)]
-enum Threat {
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename_all = "camelCase"))]
+pub enum Threat {
     Five,
     StraightFour = 500000,
     Four = 50000,
@@ -19,6 +21,25 @@ type PatternSlice = BitSlice<Msb0, u8>;
 
 type PatternWithLength = (Pattern, Pattern, usize);
 
+/// A threat on the board, as the evaluator scores it
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LineThreat {
+    pub stone: Stone,
+    pub threat: Threat,
+    /// Stones forming the threat
+    pub stones: Vec<Position>,
+    /// Empty intersections of the threat's patterns, where it grows or is blocked
+    pub empty: Vec<Position>,
+}
+
+// Axes in the order the evaluation scans them
+const EVALUATED_AXES: [Direction; 4] = [Direction::East, Direction::South, Direction::SouthWest, Direction::SouthEast];
+
+// Row and column steps along East, South, SouthEast and SouthWest
+const AXES: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
+#[derive(Clone)]
 pub struct ThreatEvaluator {
     threat_cache: [[Option<Option<Threat>>; 255]; 3],
 }
@@ -44,6 +65,12 @@ impl Evaluator for ThreatEvaluator {
     }
 }
 
+impl Default for ThreatEvaluator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ThreatEvaluator {
     pub fn new() -> Self {
         Self {
@@ -54,12 +81,7 @@ impl ThreatEvaluator {
     fn evaluate_player(&mut self, player: &Bitboard, opponent: &Bitboard) -> Eval {
         let mut total_score: isize = 0;
 
-        for axis in [
-            Direction::East,
-            Direction::South,
-            Direction::SouthWest,
-            Direction::SouthEast,
-        ] {
+        for axis in EVALUATED_AXES {
             let eval = self.evaluate_axis(player, opponent, axis);
 
             match eval {
@@ -79,7 +101,7 @@ impl ThreatEvaluator {
         for index in 0..BIT_SIZE+1 {
             if let Some(
                 (player_full_pattern, opponent_full_pattern, max_length)
-            ) = self.extract_pattern(player, opponent, index as isize, axis) {
+            ) = Self::extract_pattern(player, opponent, index as isize, axis) {
                 no_threats = true;
 
                 for (player_pattern, opponent_pattern, is_player) in [
@@ -132,6 +154,84 @@ impl ThreatEvaluator {
         Eval::Score(score)
     }
 
+    /// Threats of both sides, matched with the evaluator's patterns
+    ///
+    /// Windows over the same stones are merged into their strongest threat, so an
+    /// open four is reported once rather than also as two fours.
+    pub fn threats(goban: &Goban) -> Vec<LineThreat> {
+        let mut threats: Vec<LineThreat> = Vec::new();
+
+        for stone in [Stone::Black, Stone::White] {
+            for row in 0..GOBAN_SIZE as isize {
+                for col in 0..GOBAN_SIZE as isize {
+                    for (row_step, col_step) in AXES {
+                        let window = |length: isize| -> Option<Vec<Position>> {
+                            let (end_row, end_col) = (row + row_step * (length - 1), col + col_step * (length - 1));
+
+                            if !(0..GOBAN_SIZE as isize).contains(&end_row) || !(0..GOBAN_SIZE as isize).contains(&end_col) {
+                                return None;
+                            }
+
+                            Some(
+                                (0..length)
+                                    .map(|step| Position::new((row + row_step * step) as usize, (col + col_step * step) as usize))
+                                    .collect(),
+                            )
+                        };
+
+                        let found = [7, 6, 5].iter().filter_map(|&length| window(length)).find_map(|positions| {
+                            let stones: Vec<Option<Stone>> =
+                                positions.iter().map(|position| goban.get(position.row, position.col)).collect();
+
+                            if stones.contains(&Some(stone.opponent())) {
+                                return None;
+                            }
+
+                            let mut pattern = Pattern::zeroed();
+
+                            for (index, intersection) in stones.iter().enumerate() {
+                                pattern.set(index, intersection.is_some());
+                            }
+
+                            let threat = Self::compute_threat(&pattern[0..positions.len()], positions.len())?;
+                            let (stones, empty) = positions
+                                .iter()
+                                .partition(|position| goban.get(position.row, position.col).is_some());
+
+                            Some(LineThreat { stone, threat, stones, empty })
+                        });
+
+                        if let Some(found) = found {
+                            Self::merge_threat(&mut threats, found);
+                        }
+                    }
+                }
+            }
+        }
+
+        threats
+    }
+
+    fn merge_threat(threats: &mut Vec<LineThreat>, found: LineThreat) {
+        let strength = |threat: Threat| match threat {
+            Threat::Five => isize::MAX,
+            threat => threat as isize,
+        };
+
+        match threats.iter_mut().find(|threat| threat.stones == found.stones) {
+            Some(threat) => {
+                if strength(found.threat) > strength(threat.threat) {
+                    threat.threat = found.threat;
+                }
+
+                threat.empty.extend(found.empty);
+                threat.empty.sort_by_key(Position::index);
+                threat.empty.dedup();
+            }
+            None => threats.push(found),
+        }
+    }
+
     fn get_strongest_threat(current: Option<Threat>, new: Threat) -> Threat {
         match current {
             None => new,
@@ -142,8 +242,22 @@ impl ThreatEvaluator {
         }
     }
 
+    /// Windows extracted along every line of the board, as `evaluate` does before matching them
+    ///
+    /// Only exposed so benchmarks can time the extraction apart from the rest of the evaluation.
+    #[doc(hidden)]
+    pub fn extract_patterns(&self, player: &Bitboard, opponent: &Bitboard) -> usize {
+        EVALUATED_AXES
+            .iter()
+            .map(|&axis| {
+                (0..BIT_SIZE + 1)
+                    .filter(|&index| Self::extract_pattern(player, opponent, index as isize, axis).is_some())
+                    .count()
+            })
+            .sum()
+    }
+
     fn extract_pattern(
-        &self,
         player: &Bitboard,
         opponent: &Bitboard,
         index: isize,
@@ -151,45 +265,44 @@ impl ThreatEvaluator {
     ) -> Option<PatternWithLength> {
         [7, 6, 5]
             .iter()
-            .find_map(|length| self.extract_pattern_length(player, opponent, index, axis, *length))
+            .find_map(|length| Self::extract_pattern_length(player, opponent, index, axis, *length))
     }
 
     fn extract_pattern_length(
-        &self,
         player: &Bitboard,
         opponent: &Bitboard,
         index: isize,
         axis: Direction,
         length: usize,
     ) -> Option<PatternWithLength> {
-        let mut player_pattern: Pattern = bitarr![Msb0, u8; 0];
-        let mut opponent_pattern: Pattern = bitarr![Msb0, u8; 0];
+        let mut player_pattern = Pattern::zeroed();
+        let mut opponent_pattern = Pattern::zeroed();
 
-        if !self.is_extractable(index, axis, length) {
+        if !Self::is_extractable(index, axis, length) {
             return None;
         }
 
         for pattern_index in 0..length {
             player_pattern.set(
                 pattern_index,
-                player[(index + (axis as isize * pattern_index as isize)) as usize] as bool,
+                player[(index + (axis as isize * pattern_index as isize)) as usize],
             );
             opponent_pattern.set(
                 pattern_index,
-                opponent[(index + (axis as isize * pattern_index as isize)) as usize] as bool,
+                opponent[(index + (axis as isize * pattern_index as isize)) as usize],
             );
         }
 
         Some((player_pattern, opponent_pattern, length))
     }
 
-    fn is_extractable(&self, index: isize, axis: Direction, length: usize) -> bool {
+    fn is_extractable(index: isize, axis: Direction, length: usize) -> bool {
         let end: isize = index + (axis as isize * length as isize);
 
-        self.is_inbound(index) && self.is_inbound(end)
+        Self::is_inbound(index) && Self::is_inbound(end)
     }
 
-    fn is_inbound(&self, index: isize) -> bool {
+    fn is_inbound(index: isize) -> bool {
         index >= 0 && index < BIT_SIZE as isize && (index == 0 || index % GOBAN_SIZE as isize != 0)
     }
 
@@ -259,20 +372,208 @@ impl ThreatEvaluator {
     }
 }
 
+/// Threat matched by every window length and pattern byte, as `match_threat` caches them
+fn threat_table() -> &'static [[Option<Threat>; 256]; 3] {
+    static TABLE: OnceLock<[[Option<Threat>; 256]; 3]> = OnceLock::new();
+
+    TABLE.get_or_init(|| {
+        let mut table = [[None; 256]; 3];
+
+        for (group, threats) in table.iter_mut().enumerate() {
+            for (byte, threat) in threats.iter_mut().enumerate() {
+                let pattern = Pattern::new([byte as u8]);
+
+                *threat = ThreatEvaluator::compute_threat(&pattern[0..group + 5], group + 5);
+            }
+        }
+
+        table
+    })
+}
+
+// Lines of the board: rows, columns, then the diagonals going down to the right and to the left
+const ROWS: usize = 0;
+const COLUMNS: usize = ROWS + GOBAN_SIZE;
+const DIAGONALS: usize = COLUMNS + GOBAN_SIZE;
+const ANTI_DIAGONALS: usize = DIAGONALS + 2 * GOBAN_SIZE - 1;
+const LINES: usize = ANTI_DIAGONALS + 2 * GOBAN_SIZE - 1;
+
+/// Threats of one line, scored for black
+#[derive(Clone, Copy, Default)]
+struct LineScore {
+    score: i32,
+    five: Option<Stone>,
+}
+
+/// Score of every line of the board, kept by the board as its stones change
+///
+/// A stone only changes the four lines crossing it, which are scored again, and evaluating sums
+/// the scores of the lines. Each line is scored on its own, the windows along it matched like
+/// `ThreatEvaluator` does and the threats of consecutive windows counted once, by the strongest.
+#[derive(Clone)]
+pub struct LineThreats {
+    lines: [LineScore; LINES],
+}
+
+impl Default for LineThreats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LineThreats {
+    /// Lines of the empty board
+    pub fn new() -> Self {
+        Self {
+            lines: [LineScore::default(); LINES],
+        }
+    }
+
+    /// Scores again the lines crossing the intersection at `index`, after its stone changed
+    pub fn update(&mut self, black: &Bitboard, white: &Bitboard, index: usize) {
+        let (row, col) = (index / (GOBAN_SIZE + 1), index % (GOBAN_SIZE + 1));
+
+        for line in [
+            ROWS + row,
+            COLUMNS + col,
+            DIAGONALS + col + GOBAN_SIZE - 1 - row,
+            ANTI_DIAGONALS + row + col,
+        ] {
+            self.lines[line] = Self::score_line(black, white, line);
+        }
+    }
+
+    /// Evaluation of `stone`'s side, its threats less the other side's
+    pub fn evaluate(&self, stone: Stone) -> Eval {
+        let mut score: isize = 0;
+        let mut lost = false;
+
+        for line in self.lines.iter() {
+            match line.five {
+                Some(five) if five == stone => return Eval::Won,
+                Some(_) => lost = true,
+                None => score += line.score as isize,
+            }
+        }
+
+        match (lost, stone) {
+            (true, _) => Eval::Lost,
+            (false, Stone::Black) => Eval::Score(score),
+            (false, Stone::White) => Eval::Score(-score),
+        }
+    }
+
+    /// Index of the first intersection of `line`, the step to the next one and how many it has
+    fn cells(line: usize) -> (usize, usize, usize) {
+        let last = GOBAN_SIZE - 1;
+        let index = |row: usize, col: usize| row * (GOBAN_SIZE + 1) + col;
+
+        match line {
+            line if line < COLUMNS => (index(line - ROWS, 0), Direction::East as usize, GOBAN_SIZE),
+            line if line < DIAGONALS => (index(0, line - COLUMNS), Direction::South as usize, GOBAN_SIZE),
+            line if line < ANTI_DIAGONALS => {
+                // Numbered by column less row, from the bottom left corner
+                let offset = line - DIAGONALS;
+                let start = match offset < last {
+                    true => index(last - offset, 0),
+                    false => index(0, offset - last),
+                };
+
+                (start, Direction::SouthEast as usize, GOBAN_SIZE - offset.abs_diff(last))
+            }
+            line => {
+                // Numbered by row plus column, from the top left corner
+                let sum = line - ANTI_DIAGONALS;
+                let start = match sum < last {
+                    true => index(0, sum),
+                    false => index(sum - last, last),
+                };
+
+                (start, Direction::SouthWest as usize, GOBAN_SIZE - sum.abs_diff(last))
+            }
+        }
+    }
+
+    fn score_line(black: &Bitboard, white: &Bitboard, line: usize) -> LineScore {
+        let (start, step, length) = Self::cells(line);
+        let (mut black_stones, mut white_stones) = (0u32, 0u32);
+
+        for cell in 0..length {
+            black_stones |= (black[start + step * cell] as u32) << cell;
+            white_stones |= (white[start + step * cell] as u32) << cell;
+        }
+
+        let mut line_score = LineScore::default();
+        let mut strongest_continuous_threat: Option<(Threat, Stone)> = None;
+
+        for window in 0..(length + 1).saturating_sub(5) {
+            match Self::window(black_stones >> window, white_stones >> window, length - window) {
+                Some((Threat::Five, stone)) => {
+                    line_score.five = Some(stone);
+                    return line_score;
+                }
+                Some((threat, stone)) => {
+                    strongest_continuous_threat = Some((
+                        ThreatEvaluator::get_strongest_threat(strongest_continuous_threat.map(|st| st.0), threat),
+                        stone,
+                    ));
+                }
+                None => Self::count(&mut line_score, strongest_continuous_threat.take()),
+            }
+        }
+
+        Self::count(&mut line_score, strongest_continuous_threat);
+
+        line_score
+    }
+
+    fn count(line_score: &mut LineScore, threat: Option<(Threat, Stone)>) {
+        match threat {
+            Some((threat, Stone::Black)) => line_score.score += threat as i32,
+            Some((threat, Stone::White)) => line_score.score -= threat as i32,
+            None => {}
+        }
+    }
+
+    /// Threat either side matches in the window starting at the first intersection of `black` and
+    /// `white`, seven intersections long when `left` allows it, like `ThreatEvaluator::evaluate_axis`
+    fn window(black: u32, white: u32, left: usize) -> Option<(Threat, Stone)> {
+        let max_length = left.min(7);
+
+        // Both sides never match the same window, a threat needs the first five free of the other
+        for (stone, player, opponent) in [(Stone::Black, black, white), (Stone::White, white, black)] {
+            for length in (5..max_length + 1).rev() {
+                let mask = (1 << length) - 1;
+
+                if opponent & mask != 0 {
+                    continue;
+                }
+
+                // Patterns hold the first intersection in their highest bit
+                let pattern = ((player & mask) as u8).reverse_bits();
+
+                if let Some(threat) = threat_table()[length - 5][pattern as usize] {
+                    return Some((threat, stone));
+                }
+            }
+        }
+
+        None
+    }
+}
+
 #[cfg(test)]
 mod evaluator_tests {
-    use std::rc::Rc;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
     use crate::evaluator::{Eval};
-    use crate::goban::{Goban, Move, Player, Position, Stone, WIN_MINIMUM_LINE_SIZE};
-    use crate::threat_evaluator::ThreatEvaluator;
-    use crate::zobrist_hashing::ZobristHasher;
+    use crate::goban::{Goban, Move, Player, Position, Stone, GOBAN_SIZE, WIN_MINIMUM_LINE_SIZE};
+    use crate::threat_evaluator::{LineThreats, Threat, ThreatEvaluator};
 
     #[test]
     fn it_correctly_detects_win() {
         let mut evaluator = ThreatEvaluator::new();
-        let hasher = Rc::new(ZobristHasher::initialize());
-
-        let mut board = Goban::new(Rc::clone(&hasher));
+        let mut board = Goban::new();
 
         for i in 0..WIN_MINIMUM_LINE_SIZE {
             board.apply_move(Move::new(Stone::White, Position::new(0, i)));
@@ -282,7 +583,7 @@ mod evaluator_tests {
 
         assert_eq!(eval, Eval::Won);
 
-        board = Goban::new(Rc::clone(&hasher));
+        board = Goban::new();
 
         for i in 0..WIN_MINIMUM_LINE_SIZE {
             board.apply_move(Move::new(Stone::Black, Position::new(i, 0)));
@@ -292,7 +593,7 @@ mod evaluator_tests {
 
         assert_eq!(eval, Eval::Lost);
 
-        board = Goban::new(Rc::clone(&hasher));
+        board = Goban::new();
 
         board.apply_move(Move::new(Stone::Black, Position::new(3, 3)));
         board.apply_move(Move::new(Stone::Black, Position::new(4, 4)));
@@ -304,6 +605,101 @@ mod evaluator_tests {
 
         assert_eq!(eval, Eval::Lost);
     }
+
+    #[test]
+    fn it_lists_threats_of_both_sides() {
+        let mut board = Goban::new();
+
+        for col in 5..9 {
+            board.apply_move(Move::new(Stone::Black, Position::new(9, col)));
+        }
+        for row in 3..6 {
+            board.apply_move(Move::new(Stone::White, Position::new(row, 14)));
+        }
+
+        let threats = board.threats();
+
+        assert_eq!(threats.len(), 2);
+        assert_eq!(threats[0].stone, Stone::Black);
+        assert_eq!(threats[0].threat, Threat::StraightFour);
+        assert_eq!(threats[0].empty, vec![Position::new(9, 4), Position::new(9, 9)]);
+        assert_eq!(threats[1].stone, Stone::White);
+        assert_eq!(threats[1].threat, Threat::Three);
+        assert_eq!(threats[1].stones, vec![Position::new(3, 14), Position::new(4, 14), Position::new(5, 14)]);
+
+        board.apply_move(Move::new(Stone::White, Position::new(9, 9)));
+
+        assert_eq!(board.threats()[0].threat, Threat::Four);
+        assert_eq!(board.threats()[0].empty, vec![Position::new(9, 4)]);
+    }
+
+    #[test]
+    fn it_keeps_the_line_scores_up_to_date() {
+        let mut rng = StdRng::seed_from_u64(11);
+
+        for _ in 0..10 {
+            let mut board = Goban::new();
+
+            for ply in 0..80 {
+                let position = Position::new(rng.gen_range(0..GOBAN_SIZE), rng.gen_range(0..GOBAN_SIZE));
+
+                // Some stones are taken back, the lines crossing them must be scored again
+                match ply % 5 {
+                    4 => {
+                        board.clear(&position);
+                    }
+                    _ => board.apply_move(Move::new(board.side_to_move(), position)),
+                }
+
+                let mut scored = LineThreats::new();
+
+                for index in (0..GOBAN_SIZE * GOBAN_SIZE).map(|cell| Position::new(cell / GOBAN_SIZE, cell % GOBAN_SIZE).index()) {
+                    scored.update(board.stones(Stone::Black), board.stones(Stone::White), index);
+                }
+
+                for player in [Player::Computer, Player::Opponent] {
+                    assert_eq!(board.evaluate_lines(player), scored.evaluate(player.into()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn it_scores_the_threats_of_every_line() {
+        let lines = [
+            [(9, 8), (9, 9), (9, 10)],
+            [(2, 4), (3, 4), (4, 4)],
+            [(10, 3), (11, 4), (12, 5)],
+            [(3, 15), (4, 14), (5, 13)],
+        ];
+
+        for line in lines {
+            let mut board = Goban::new();
+
+            for (row, col) in line {
+                board.apply_move(Move::new(Stone::Black, Position::new(row, col)));
+            }
+
+            assert_eq!(board.evaluate_lines(Player::Opponent), Eval::Score(Threat::Three as isize));
+            assert_eq!(board.evaluate_lines(Player::Computer), Eval::Score(-(Threat::Three as isize)));
+        }
+
+        // Threats on two lines count twice, however close their windows are on the board
+        let mut board = Goban::new();
+
+        for (row, col) in [(0, 14), (0, 15), (0, 16), (1, 2), (1, 3), (1, 4)] {
+            board.apply_move(Move::new(Stone::White, Position::new(row, col)));
+        }
+
+        assert_eq!(board.evaluate_lines(Player::Computer), Eval::Score(2 * Threat::Three as isize));
+
+        for col in 5..7 {
+            board.apply_move(Move::new(Stone::White, Position::new(1, col)));
+        }
+
+        assert_eq!(board.evaluate_lines(Player::Computer), Eval::Won);
+        assert_eq!(board.evaluate_lines(Player::Opponent), Eval::Lost);
+    }
 }
 
 #[cfg(test)]
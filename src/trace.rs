@@ -0,0 +1,31 @@
+//! Search instrumentation, compiled to nothing without the `tracing` feature
+
+#[cfg(feature = "tracing")]
+macro_rules! trace_span {
+    ($level:ident, $($arg:tt)*) => {
+        tracing::span!(tracing::Level::$level, $($arg)*).entered()
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace_span {
+    ($level:ident, $($arg:tt)*) => {
+        $crate::trace::NoSpan
+    };
+}
+
+#[cfg(feature = "tracing")]
+macro_rules! trace_event {
+    ($level:ident, $($arg:tt)*) => {
+        tracing::event!(tracing::Level::$level, $($arg)*)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace_event {
+    ($level:ident, $($arg:tt)*) => {};
+}
+
+/// Stands for an entered span when tracing is disabled
+#[cfg(not(feature = "tracing"))]
+pub struct NoSpan;
@@ -1,7 +1,557 @@
-use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::mem::size_of;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
 
 use crate::evaluator::Eval;
-use crate::goban::Player;
+use crate::goban::Position;
 use crate::zobrist_hashing::ZobristHash;
 
-pub type TranspositionTable = HashMap<(ZobristHash, Player), Eval>;
\ No newline at end of file
+pub const DEFAULT_SIZE_MB: usize = 32;
+
+const NO_MOVE: u16 = u16::MAX;
+
+// Scores are stored on 32 bits, the extremes encoding wins and losses
+const WON_SCORE: i32 = i32::MAX;
+const LOST_SCORE: i32 = i32::MIN;
+
+const FILE_MAGIC: &[u8; 8] = b"GMKTT\0\0\x02";
+
+const BOUND_MASK: u8 = 0b0000_0011;
+const OCCUPIED_FLAG: u8 = 0b0000_1000;
+const GENERATION_SHIFT: u8 = 4;
+
+/// How a new entry chooses the slot it overwrites
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplacementPolicy {
+    /// One slot per index, the newest entry always wins
+    AlwaysReplace,
+    /// Buckets of a depth-preferred slot, only overwritten by deeper or equal searches
+    /// or when left by a previous search, and an always-replace slot catching everything else
+    TwoTier,
+}
+
+/// How the stored score relates to the true value of the position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    Exact = 0,
+    Lower = 1,
+    Upper = 2,
+}
+
+/// 16 bytes table entry
+///
+/// `check` is the upper half of the 128 bits hash, the lower half picking the slot,
+/// so a wrong position is only returned if it matches on 64 bits plus the index bits.
+/// `flags` packs the bound (2 bits), an occupied bit and the 4 low bits of the search generation.
+#[derive(Clone, Copy, Default)]
+pub struct Entry {
+    check: u64,
+    score: i32,
+    depth: u8,
+    flags: u8,
+    best_move: u16,
+}
+
+impl Entry {
+    fn new(hash: ZobristHash, depth: usize, bound: Bound, eval: Eval, best_move: Option<Position>, generation: u8) -> Self {
+        let score = match eval {
+            Eval::Won => WON_SCORE,
+            Eval::Lost => LOST_SCORE,
+            Eval::Score(n) => n.clamp(LOST_SCORE as isize + 1, WON_SCORE as isize - 1) as i32,
+        };
+
+        Self {
+            check: verification_key(hash),
+            score,
+            depth: depth.min(u8::MAX as usize) as u8,
+            flags: bound as u8 | OCCUPIED_FLAG | (generation << GENERATION_SHIFT),
+            best_move: best_move.map_or(NO_MOVE, |position| position.index() as u16),
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth as usize
+    }
+
+    pub fn bound(&self) -> Bound {
+        match self.flags & BOUND_MASK {
+            1 => Bound::Lower,
+            2 => Bound::Upper,
+            _ => Bound::Exact,
+        }
+    }
+
+    pub fn eval(&self) -> Eval {
+        match self.score {
+            WON_SCORE => Eval::Won,
+            LOST_SCORE => Eval::Lost,
+            n => Eval::Score(n as isize),
+        }
+    }
+
+    pub fn best_move(&self) -> Option<Position> {
+        match self.best_move {
+            NO_MOVE => None,
+            index => Position::from_index(index as usize),
+        }
+    }
+
+    fn is_occupied(&self) -> bool {
+        self.flags & OCCUPIED_FLAG != 0
+    }
+
+    fn generation(&self) -> u8 {
+        self.flags >> GENERATION_SHIFT
+    }
+
+    fn pack(&self) -> u64 {
+        (self.score as u32 as u64) << 32
+            | (self.depth as u64) << 24
+            | (self.flags as u64) << 16
+            | self.best_move as u64
+    }
+
+    fn unpack(check: u64, data: u64) -> Self {
+        Self {
+            check,
+            score: (data >> 32) as u32 as i32,
+            depth: (data >> 24) as u8,
+            flags: (data >> 16) as u8,
+            best_move: data as u16,
+        }
+    }
+
+    fn matches(&self, hash: ZobristHash) -> bool {
+        self.is_occupied() && self.check == verification_key(hash)
+    }
+}
+
+/// Lockless slot holding an entry in two words
+///
+/// The key is stored xored with the data, so an entry torn by concurrent writes
+/// fails the key verification instead of being returned half updated.
+#[derive(Default)]
+struct Slot {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+impl Slot {
+    fn read(&self) -> Entry {
+        let data = self.data.load(Ordering::Relaxed);
+        let key = self.key.load(Ordering::Relaxed);
+
+        Entry::unpack(key ^ data, data)
+    }
+
+    fn write(&self, entry: &Entry) {
+        let data = entry.pack();
+
+        self.key.store(entry.check ^ data, Ordering::Relaxed);
+        self.data.store(data, Ordering::Relaxed);
+    }
+}
+
+/// Fixed-size table indexed by the low bits of the zobrist hash, verified by its upper half
+///
+/// Entries hold search results from the side to move's point of view, along with the
+/// remaining depth they were searched to.
+/// An entry is only returned for a position matching its upper 64 bits and the index bits, so
+/// another position is taken for it only on a collision of all of them, which is unlikely.
+///
+/// Every operation takes `&self`, the table can be shared by several search threads.
+pub struct TranspositionTable {
+    entries: Vec<Slot>,
+    mask: usize,
+    policy: ReplacementPolicy,
+    generation: AtomicU8,
+}
+
+impl TranspositionTable {
+    /// Allocates the largest power of two number of entries fitting in `size_mb` megabytes
+    pub fn with_size_mb(size_mb: usize, policy: ReplacementPolicy) -> Self {
+        let budget = size_mb * 1024 * 1024 / size_of::<Entry>();
+        let capacity = match budget {
+            0..=2 => 2,
+            n if n.is_power_of_two() => n,
+            n => n.next_power_of_two() >> 1,
+        };
+
+        Self {
+            entries: (0..capacity).map(|_| Slot::default()).collect(),
+            mask: capacity - 1,
+            policy,
+            generation: AtomicU8::new(0),
+        }
+    }
+
+    /// Starts a new generation, entries from previous searches become the first to be evicted
+    pub fn new_search(&self) {
+        let generation = (self.generation() + 1) & (u8::MAX >> GENERATION_SHIFT);
+
+        self.generation.store(generation, Ordering::Relaxed);
+    }
+
+    fn generation(&self) -> u8 {
+        self.generation.load(Ordering::Relaxed)
+    }
+
+    pub fn clear(&self) {
+        for slot in self.entries.iter() {
+            slot.write(&Entry::default());
+        }
+    }
+
+    /// Permille of the table holding entries of the current search, estimated on its first slots
+    pub fn hashfull(&self) -> usize {
+        let sample = self.entries.len().min(1000);
+        let generation = self.generation();
+        let used = self.entries[..sample]
+            .iter()
+            .map(Slot::read)
+            .filter(|entry| entry.is_occupied() && entry.generation() == generation)
+            .count();
+
+        used * 1000 / sample
+    }
+
+    /// Deepest search result of the position searched at least `depth` plies
+    pub fn probe_search(&self, hash: ZobristHash, depth: usize) -> Option<Entry> {
+        // On equal depths the depth-preferred slot holds the freshest entry
+        self.slots(hash)
+            .filter(|entry| entry.depth() >= depth)
+            .fold(None, |best: Option<Entry>, entry| match best {
+                Some(best) if best.depth >= entry.depth => Some(best),
+                _ => Some(entry),
+            })
+    }
+
+    /// Hints the CPU to load the slots of `hash`, to be called ahead of probing it
+    pub fn prefetch(&self, hash: ZobristHash) {
+        #[cfg(target_arch = "x86_64")]
+        {
+            use std::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+
+            let index = match self.policy {
+                ReplacementPolicy::AlwaysReplace => self.index(hash),
+                ReplacementPolicy::TwoTier => self.index(hash) & !1,
+            };
+
+            // SAFETY: prefetching never faults and the pointer is in bounds anyway
+            unsafe { _mm_prefetch::<_MM_HINT_T0>(&self.entries[index] as *const Slot as *const i8) }
+        }
+
+        #[cfg(not(target_arch = "x86_64"))]
+        let _ = hash;
+    }
+
+    /// Best move found by any previous search of the position
+    pub fn probe_move(&self, hash: ZobristHash) -> Option<Position> {
+        self.probe_search(hash, 0).and_then(|entry| entry.best_move())
+    }
+
+    pub fn store(
+        &self,
+        hash: ZobristHash,
+        depth: usize,
+        bound: Bound,
+        eval: Eval,
+        best_move: Option<Position>,
+    ) {
+        let entry = Entry::new(hash, depth, bound, eval, best_move, self.generation());
+
+        self.insert(entry, self.index(hash));
+    }
+
+    /// Writes every occupied entry and its slot, tagged with the zobrist keys `fingerprint`
+    pub fn save<W: Write>(&self, writer: &mut W, fingerprint: u64) -> io::Result<()> {
+        let occupied: Vec<(usize, Entry)> = self
+            .entries
+            .iter()
+            .map(Slot::read)
+            .enumerate()
+            .filter(|(_, entry)| entry.is_occupied())
+            .collect();
+
+        writer.write_all(FILE_MAGIC)?;
+        writer.write_all(&fingerprint.to_le_bytes())?;
+        writer.write_all(&(self.entries.len() as u64).to_le_bytes())?;
+        writer.write_all(&(occupied.len() as u64).to_le_bytes())?;
+
+        for (index, entry) in occupied {
+            writer.write_all(&(index as u64).to_le_bytes())?;
+            writer.write_all(&entry.check.to_le_bytes())?;
+            writer.write_all(&entry.pack().to_le_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds the entries of a saved table, which must have been hashed with the same zobrist keys
+    ///
+    /// Loaded entries belong to the current generation and are placed as if freshly stored.
+    /// Only the low bits of the hashes survive in the file, so when loading into a larger table
+    /// each entry is copied to every slot its position could map to.
+    pub fn load<R: Read>(&self, reader: &mut R, fingerprint: u64) -> io::Result<()> {
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+
+        if &magic != FILE_MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a transposition table file"));
+        }
+
+        if read_u64(reader)? != fingerprint {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "transposition table was saved with different zobrist keys",
+            ));
+        }
+
+        let saved_capacity = read_u64(reader)? as usize;
+
+        if !saved_capacity.is_power_of_two() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid transposition table size"));
+        }
+
+        let copies = (self.entries.len() / saved_capacity).max(1);
+
+        for _ in 0..read_u64(reader)? {
+            let index = read_u64(reader)? as usize;
+            let check = read_u64(reader)?;
+            let mut entry = Entry::unpack(check, read_u64(reader)?);
+
+            entry.flags = (entry.flags & !(u8::MAX << GENERATION_SHIFT)) | (self.generation() << GENERATION_SHIFT);
+
+            for copy in 0..copies {
+                self.insert(entry, (index + copy * saved_capacity) & self.mask);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn insert(&self, entry: Entry, index: usize) {
+        match self.policy {
+            ReplacementPolicy::AlwaysReplace => self.entries[index].write(&entry),
+            ReplacementPolicy::TwoTier => {
+                let bucket = index & !1;
+                let deep = self.entries[bucket].read();
+
+                let preferred = !deep.is_occupied()
+                    || deep.generation() != self.generation()
+                    || deep.depth <= entry.depth
+                    || deep.check == entry.check;
+
+                self.entries[if preferred { bucket } else { bucket + 1 }].write(&entry);
+            }
+        }
+    }
+
+    fn slots(&self, hash: ZobristHash) -> impl Iterator<Item = Entry> + '_ {
+        let index = self.index(hash);
+        let slots = match self.policy {
+            ReplacementPolicy::AlwaysReplace => &self.entries[index..index + 1],
+            ReplacementPolicy::TwoTier => &self.entries[(index & !1)..(index & !1) + 2],
+        };
+
+        slots
+            .iter()
+            .map(Slot::read)
+            .filter(move |entry| entry.matches(hash))
+    }
+
+    fn index(&self, hash: ZobristHash) -> usize {
+        hash as usize & self.mask
+    }
+}
+
+fn verification_key(hash: ZobristHash) -> u64 {
+    (hash >> 64) as u64
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(u64::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod transposition_table_tests {
+    use std::sync::Arc;
+    use std::thread;
+
+    use crate::evaluator::Eval;
+    use crate::goban::Position;
+    use crate::transposition_table::{Bound, Entry, ReplacementPolicy, TranspositionTable};
+    use crate::zobrist_hashing::ZobristHash;
+
+    // Test hashes differing in both the index and the verification bits
+    fn key(n: u64) -> ZobristHash {
+        (n as ZobristHash) << 64 | n as ZobristHash
+    }
+
+    #[test]
+    fn it_is_consistent_under_concurrent_access() {
+        let table = Arc::new(TranspositionTable::with_size_mb(0, ReplacementPolicy::TwoTier));
+
+        let threads: Vec<_> = (0..4u64)
+            .map(|thread| {
+                let table = Arc::clone(&table);
+
+                thread::spawn(move || {
+                    for i in 0..20000u64 {
+                        let hash = (i % 16) * 0x1_0000_0001 + thread;
+                        let score = (hash % 1000) as isize;
+
+                        table.store(key(hash), (i % 7) as usize, Bound::Exact, Eval::Score(score), None);
+
+                        if let Some(entry) = table.probe_search(key(hash ^ 1), 0) {
+                            assert_eq!(entry.eval(), Eval::Score(((hash ^ 1) % 1000) as isize));
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn it_fits_in_the_memory_budget() {
+        let table = TranspositionTable::with_size_mb(1, ReplacementPolicy::TwoTier);
+
+        assert_eq!(std::mem::size_of::<Entry>(), 16);
+        assert_eq!(std::mem::size_of::<super::Slot>(), 16);
+        assert_eq!(table.entries.len(), 1024 * 1024 / 16);
+        assert_eq!(TranspositionTable::with_size_mb(0, ReplacementPolicy::TwoTier).entries.len(), 2);
+    }
+
+    #[test]
+    fn it_packs_and_unpacks_entries() {
+        let table = TranspositionTable::with_size_mb(0, ReplacementPolicy::AlwaysReplace);
+
+        for (bound, eval, best_move) in [
+            (Bound::Lower, Eval::Won, Some(Position::new(18, 18))),
+            (Bound::Upper, Eval::Lost, None),
+            (Bound::Exact, Eval::Score(-500000), Some(Position::new(0, 0))),
+        ] {
+            table.store(key(42), 3, bound, eval, best_move);
+
+            let entry = table.probe_search(key(42), 3).unwrap();
+
+            assert_eq!(entry.depth(), 3);
+            assert_eq!(entry.bound(), bound);
+            assert_eq!(entry.eval(), eval);
+            assert_eq!(entry.best_move(), best_move);
+        }
+    }
+
+    #[test]
+    fn it_verifies_the_upper_half_of_the_hash() {
+        let table = TranspositionTable::with_size_mb(1, ReplacementPolicy::AlwaysReplace);
+
+        table.store(key(42), 0, Bound::Exact, Eval::Score(10), None);
+
+        assert_eq!(table.probe_search(key(42) ^ 1 << 100, 0).map(|entry| entry.eval()), None);
+        assert_eq!(table.probe_search(key(42), 0).map(|entry| entry.eval()), Some(Eval::Score(10)));
+    }
+
+    #[test]
+    fn it_rejects_an_entry_with_a_different_verification_key() {
+        let table = TranspositionTable::with_size_mb(0, ReplacementPolicy::AlwaysReplace);
+
+        table.store(key(42), 0, Bound::Exact, Eval::Score(10), None);
+
+        assert_eq!(table.probe_search(key(42), 0).map(|entry| entry.eval()), Some(Eval::Score(10)));
+        assert_eq!(table.probe_search(key(44), 0).map(|entry| entry.eval()), None);
+
+        table.store(key(44), 0, Bound::Exact, Eval::Won, None);
+
+        assert_eq!(table.probe_search(key(42), 0).map(|entry| entry.eval()), None);
+        assert_eq!(table.probe_search(key(44), 0).map(|entry| entry.eval()), Some(Eval::Won));
+    }
+
+    #[test]
+    fn it_keeps_deep_entries_with_two_tiers() {
+        let table = TranspositionTable::with_size_mb(0, ReplacementPolicy::TwoTier);
+
+        table.store(key(42), 4, Bound::Exact, Eval::Score(100), None);
+        table.store(key(44), 0, Bound::Exact, Eval::Score(1), None);
+        table.store(key(46), 0, Bound::Exact, Eval::Score(2), None);
+
+        assert_eq!(table.probe_search(key(42), 3).map(|e| e.depth()), Some(4));
+        assert!(table.probe_search(key(42), 5).is_none());
+        assert_eq!(table.probe_search(key(44), 0).map(|entry| entry.eval()), None);
+        assert_eq!(table.probe_search(key(46), 0).map(|entry| entry.eval()), Some(Eval::Score(2)));
+
+        table.store(key(44), 6, Bound::Lower, Eval::Score(3), None);
+
+        assert!(table.probe_search(key(42), 1).is_none());
+        assert_eq!(table.probe_search(key(44), 6).map(|e| e.bound()), Some(Bound::Lower));
+    }
+
+    #[test]
+    fn it_clears_every_entry() {
+        let table = TranspositionTable::with_size_mb(1, ReplacementPolicy::TwoTier);
+
+        table.store(key(42), 3, Bound::Exact, Eval::Score(1), None);
+        table.clear();
+
+        assert!(table.probe_search(key(42), 0).is_none());
+    }
+
+    #[test]
+    fn it_estimates_the_fill_of_the_current_search() {
+        let table = TranspositionTable::with_size_mb(0, ReplacementPolicy::AlwaysReplace);
+
+        assert_eq!(table.hashfull(), 0);
+
+        table.store(key(0), 1, Bound::Exact, Eval::Score(1), None);
+        assert_eq!(table.hashfull(), 500);
+
+        table.new_search();
+        assert_eq!(table.hashfull(), 0);
+    }
+
+    #[test]
+    fn it_evicts_entries_from_previous_searches() {
+        let table = TranspositionTable::with_size_mb(0, ReplacementPolicy::TwoTier);
+
+        table.store(key(42), 8, Bound::Exact, Eval::Score(100), None);
+        table.store(key(44), 2, Bound::Exact, Eval::Score(1), None);
+
+        assert!(table.probe_search(key(42), 8).is_some());
+
+        table.new_search();
+        table.store(key(46), 2, Bound::Exact, Eval::Score(2), None);
+
+        assert!(table.probe_search(key(42), 1).is_none());
+        assert!(table.probe_search(key(44), 1).is_some());
+        assert!(table.probe_search(key(46), 1).is_some());
+    }
+
+    #[test]
+    fn it_saves_and_loads_entries() {
+        let table = TranspositionTable::with_size_mb(1, ReplacementPolicy::TwoTier);
+
+        table.store(key(42), 6, Bound::Upper, Eval::Score(-7), Some(Position::new(3, 4)));
+        table.store(key(4242), 0, Bound::Exact, Eval::Won, None);
+
+        let mut file = Vec::new();
+        table.save(&mut file, 1234).unwrap();
+
+        let loaded = TranspositionTable::with_size_mb(2, ReplacementPolicy::TwoTier);
+        assert!(loaded.load(&mut file.as_slice(), 4321).is_err());
+
+        loaded.load(&mut file.as_slice(), 1234).unwrap();
+
+        let entry = loaded.probe_search(key(42), 6).unwrap();
+        assert_eq!(entry.bound(), Bound::Upper);
+        assert_eq!(entry.eval(), Eval::Score(-7));
+        assert_eq!(entry.best_move(), Some(Position::new(3, 4)));
+        assert_eq!(loaded.probe_search(key(4242), 0).map(|entry| entry.eval()), Some(Eval::Won));
+    }
+}
@@ -1,28 +1,63 @@
+use std::sync::OnceLock;
+
 use rand::prelude::*;
 
 use crate::goban::{GOBAN_SIZE, GOBAN_TOTAL_SIZE, Move, Stone};
 
-pub type ZobristHash = u64;
+/// 128 bits so the transposition table can verify entries on bits not used to index them
+pub type ZobristHash = u128;
 
 pub const INITIAL_BOARD_HASH: ZobristHash = 0;
 
+/// Seed of the engine's keys, hashes built from it are stable across runs and machines
+pub const DEFAULT_SEED: u64 = 0x676F_6D6F_6B75;
+
 // Either Black or White
 const STONE_COLORS: usize = 2;
 
-type ZobristTable = [[u64; STONE_COLORS]; GOBAN_TOTAL_SIZE];
+type ZobristTable = [[ZobristHash; STONE_COLORS]; GOBAN_TOTAL_SIZE];
+
+static HASHER: OnceLock<ZobristHasher> = OnceLock::new();
+
+/// Keys shared by every board, derived from `DEFAULT_SEED` unless `seed` was called first
+pub fn hasher() -> &'static ZobristHasher {
+    HASHER.get_or_init(|| ZobristHasher::with_seed(DEFAULT_SEED))
+}
+
+/// Picks the seed of the shared keys, which is only possible before any board was hashed
+pub fn seed(seed: u64) -> Result<(), &'static str> {
+    HASHER
+        .set(ZobristHasher::with_seed(seed))
+        .map_err(|_| "zobrist keys are already initialized")
+}
 
 pub struct ZobristHasher {
     table: ZobristTable,
+    white_to_move: ZobristHash,
 }
 
 impl ZobristHasher {
+    /// Random keys, different for every process
     pub fn initialize() -> Self {
+        Self::with_seed(random())
+    }
+
+    /// Keys derived from `seed` only, so hashes can be shared through files between runs
+    pub fn with_seed(seed: u64) -> Self {
+        let mut state = seed;
+
         Self {
-            table: initialize_zobrist_table()
+            table: initialize_zobrist_table(&mut state),
+            white_to_move: random_key(&mut state),
         }
     }
 
-    pub fn update_hash(&self, hash: u64, played_move: Move) -> ZobristHash {
+    /// Xored in when the side to move changes, the key is present while White is to move
+    pub fn toggle_side(&self, hash: ZobristHash) -> ZobristHash {
+        hash ^ self.white_to_move
+    }
+
+    pub fn update_hash(&self, hash: ZobristHash, played_move: Move) -> ZobristHash {
         let stone_index = match played_move.stone {
             Stone::Black => 0,
             Stone::White => 1,
@@ -30,30 +65,52 @@ impl ZobristHasher {
 
         let position_index = (played_move.position.row * GOBAN_SIZE) + played_move.position.col;
 
-        hash ^ self.table[position_index][stone_index] as u64
+        hash ^ self.table[position_index][stone_index]
+    }
+
+    /// Identifies the keys, hashes are only comparable between hashers sharing a fingerprint
+    pub fn fingerprint(&self) -> u64 {
+        self.table
+            .iter()
+            .flatten()
+            .fold(self.white_to_move, |fingerprint, key| fingerprint.rotate_left(7) ^ key) as u64
     }
 }
 
-fn initialize_zobrist_table() -> ZobristTable {
+fn initialize_zobrist_table(state: &mut u64) -> ZobristTable {
     let mut table: ZobristTable = [[0; STONE_COLORS]; GOBAN_TOTAL_SIZE];
 
     for stone in table.iter_mut() {
         for color in stone.iter_mut() {
-            *color = random();
+            *color = random_key(state);
         }
     }
 
     table
 }
 
+fn random_key(state: &mut u64) -> ZobristHash {
+    (split_mix(state) as ZobristHash) << 64 | split_mix(state) as ZobristHash
+}
+
+// SplitMix64, fully specified so a seed gives the same keys with any rand version
+fn split_mix(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+    z ^ (z >> 31)
+}
+
 #[cfg(test)]
 mod zobrist_hash_tests {
-    use std::rc::Rc;
     use crate::goban::{Goban, Move, Position, Stone};
-    use crate::zobrist_hashing::{ZobristHash, ZobristHasher};
+    use crate::zobrist_hashing::{DEFAULT_SEED, hasher, INITIAL_BOARD_HASH, ZobristHash, ZobristHasher};
 
-    fn compute_hash_from_moves(zobrist: Rc<ZobristHasher>, moves: Vec<Move>) -> ZobristHash {
-        let mut goban = Goban::new(zobrist);
+    fn compute_hash_from_moves(moves: Vec<Move>) -> ZobristHash {
+        let mut goban = Goban::new();
 
         moves.into_iter().for_each(|m| goban.apply_move(m));
 
@@ -62,8 +119,6 @@ mod zobrist_hash_tests {
 
     #[test]
     fn it_correctly_hash_a_goban() {
-        let zobrist_hasher = Rc::new(ZobristHasher::initialize());
-
         let ordered_moves = vec![
             Move::new(Stone::Black, Position::new(5, 5)),
             Move::new(Stone::White, Position::new(5, 6)),
@@ -78,9 +133,63 @@ mod zobrist_hash_tests {
             Move::new(Stone::White, Position::new(5, 8)),
         ];
 
-        let ordered_hash = compute_hash_from_moves(Rc::clone(&zobrist_hasher), ordered_moves);
-        let reversed_hash = compute_hash_from_moves(Rc::clone(&zobrist_hasher), reversed_moves);
+        let ordered_hash = compute_hash_from_moves(ordered_moves);
+        let reversed_hash = compute_hash_from_moves(reversed_moves);
 
         assert_eq!(ordered_hash, reversed_hash);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn it_restores_the_hash_when_clearing_stones() {
+        let mut goban = Goban::new();
+
+        goban.apply_move(Move::new(Stone::Black, Position::new(5, 5)));
+        let hash = goban.get_hash();
+
+        goban.apply_move(Move::new(Stone::White, Position::new(5, 6)));
+        assert_eq!(goban.clear(&Position::new(5, 6)), Some(Stone::White));
+        goban.set_side_to_move(Stone::White);
+        assert_eq!(goban.get_hash(), hash);
+
+        goban.apply_move(Move::new(Stone::White, Position::new(5, 5)));
+        goban.apply_move(Move::new(Stone::Black, Position::new(5, 5)));
+        assert_eq!(goban.get_hash(), hash);
+
+        assert_eq!(goban.clear(&Position::new(5, 5)), Some(Stone::Black));
+        assert_eq!(goban.clear(&Position::new(5, 5)), None);
+        goban.set_side_to_move(Stone::Black);
+        assert_eq!(goban.get_hash(), INITIAL_BOARD_HASH);
+    }
+
+    #[test]
+    fn it_hashes_the_side_to_move() {
+        let white_to_move = compute_hash_from_moves(vec![
+            Move::new(Stone::White, Position::new(5, 6)),
+            Move::new(Stone::Black, Position::new(5, 5)),
+        ]);
+
+        let black_to_move = compute_hash_from_moves(vec![
+            Move::new(Stone::Black, Position::new(5, 5)),
+            Move::new(Stone::White, Position::new(5, 6)),
+        ]);
+
+        assert_ne!(white_to_move, black_to_move);
+        assert_eq!(hasher().toggle_side(white_to_move), black_to_move);
+    }
+
+    #[test]
+    fn it_derives_the_same_keys_from_a_seed() {
+        let first = ZobristHasher::with_seed(42);
+        let second = ZobristHasher::with_seed(42);
+
+        assert_eq!(first.fingerprint(), second.fingerprint());
+        assert_ne!(first.fingerprint(), ZobristHasher::with_seed(43).fingerprint());
+
+        // Pinned so a change of the key derivation, which invalidates saved files, is noticed
+        let played = Move::new(Stone::Black, Position::new(0, 0));
+        assert_eq!(
+            ZobristHasher::with_seed(DEFAULT_SEED).update_hash(INITIAL_BOARD_HASH, played),
+            0xAF07_26DD_FAA3_E15E_B8D0_4381_2FBE_6EF7
+        );
+    }
+}